@@ -0,0 +1,42 @@
+extern crate libspartan;
+
+use libspartan::bench_internals::MsmInput;
+use multiexp::multiexp;
+
+use criterion::*;
+
+// The membership circuits pad to 2^13 variables, so their commitments and
+// inner product arguments run multi-scalar multiplications of at most a few
+// hundred terms; the largest size stands in for the merged checks of
+// batch verification.
+const SIZES: [usize; 7] = [2, 8, 32, 64, 128, 256, 4096];
+
+fn msm_benchmark(c: &mut Criterion) {
+  let mut group = c.benchmark_group("MSM_benchmark");
+
+  for &n in SIZES.iter() {
+    let input = MsmInput::random(n);
+
+    group.bench_function(format!("pippenger_{}", n), |b| {
+      b.iter(|| black_box(&input).multiscalar_mul());
+    });
+
+    // the previous implementation: convert every scalar, then call multiexp
+    group.bench_function(format!("multiexp_{}", n), |b| {
+      b.iter(|| multiexp(&black_box(&input).to_multiexp_pairs()));
+    });
+  }
+  group.finish();
+}
+
+fn set_duration() -> Criterion {
+  Criterion::default().sample_size(10)
+}
+
+criterion_group! {
+name = benches_msm;
+config = set_duration();
+targets = msm_benchmark
+}
+
+criterion_main!(benches_msm);
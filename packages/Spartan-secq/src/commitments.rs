@@ -1,15 +1,28 @@
-use super::group::{GroupElement, VartimeMultiscalarMul};
-use super::scalar::Scalar;
+use super::group::{
+  normalize_batch, CompressedGroup, CompressedGroupExt, GroupElement, ProjectiveGroupElement,
+  VartimeMultiscalarMul,
+};
+use super::msm::FixedBaseTable;
+use super::scalar::{Scalar, ScalarBytesFromScalar};
+use core::iter;
 use digest::{ExtendableOutput, Input};
+use once_cell::sync::OnceCell;
 use secq256k1::AffinePoint;
+use serde::de::Error as DeError;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha3::Shake256;
 use std::io::Read;
+use zeroize::Zeroizing;
 
 #[derive(Debug)]
 pub struct MultiCommitGens {
   pub n: usize,
   pub G: Vec<GroupElement>,
   pub h: GroupElement,
+  /// Fixed-base table over `G` followed by `h`, used by commitments in place
+  /// of a variable-base multiplication. It is built on the first commitment,
+  /// so gens that never commit, such as a verifier's, go without it.
+  pub table: OnceCell<FixedBaseTable>,
 }
 
 impl MultiCommitGens {
@@ -30,67 +43,176 @@ impl MultiCommitGens {
       n,
       G: gens[..n].to_vec(),
       h: gens[n],
+      table: OnceCell::new(),
     }
   }
 
+  /// The fixed-base table, built on first use; it takes about 256 doublings
+  /// and stores a point per window for each base.
+  fn table(&self) -> &FixedBaseTable {
+    self.table.get_or_init(|| {
+      let bases = self
+        .G
+        .iter()
+        .chain(iter::once(&self.h))
+        .copied()
+        .collect::<Vec<_>>();
+      FixedBaseTable::new(&bases)
+    })
+  }
+
   pub fn clone(&self) -> MultiCommitGens {
     MultiCommitGens {
       n: self.n,
       h: self.h,
       G: self.G.clone(),
+      table: self.table.clone(),
     }
   }
 
+  /// Returns `r * h`, the blinding term of a commitment.
+  pub fn blind(&self, r: &Scalar) -> GroupElement {
+    AffinePoint(self.table().mul(self.n, r).to_affine())
+  }
+
   pub fn scale(&self, s: &Scalar) -> MultiCommitGens {
+    let s = Scalar::decompress_scalar(s);
+    let G = self
+      .G
+      .iter()
+      .map(|g| ProjectiveGroupElement::from(g.0) * s)
+      .collect::<Vec<_>>();
+
     MultiCommitGens {
       n: self.n,
       h: self.h,
-      G: (0..self.n).map(|i| s * self.G[i]).collect(),
+      G: normalize_batch(&G),
+      table: OnceCell::new(),
     }
   }
 
   pub fn split_at(&self, mid: usize) -> (MultiCommitGens, MultiCommitGens) {
     let (G1, G2) = self.G.split_at(mid);
+    // both halves keep `h` as the last base of their table, which is taken
+    // from ours if it is already built
+    let (table1, table2) = match self.table.get() {
+      Some(table) => (
+        OnceCell::with_value(table.select((0..mid).chain(iter::once(self.n)))),
+        OnceCell::with_value(table.select(mid..=self.n)),
+      ),
+      None => (OnceCell::new(), OnceCell::new()),
+    };
 
     (
       MultiCommitGens {
         n: G1.len(),
         G: G1.to_vec(),
         h: self.h,
+        table: table1,
       },
       MultiCommitGens {
         n: G2.len(),
         G: G2.to_vec(),
         h: self.h,
+        table: table2,
       },
     )
   }
 }
 
+// Generators are serialized as compressed points so that loading them only
+// costs a decompression per point instead of a hash-to-curve.
+impl Serialize for MultiCommitGens {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    let G: Vec<CompressedGroup> = self.G.iter().map(|g| g.compress()).collect();
+    (self.n, G, self.h.compress()).serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for MultiCommitGens {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let (n, G, h): (usize, Vec<CompressedGroup>, CompressedGroup) =
+      Deserialize::deserialize(deserializer)?;
+    if G.len() != n {
+      return Err(D::Error::invalid_length(G.len(), &"n generators"));
+    }
+
+    let G = G
+      .iter()
+      .map(|g| g.unpack())
+      .collect::<Result<Vec<GroupElement>, _>>()
+      .map_err(D::Error::custom)?;
+    let h = h.unpack().map_err(D::Error::custom)?;
+
+    Ok(MultiCommitGens {
+      n,
+      G,
+      h,
+      table: OnceCell::new(),
+    })
+  }
+}
+
 pub trait Commitments {
   fn commit(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement;
+
+  /// `commit` in variable time and without building the table, for the
+  /// public values a verifier commits to
+  fn commit_vartime(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement;
 }
 
 impl Commitments for Scalar {
   fn commit(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement {
     assert_eq!(gens_n.n, 1);
-    GroupElement::vartime_multiscalar_mul(
-      [*self, *blind].to_vec(),
-      [gens_n.G[0], gens_n.h].to_vec(),
-    )
+    [*self].commit(blind, gens_n)
+  }
+
+  fn commit_vartime(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement {
+    assert_eq!(gens_n.n, 1);
+    [*self].commit_vartime(blind, gens_n)
   }
 }
 
 impl Commitments for Vec<Scalar> {
   fn commit(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement {
-    assert_eq!(gens_n.n, self.len());
-    GroupElement::vartime_multiscalar_mul((*self).clone(), gens_n.G.clone()) + blind * gens_n.h
+    self.as_slice().commit(blind, gens_n)
+  }
+
+  fn commit_vartime(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement {
+    self.as_slice().commit_vartime(blind, gens_n)
   }
 }
 
 impl Commitments for [Scalar] {
   fn commit(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement {
     assert_eq!(gens_n.n, self.len());
-    GroupElement::vartime_multiscalar_mul(self.to_vec(), gens_n.G.clone()) + blind * gens_n.h
+    // committed values and blinds are secret, so the table multiplies in
+    // constant time; the blinding term goes into the same multiplication, so
+    // the result is normalized once
+    let scalars = Zeroizing::new(
+      self
+        .iter()
+        .chain(iter::once(blind))
+        .copied()
+        .collect::<Vec<Scalar>>(),
+    );
+    AffinePoint(gens_n.table().multiscalar_mul(&scalars).to_affine())
+  }
+
+  fn commit_vartime(&self, blind: &Scalar, gens_n: &MultiCommitGens) -> GroupElement {
+    assert_eq!(gens_n.n, self.len());
+    GroupElement::vartime_multiscalar_mul(
+      &self
+        .iter()
+        .chain(iter::once(blind))
+        .copied()
+        .collect::<Vec<Scalar>>(),
+      &gens_n
+        .G
+        .iter()
+        .chain(iter::once(&gens_n.h))
+        .copied()
+        .collect::<Vec<GroupElement>>(),
+    )
   }
 }
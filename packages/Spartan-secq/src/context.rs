@@ -0,0 +1,61 @@
+use merlin::Transcript;
+
+/// `ProofContext` identifies the application a proof is made for, e.g. a
+/// domain label together with a chain id, an external nullifier or any other
+/// message. Proofs made with `ProofContext::transcript` only verify against a
+/// transcript built from the same context, so a proof cannot be replayed in
+/// another application that uses the same circuit and public inputs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProofContext {
+  domain: Vec<u8>,
+  entries: Vec<(&'static [u8], Vec<u8>)>,
+}
+
+impl ProofContext {
+  /// Creates a context for the application identified by `domain`
+  pub fn new(domain: &[u8]) -> Self {
+    ProofContext {
+      domain: domain.to_vec(),
+      entries: Vec::new(),
+    }
+  }
+
+  /// Binds `data` under `label`; entries are absorbed in the order they are added
+  pub fn with(mut self, label: &'static [u8], data: &[u8]) -> Self {
+    self.entries.push((label, data.to_vec()));
+    self
+  }
+
+  /// Binds the id of the chain the proof is meant for
+  pub fn with_chain_id(self, chain_id: u64) -> Self {
+    self.with(b"chain_id", &chain_id.to_le_bytes())
+  }
+
+  /// Binds an external nullifier, e.g. the id of a poll or an airdrop
+  pub fn with_external_nullifier(self, nullifier: &[u8]) -> Self {
+    self.with(b"external_nullifier", nullifier)
+  }
+
+  /// Binds an arbitrary message, e.g. the vote or transaction the proof endorses
+  pub fn with_message(self, message: &[u8]) -> Self {
+    self.with(b"message", message)
+  }
+
+  /// Returns a fresh transcript that has absorbed the context, to be passed to
+  /// both `prove` and `verify`
+  pub fn transcript(&self) -> Transcript {
+    let mut transcript = Transcript::new(b"Spartan proof context");
+    self.append_to_transcript(&mut transcript);
+    transcript
+  }
+
+  /// Absorbs the context into `transcript`
+  pub fn append_to_transcript(&self, transcript: &mut Transcript) {
+    transcript.append_message(b"domain", &self.domain);
+    for (label, data) in &self.entries {
+      transcript.append_message(label, data);
+    }
+    // closes the context, so that it is never a prefix of another one
+    transcript.append_u64(b"context_len", self.entries.len() as u64);
+  }
+}
@@ -1,27 +1,32 @@
 #![allow(clippy::too_many_arguments)]
 use super::commitments::{Commitments, MultiCommitGens};
 use super::errors::ProofVerifyError;
-use super::group::{CompressedGroup, GroupElement, VartimeMultiscalarMul};
+use super::group::{
+  CompressedGroup, CompressedGroupExt, GroupElement, MultiScalarCheck, VartimeMultiscalarMul,
+};
+use super::layout::LayoutReader;
 use super::math::Math;
 use super::nizk::{DotProductProofGens, DotProductProofLog};
 use super::random::RandomTape;
 use super::scalar::Scalar;
 use super::transcript::{AppendToTranscript, ProofTranscript};
-use crate::group::DecompressEncodedPoint;
 use core::ops::Index;
 use merlin::Transcript;
 use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 #[cfg(feature = "multicore")]
 use rayon::prelude::*;
 
-#[derive(Debug)]
+// polynomials over the witness hold secrets, so every polynomial is wiped on drop
+#[derive(Debug, Zeroize, ZeroizeOnDrop)]
 pub struct DensePolynomial {
   num_vars: usize, // the number of variables in the multilinear polynomial
   len: usize,
   Z: Vec<Scalar>, // evaluations of the polynomial in all the 2^num_vars Boolean inputs
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct PolyCommitmentGens {
   pub gens: DotProductProofGens,
 }
@@ -36,7 +41,7 @@ impl PolyCommitmentGens {
 }
 
 pub struct PolyCommitmentBlinds {
-  blinds: Vec<Scalar>,
+  blinds: Zeroizing<Vec<Scalar>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -196,7 +201,7 @@ impl DensePolynomial {
       }
     } else {
       PolyCommitmentBlinds {
-        blinds: vec![Scalar::zero(); L_size],
+        blinds: Zeroizing::new(vec![Scalar::zero(); L_size]),
       }
     };
 
@@ -287,6 +292,14 @@ impl Index<usize> for DensePolynomial {
   }
 }
 
+impl PolyCommitment {
+  // the number of variables in the committed multilinear polynomial
+  pub fn check_layout(reader: &mut LayoutReader, num_vars: usize) -> Result<(), ProofVerifyError> {
+    let (left_num_vars, _right_num_vars) = EqPolynomial::compute_factored_lens(num_vars);
+    reader.point_vec(left_num_vars.pow2())
+  }
+}
+
 impl AppendToTranscript for PolyCommitment {
   fn append_to_transcript(&self, label: &'static [u8], transcript: &mut Transcript) {
     transcript.append_message(label, b"poly_commitment_begin");
@@ -327,7 +340,7 @@ impl PolyEvalProof {
     let R_size = right_num_vars.pow2();
 
     let default_blinds = PolyCommitmentBlinds {
-      blinds: vec![Scalar::zero(); L_size],
+      blinds: Zeroizing::new(vec![Scalar::zero(); L_size]),
     };
     let blinds = blinds_opt.map_or(&default_blinds, |p| p);
 
@@ -362,6 +375,12 @@ impl PolyEvalProof {
     (PolyEvalProof { proof }, C_Zr_prime)
   }
 
+  // the number of variables in the polynomial being evaluated
+  pub fn check_layout(reader: &mut LayoutReader, num_vars: usize) -> Result<(), ProofVerifyError> {
+    let (_left_num_vars, right_num_vars) = EqPolynomial::compute_factored_lens(num_vars);
+    DotProductProofLog::check_layout(reader, right_num_vars.pow2())
+  }
+
   pub fn verify(
     &self,
     gens: &PolyCommitmentGens,
@@ -370,20 +389,46 @@ impl PolyEvalProof {
     C_Zr: &CompressedGroup, // commitment to \widetilde{Z}(r)
     comm: &PolyCommitment,
   ) -> Result<(), ProofVerifyError> {
+    self
+      .verify_deferred(gens, transcript, r, C_Zr, comm)?
+      .verify()
+  }
+
+  /// Runs the verifier up to the final check of the inner product argument,
+  /// which is returned rather than evaluated
+  pub fn verify_deferred(
+    &self,
+    gens: &PolyCommitmentGens,
+    transcript: &mut Transcript,
+    r: &[Scalar],
+    C_Zr: &CompressedGroup,
+    comm: &PolyCommitment,
+  ) -> Result<MultiScalarCheck, ProofVerifyError> {
     transcript.append_protocol_name(PolyEvalProof::protocol_name());
 
     // compute L and R
     let eq = EqPolynomial::new(r.to_vec());
     let (L, R) = eq.compute_factored_evals();
 
+    // the commitment must have one row per entry of L
+    if comm.C.len() != L.len() {
+      return Err(ProofVerifyError::MalformedProof(
+        "polynomial commitment has the wrong number of rows",
+      ));
+    }
+
     // compute a weighted sum of commitments and L
-    let C_decompressed = comm.C.iter().map(|pt| pt.decompress().unwrap());
+    let C_decompressed = comm
+      .C
+      .iter()
+      .map(|pt| pt.unpack())
+      .collect::<Result<Vec<GroupElement>, ProofVerifyError>>()?;
 
-    let C_LZ = GroupElement::vartime_multiscalar_mul(L, C_decompressed.collect()).compress();
+    let C_LZ = GroupElement::vartime_multiscalar_mul(&L, &C_decompressed).compress();
 
     self
       .proof
-      .verify(R.len(), &gens.gens, transcript, &R, &C_LZ, C_Zr)
+      .verify_deferred(R.len(), &gens.gens, transcript, &R, &C_LZ, C_Zr)
   }
 
   pub fn verify_plain(
@@ -395,7 +440,9 @@ impl PolyEvalProof {
     comm: &PolyCommitment,
   ) -> Result<(), ProofVerifyError> {
     // compute a commitment to Zr with a blind of zero
-    let C_Zr = Zr.commit(&Scalar::zero(), &gens.gens.gens_1).compress();
+    let C_Zr = Zr
+      .commit_vartime(&Scalar::zero(), &gens.gens.gens_1)
+      .compress();
 
     self.verify(gens, transcript, r, &C_Zr, comm)
   }
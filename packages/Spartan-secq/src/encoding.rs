@@ -0,0 +1,127 @@
+//! Canonical byte encoding of `NIZK` and `SNARK` proofs.
+//!
+//! Version 1 of the encoding is a fixed 43-byte header followed by the body:
+//!
+//! | offset | size | field                                               |
+//! |--------|------|-----------------------------------------------------|
+//! | 0      | 4    | magic, the ASCII bytes `SPRF`                       |
+//! | 4      | 4    | format version, little-endian `u32` (currently 1)   |
+//! | 8      | 2    | curve id, little-endian `u16` (1 = secq256k1)       |
+//! | 10     | 1    | proof kind (1 = `NIZK`, 2 = `SNARK`)                |
+//! | 11     | 32   | SHA3-256 digest of the statement the proof is for   |
+//! | 43     | ...  | body                                                |
+//!
+//! For a `NIZK` the digest is taken over the instance digest that is also
+//! absorbed into the transcript; for a `SNARK` it is taken over the bincode
+//! encoding of the computation commitment.
+//!
+//! The body is the sequence of scalars and group elements of the proof, in
+//! the order the verifier consumes them (the `check_layout` functions spell
+//! this order out for every component). Each scalar is 32 bytes, little-endian
+//! and fully reduced; each group element is a 33-byte SEC1 compressed point,
+//! with the identity written as 33 zero bytes. Nothing else is stored: the
+//! length of every vector follows from the dimensions of the instance, so the
+//! total size of a proof is fixed once the instance is known.
+//!
+//! Bytes that do not start with the magic are decoded as the bincode
+//! serialization of the proof structs that earlier releases produced
+//! (version 0), with the same bounds checks.
+use super::errors::ProofVerifyError;
+use super::layout::{Format, LayoutReader};
+use serde::de::DeserializeOwned;
+use sha3::{Digest, Sha3_256};
+
+const PROOF_MAGIC: [u8; 4] = *b"SPRF";
+const PROOF_FORMAT_VERSION: u32 = 1;
+const CURVE_ID_SECQ256K1: u16 = 1;
+const DIGEST_BYTES: usize = 32;
+const HEADER_BYTES: usize = 4 + 4 + 2 + 1 + DIGEST_BYTES;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProofKind {
+  Nizk = 1,
+  Snark = 2,
+}
+
+pub fn statement_digest(bytes: &[u8]) -> [u8; DIGEST_BYTES] {
+  let mut digest = [0u8; DIGEST_BYTES];
+  digest.copy_from_slice(&Sha3_256::digest(bytes));
+  digest
+}
+
+/// Encodes a proof given its bincode serialization, using `layout` to walk
+/// the components in order.
+pub fn encode_proof<F>(
+  kind: ProofKind,
+  digest: &[u8; DIGEST_BYTES],
+  legacy: &[u8],
+  layout: F,
+) -> Vec<u8>
+where
+  F: FnOnce(&mut LayoutReader) -> Result<(), ProofVerifyError>,
+{
+  let mut reader = LayoutReader::transcode(legacy, Format::Bincode, Format::Canonical);
+  layout(&mut reader).expect("proof does not match its own layout");
+  let body = reader.into_output().unwrap();
+
+  let mut bytes = Vec::with_capacity(HEADER_BYTES + body.len());
+  bytes.extend_from_slice(&PROOF_MAGIC);
+  bytes.extend_from_slice(&PROOF_FORMAT_VERSION.to_le_bytes());
+  bytes.extend_from_slice(&CURVE_ID_SECQ256K1.to_le_bytes());
+  bytes.push(kind as u8);
+  bytes.extend_from_slice(digest);
+  bytes.extend_from_slice(&body);
+  bytes
+}
+
+/// Decodes a proof in either the canonical or the legacy bincode encoding.
+/// `layout` must check the components the proof has for the statement at hand.
+pub fn decode_proof<T, F>(
+  bytes: &[u8],
+  kind: ProofKind,
+  digest: &[u8; DIGEST_BYTES],
+  layout: F,
+) -> Result<T, ProofVerifyError>
+where
+  T: DeserializeOwned,
+  F: FnOnce(&mut LayoutReader) -> Result<(), ProofVerifyError>,
+{
+  if !bytes.starts_with(&PROOF_MAGIC) {
+    let mut reader = LayoutReader::new(bytes);
+    layout(&mut reader)?;
+    reader.finish()?;
+    return deserialize(bytes);
+  }
+
+  if bytes.len() < HEADER_BYTES {
+    return Err(ProofVerifyError::MalformedProof("proof is truncated"));
+  }
+  let (header, body) = bytes.split_at(HEADER_BYTES);
+
+  let version = u32::from_le_bytes(header[4..8].try_into().unwrap());
+  if version != PROOF_FORMAT_VERSION {
+    return Err(ProofVerifyError::UnsupportedVersion(version));
+  }
+  if u16::from_le_bytes(header[8..10].try_into().unwrap()) != CURVE_ID_SECQ256K1 {
+    return Err(ProofVerifyError::MalformedProof(
+      "proof is over a different curve",
+    ));
+  }
+  if header[10] != kind as u8 {
+    return Err(ProofVerifyError::MalformedProof(
+      "proof is of a different kind",
+    ));
+  }
+  if header[11..] != digest[..] {
+    return Err(ProofVerifyError::StatementMismatch);
+  }
+
+  let mut reader = LayoutReader::transcode(body, Format::Canonical, Format::Bincode);
+  layout(&mut reader)?;
+  deserialize(&reader.into_output()?)
+}
+
+fn deserialize<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ProofVerifyError> {
+  bincode::deserialize(bytes)
+    .map_err(|_| ProofVerifyError::MalformedProof("proof failed to deserialize"))
+}
@@ -1,12 +1,52 @@
 use core::fmt::Debug;
 use thiserror::Error;
 
+/// Errors returned when a proof fails to verify
 #[derive(Error, Debug)]
 pub enum ProofVerifyError {
+  /// returned if verification fails for a reason not covered by a more specific variant
   #[error("Proof verification failed")]
   InternalError,
+  /// returned if a compressed group element in the proof is not a valid point
   #[error("Compressed group element failed to decompress: {0:?}")]
-  DecompressionError([u8; 32]),
+  DecompressionError(Vec<u8>),
+  /// returned if the supplied public input does not match the instance
+  #[error("Input length mismatch: expected {expected}, got {actual}")]
+  InputLengthMismatch {
+    /// number of inputs the instance expects
+    expected: usize,
+    /// number of inputs that were supplied
+    actual: usize,
+  },
+  /// returned if a proof component has the wrong number of elements
+  #[error("Malformed proof: {0}")]
+  MalformedProof(&'static str),
+  /// returned if a round of a sum-check protocol does not verify
+  #[error("Sum-check verification failed in round {0}")]
+  SumcheckRoundFailure(usize),
+  /// returned if a sigma protocol or inner product argument does not verify
+  #[error("Sub-proof verification failed: {0}")]
+  SubProofFailure(&'static str),
+  /// returned if a claimed evaluation differs from the value the verifier computes
+  #[error("Claimed evaluation does not match the expected value")]
+  EvaluationMismatch,
+  /// returned if the point claimed by the prover differs from the one in the transcript
+  #[error("Claimed point does not match the point derived from the transcript")]
+  TranscriptPointMismatch,
+  /// returned if an encoded proof has a format version this build cannot read
+  #[error("Unsupported proof format version: {0}")]
+  UnsupportedVersion(u32),
+  /// returned if an encoded proof was produced for a different statement
+  #[error("Proof was produced for a different statement")]
+  StatementMismatch,
+  /// returned by batch verification to identify the proof that failed
+  #[error("Proof {index} of the batch failed to verify: {error}")]
+  BatchFailure {
+    /// position of the failing proof in the batch
+    index: usize,
+    /// why the proof failed
+    error: Box<ProofVerifyError>,
+  },
 }
 
 impl Default for ProofVerifyError {
@@ -15,6 +55,7 @@ impl Default for ProofVerifyError {
   }
 }
 
+/// Errors returned when constructing an R1CS instance or assignment
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum R1CSError {
   /// returned if the number of constraints is not a power of 2
@@ -30,3 +71,20 @@ pub enum R1CSError {
   /// returned if the supplied row or col in (row,col,val) tuple is out of range
   InvalidIndex,
 }
+
+/// Errors returned when decoding serialized public parameters
+#[derive(Error, Debug)]
+pub enum GensError {
+  /// returned if the bytes do not start with the public parameters magic
+  #[error("Public parameters have an invalid magic")]
+  InvalidMagic,
+  /// returned if the format version is not supported by this build
+  #[error("Unsupported public parameters format version: {0}")]
+  UnsupportedVersion(u32),
+  /// returned if the header label or sizes do not match the requested parameters
+  #[error("Public parameters were generated for a different statement")]
+  HeaderMismatch,
+  /// returned if the body fails to deserialize or has trailing bytes
+  #[error("Malformed public parameters: {0}")]
+  Malformed(String),
+}
@@ -1,11 +1,17 @@
+use secq256k1::affine::{AffinePointCore, Group};
+use secq256k1::elliptic_curve::group::Curve;
 use secq256k1::{AffinePoint, ProjectivePoint};
 
 use super::errors::ProofVerifyError;
-use super::scalar::{Scalar, ScalarBytes, ScalarBytesFromScalar};
+use super::msm;
+use super::scalar::{Scalar, ScalarBytesFromScalar};
 use core::ops::{Mul, MulAssign};
-use multiexp::multiexp;
 
 pub type GroupElement = secq256k1::AffinePoint;
+/// Intermediate results are kept in projective coordinates, where additions
+/// and scalar multiplications need no field inversion; they are converted to
+/// `GroupElement` only when a point is compressed or stored.
+pub type ProjectiveGroupElement = ProjectivePoint;
 pub type CompressedGroup = secq256k1::EncodedPoint;
 pub trait CompressedGroupExt {
   type Group;
@@ -20,7 +26,7 @@ impl CompressedGroupExt for CompressedGroup {
       return Ok(result.unwrap());
     } else {
       Err(ProofVerifyError::DecompressionError(
-        (*self.to_bytes()).try_into().unwrap(),
+        self.as_bytes().to_vec(),
       ))
     }
   }
@@ -32,7 +38,7 @@ pub trait DecompressEncodedPoint {
 
 impl DecompressEncodedPoint for CompressedGroup {
   fn decompress(&self) -> Option<GroupElement> {
-    Some(self.unpack().unwrap())
+    self.unpack().ok()
   }
 }
 
@@ -97,26 +103,108 @@ define_mul_assign_variants!(LHS = GroupElement, RHS = Scalar);
 define_mul_variants!(LHS = GroupElement, RHS = Scalar, Output = GroupElement);
 define_mul_variants!(LHS = Scalar, RHS = GroupElement, Output = GroupElement);
 
-pub trait VartimeMultiscalarMul {
+pub trait VartimeMultiscalarMul: Sized {
   type Scalar;
-  fn vartime_multiscalar_mul(scalars: Vec<Scalar>, points: Vec<GroupElement>) -> Self;
+  fn vartime_multiscalar_mul(scalars: &[Scalar], points: &[Self]) -> Self;
+}
+
+impl VartimeMultiscalarMul for ProjectiveGroupElement {
+  type Scalar = super::scalar::Scalar;
+  fn vartime_multiscalar_mul(scalars: &[Scalar], points: &[ProjectiveGroupElement]) -> Self {
+    msm::multiscalar_mul(scalars, points)
+  }
 }
 
 impl VartimeMultiscalarMul for GroupElement {
   type Scalar = super::scalar::Scalar;
-  // TODO Borrow the arguments so we don't have to clone them, as it was in the original implementation
-  fn vartime_multiscalar_mul(scalars: Vec<Scalar>, points: Vec<GroupElement>) -> Self {
-    let points: Vec<ProjectivePoint> = points.iter().map(|p| ProjectivePoint::from(p.0)).collect();
-
-    let pairs: Vec<(ScalarBytes, ProjectivePoint)> = scalars
-      .into_iter()
-      .enumerate()
-      .map(|(i, s)| (Scalar::decompress_scalar(&s), points[i]))
-      .collect();
+  fn vartime_multiscalar_mul(scalars: &[Scalar], points: &[GroupElement]) -> Self {
+    let points: Vec<ProjectiveGroupElement> =
+      points.iter().map(|p| ProjectivePoint::from(p.0)).collect();
+    AffinePoint(msm::multiscalar_mul(scalars, &points).to_affine())
+  }
+}
+
+/// Converts points to affine form, sharing one field inversion among all of them.
+pub fn normalize_batch(points: &[ProjectiveGroupElement]) -> Vec<GroupElement> {
+  let mut affine = vec![AffinePointCore::default(); points.len()];
+  ProjectiveGroupElement::batch_normalize(points, &mut affine);
+  affine.into_iter().map(AffinePoint).collect()
+}
+
+pub fn compress_batch(points: &[ProjectiveGroupElement]) -> Vec<CompressedGroup> {
+  normalize_batch(points)
+    .iter()
+    .map(|p| p.compress())
+    .collect()
+}
+
+/// A check that `sum_i scalars[i] * points[i]` is the identity, kept around
+/// instead of being evaluated so that checks from many proofs can be merged
+/// into a single multi-scalar multiplication.
+pub struct MultiScalarCheck {
+  label: &'static str,
+  scalars: Vec<Scalar>,
+  points: Vec<GroupElement>,
+}
+
+impl MultiScalarCheck {
+  /// `label` names the sub-proof reported when the check fails
+  pub fn new(label: &'static str) -> Self {
+    MultiScalarCheck {
+      label,
+      scalars: Vec::new(),
+      points: Vec::new(),
+    }
+  }
+
+  pub fn push(&mut self, scalar: Scalar, point: GroupElement) {
+    self.scalars.push(scalar);
+    self.points.push(point);
+  }
+
+  pub fn extend<I>(&mut self, terms: I)
+  where
+    I: IntoIterator<Item = (Scalar, GroupElement)>,
+  {
+    for (scalar, point) in terms {
+      self.push(scalar, point);
+    }
+  }
+
+  /// Adds `weight * other` to this check. Checks built for the same instance
+  /// start with the same public generators, so leading terms over a base this
+  /// check already has at the same position are folded into it rather than
+  /// appended. With `weight` chosen at random after `other` is fixed, the merged
+  /// check passes only if both do, except with negligible probability.
+  pub fn merge(&mut self, other: &MultiScalarCheck, weight: &Scalar) {
+    let shared = self
+      .points
+      .iter()
+      .zip(other.points.iter())
+      .take_while(|(p, q)| p == q)
+      .count();
 
-    let result = multiexp::<ProjectivePoint>(pairs.as_slice());
+    for (i, (scalar, point)) in other.scalars.iter().zip(other.points.iter()).enumerate() {
+      if i < shared {
+        self.scalars[i] += weight * scalar;
+      } else {
+        self.push(weight * scalar, *point);
+      }
+    }
+  }
 
-    AffinePoint(result.to_affine())
+  pub fn verify(&self) -> Result<(), ProofVerifyError> {
+    let points: Vec<ProjectiveGroupElement> = self
+      .points
+      .iter()
+      .map(|p| ProjectivePoint::from(p.0))
+      .collect();
+    let result = ProjectiveGroupElement::vartime_multiscalar_mul(&self.scalars, &points);
+    if bool::from(result.is_identity()) {
+      Ok(())
+    } else {
+      Err(ProofVerifyError::SubProofFailure(self.label))
+    }
   }
 }
 
@@ -131,8 +219,60 @@ mod tests {
       GroupElement::generator(),
       GroupElement::generator(),
     ];
-    let result = GroupElement::vartime_multiscalar_mul(scalars, points);
+    let result = GroupElement::vartime_multiscalar_mul(&scalars, &points);
 
     assert_eq!(result, GroupElement::generator() * Scalar::from(6));
   }
+
+  #[test]
+  fn batch_normalization() {
+    let g = ProjectiveGroupElement::generator();
+    let points = vec![
+      g,
+      g * Scalar::decompress_scalar(&Scalar::from(3)),
+      ProjectiveGroupElement::identity(),
+      g + g + g,
+    ];
+
+    let affine = normalize_batch(&points);
+    for (p, a) in points.iter().zip(affine.iter()) {
+      assert_eq!(p.to_affine(), a.0);
+    }
+    assert_eq!(affine[1], affine[3]);
+
+    let compressed = compress_batch(&points);
+    assert_eq!(compressed[0], GroupElement::generator().compress());
+    assert_eq!(compressed[2], GroupElement::identity().compress());
+  }
+
+  #[test]
+  fn merged_checks() {
+    let g = GroupElement::generator();
+    let h = g * Scalar::from(5);
+
+    // 2g + 3h - 17g == 0
+    let mut first = MultiScalarCheck::new("first");
+    first.extend([(Scalar::from(2), g), (Scalar::from(3), h)]);
+    first.push(-Scalar::from(17), g);
+
+    // g + h - 6g == 0
+    let mut second = MultiScalarCheck::new("second");
+    second.extend([(Scalar::one(), g), (Scalar::one(), h)]);
+    second.push(-Scalar::from(6), g);
+
+    let mut bad = MultiScalarCheck::new("bad");
+    bad.extend([(Scalar::one(), g), (Scalar::one(), h)]);
+    bad.push(-Scalar::from(5), g);
+
+    let mut batch = MultiScalarCheck::new("batch");
+    batch.merge(&first, &Scalar::from(7));
+    batch.merge(&second, &Scalar::from(11));
+    // the shared leading bases were folded together
+    assert_eq!(batch.points.len(), 3);
+    assert!(batch.verify().is_ok());
+
+    let mut batch = MultiScalarCheck::new("batch");
+    batch.merge(&bad, &Scalar::from(7));
+    assert!(batch.verify().is_err());
+  }
 }
@@ -0,0 +1,272 @@
+//! Length checks on bincode-encoded proofs.
+//!
+//! bincode trusts the length prefix of every vector it decodes, so a proof
+//! taken straight from the network can make the decoder allocate far more
+//! memory than the bytes it arrived in. `LayoutReader` walks the encoding
+//! without allocating and checks every length prefix against the size the
+//! verifier expects for the instance at hand. Only once the whole layout
+//! matches, with no bytes left over, is the proof handed to bincode.
+//!
+//! The same walk converts between bincode and the canonical encoding
+//! described in `encoding.rs`: a reader created with `transcode` re-emits
+//! every element it reads in the target format.
+use super::errors::ProofVerifyError;
+use super::scalar::Scalar;
+
+// bincode (fixint) writes lengths as little-endian u64s
+const LEN_PREFIX_BYTES: usize = 8;
+const SCALAR_BYTES: usize = 32;
+// SEC1 encodings: the identity is a single byte, any other point is compressed
+const IDENTITY_POINT_BYTES: usize = 1;
+const COMPRESSED_POINT_BYTES: usize = 33;
+const SEC1_IDENTITY_TAG: u8 = 0x00;
+const SEC1_COMPRESSED_TAGS: [u8; 2] = [0x02, 0x03];
+
+/// How the elements of a proof are laid out in bytes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+  /// `bincode::serialize` of the proof structs, with length prefixes
+  Bincode,
+  /// fixed-width scalars and points, lengths implied by the instance
+  Canonical,
+}
+
+pub struct LayoutReader<'a> {
+  bytes: &'a [u8],
+  format: Format,
+  output: Option<(Format, Vec<u8>)>,
+}
+
+impl<'a> LayoutReader<'a> {
+  pub fn new(bytes: &'a [u8]) -> Self {
+    LayoutReader {
+      bytes,
+      format: Format::Bincode,
+      output: None,
+    }
+  }
+
+  /// Reads `bytes` in format `from`, writing everything read back out in
+  /// format `to`; the result is returned by `into_output`.
+  pub fn transcode(bytes: &'a [u8], from: Format, to: Format) -> Self {
+    LayoutReader {
+      bytes,
+      format: from,
+      output: Some((to, Vec::with_capacity(bytes.len()))),
+    }
+  }
+
+  fn take(&mut self, n: usize) -> Result<&'a [u8], ProofVerifyError> {
+    if self.bytes.len() < n {
+      return Err(ProofVerifyError::MalformedProof("proof is truncated"));
+    }
+    let (head, tail) = self.bytes.split_at(n);
+    self.bytes = tail;
+    Ok(head)
+  }
+
+  fn len_prefix(&mut self) -> Result<u64, ProofVerifyError> {
+    let mut buf = [0u8; LEN_PREFIX_BYTES];
+    buf.copy_from_slice(self.take(LEN_PREFIX_BYTES)?);
+    Ok(u64::from_le_bytes(buf))
+  }
+
+  fn emit(&mut self, bincode_prefix: Option<usize>, canonical: &[u8], bincode: &[u8]) {
+    match &mut self.output {
+      Some((Format::Bincode, out)) => {
+        if let Some(len) = bincode_prefix {
+          out.extend_from_slice(&(len as u64).to_le_bytes());
+        }
+        out.extend_from_slice(bincode);
+      }
+      Some((Format::Canonical, out)) => out.extend_from_slice(canonical),
+      None => (),
+    }
+  }
+
+  /// Reads the length prefix of a vector and checks it against `expected`.
+  /// The canonical format stores no lengths, so there is nothing to read.
+  pub fn vec_len(&mut self, expected: usize) -> Result<(), ProofVerifyError> {
+    if self.format == Format::Bincode && self.len_prefix()? != expected as u64 {
+      return Err(ProofVerifyError::MalformedProof(
+        "proof has a vector of unexpected length",
+      ));
+    }
+    self.emit(Some(expected), &[], &[]);
+    Ok(())
+  }
+
+  pub fn scalar(&mut self) -> Result<(), ProofVerifyError> {
+    // bincode serializes a `Scalar` as a sequence of its 32 bytes
+    if self.format == Format::Bincode && self.len_prefix()? != SCALAR_BYTES as u64 {
+      return Err(ProofVerifyError::MalformedProof(
+        "proof has a scalar of unexpected length",
+      ));
+    }
+    let bytes = self.take(SCALAR_BYTES)?;
+    if self.format == Format::Canonical {
+      let mut repr = [0u8; SCALAR_BYTES];
+      repr.copy_from_slice(bytes);
+      if bool::from(Scalar::from_bytes(&repr).is_none()) {
+        return Err(ProofVerifyError::MalformedProof(
+          "proof has a scalar that is not canonically encoded",
+        ));
+      }
+    }
+    self.emit(Some(SCALAR_BYTES), bytes, bytes);
+    Ok(())
+  }
+
+  /// Scalars stored inline, as in a tuple or fixed-size array.
+  pub fn scalars(&mut self, n: usize) -> Result<(), ProofVerifyError> {
+    (0..n).try_for_each(|_| self.scalar())
+  }
+
+  pub fn scalar_vec(&mut self, n: usize) -> Result<(), ProofVerifyError> {
+    self.vec_len(n)?;
+    self.scalars(n)
+  }
+
+  pub fn point(&mut self) -> Result<(), ProofVerifyError> {
+    let bytes = match self.format {
+      Format::Bincode => {
+        let len = self.len_prefix()?;
+        if len != IDENTITY_POINT_BYTES as u64 && len != COMPRESSED_POINT_BYTES as u64 {
+          return Err(ProofVerifyError::MalformedProof(
+            "proof has a group element of unexpected length",
+          ));
+        }
+        self.take(len as usize)?
+      }
+      Format::Canonical => self.take(COMPRESSED_POINT_BYTES)?,
+    };
+
+    let is_identity = match bytes {
+      [SEC1_IDENTITY_TAG] => true,
+      [tag, ..] if bytes.len() == COMPRESSED_POINT_BYTES && SEC1_COMPRESSED_TAGS.contains(tag) => {
+        false
+      }
+      // the canonical format pads the identity out to the width of a point
+      _ if self.format == Format::Canonical && bytes.iter().all(|b| *b == 0) => true,
+      _ => {
+        return Err(ProofVerifyError::MalformedProof(
+          "proof has a group element with an invalid encoding",
+        ))
+      }
+    };
+
+    if is_identity {
+      self.emit(
+        Some(IDENTITY_POINT_BYTES),
+        &[0u8; COMPRESSED_POINT_BYTES],
+        &[SEC1_IDENTITY_TAG],
+      );
+    } else {
+      self.emit(Some(COMPRESSED_POINT_BYTES), bytes, bytes);
+    }
+    Ok(())
+  }
+
+  pub fn points(&mut self, n: usize) -> Result<(), ProofVerifyError> {
+    (0..n).try_for_each(|_| self.point())
+  }
+
+  pub fn point_vec(&mut self, n: usize) -> Result<(), ProofVerifyError> {
+    self.vec_len(n)?;
+    self.points(n)
+  }
+
+  /// Succeeds only if every byte of the input has been accounted for.
+  pub fn finish(self) -> Result<(), ProofVerifyError> {
+    self.into_output().map(|_| ())
+  }
+
+  /// Like `finish`, but also returns the bytes written by a reader created
+  /// with `transcode`.
+  pub fn into_output(self) -> Result<Vec<u8>, ProofVerifyError> {
+    if !self.bytes.is_empty() {
+      return Err(ProofVerifyError::MalformedProof("proof has trailing bytes"));
+    }
+    Ok(self.output.map(|(_, out)| out).unwrap_or_default())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::group::{CompressedGroup, GroupElement};
+  use crate::scalar::Scalar;
+
+  #[test]
+  fn check_layout_matches_bincode() {
+    let point: CompressedGroup = GroupElement::generator().compress();
+    let value = (
+      vec![Scalar::one(), Scalar::zero()],
+      point,
+      [Scalar::one(); 3],
+    );
+    let bytes = bincode::serialize(&value).unwrap();
+
+    let mut reader = LayoutReader::new(&bytes);
+    reader.scalar_vec(2).unwrap();
+    reader.point().unwrap();
+    reader.scalars(3).unwrap();
+    reader.finish().unwrap();
+
+    let mut reader = LayoutReader::new(&bytes);
+    assert!(reader.scalar_vec(3).is_err());
+
+    let mut reader = LayoutReader::new(&bytes[..bytes.len() - 1]);
+    reader.scalar_vec(2).unwrap();
+    reader.point().unwrap();
+    assert!(reader.scalars(3).is_err());
+
+    let mut extended = bytes.clone();
+    extended.push(0);
+    let mut reader = LayoutReader::new(&extended);
+    reader.scalar_vec(2).unwrap();
+    reader.point().unwrap();
+    reader.scalars(3).unwrap();
+    assert!(reader.finish().is_err());
+  }
+
+  #[test]
+  fn check_transcode_round_trip() {
+    let points: Vec<CompressedGroup> = vec![
+      GroupElement::generator().compress(),
+      GroupElement::identity().compress(),
+    ];
+    let value = (points, Scalar::from(7u64));
+    let bytes = bincode::serialize(&value).unwrap();
+
+    let walk = |reader: &mut LayoutReader| -> Result<(), ProofVerifyError> {
+      reader.point_vec(2)?;
+      reader.scalar()
+    };
+
+    let mut reader = LayoutReader::transcode(&bytes, Format::Bincode, Format::Canonical);
+    walk(&mut reader).unwrap();
+    let canonical = reader.into_output().unwrap();
+    assert_eq!(canonical.len(), 2 * COMPRESSED_POINT_BYTES + SCALAR_BYTES);
+    assert!(
+      canonical[COMPRESSED_POINT_BYTES..2 * COMPRESSED_POINT_BYTES]
+        .iter()
+        .all(|b| *b == 0)
+    );
+
+    let mut reader = LayoutReader::transcode(&canonical, Format::Canonical, Format::Bincode);
+    walk(&mut reader).unwrap();
+    assert_eq!(reader.into_output().unwrap(), bytes);
+
+    // scalars at or above the modulus have no canonical encoding
+    let mut non_canonical = canonical.clone();
+    non_canonical[2 * COMPRESSED_POINT_BYTES..].copy_from_slice(&[0xff; SCALAR_BYTES]);
+    let mut reader = LayoutReader::transcode(&non_canonical, Format::Canonical, Format::Bincode);
+    assert!(walk(&mut reader).is_err());
+
+    let mut bad_tag = canonical;
+    bad_tag[0] = 0x04;
+    let mut reader = LayoutReader::transcode(&bad_tag, Format::Canonical, Format::Bincode);
+    assert!(walk(&mut reader).is_err());
+  }
+}
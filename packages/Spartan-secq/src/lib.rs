@@ -14,11 +14,16 @@ extern crate sha3;
 extern crate rayon;
 
 mod commitments;
+mod context;
 mod dense_mlpoly;
+mod encoding;
 mod errors;
 mod group;
+mod layout;
 mod math;
+mod msm;
 mod nizk;
+mod params;
 mod product_tree;
 mod r1csinstance;
 mod r1csproof;
@@ -30,18 +35,68 @@ mod timer;
 mod transcript;
 mod unipoly;
 
+pub use context::ProofContext;
 use core::cmp::max;
-use errors::{ProofVerifyError, R1CSError};
+use encoding::{decode_proof, encode_proof, statement_digest, ProofKind};
+pub use errors::{GensError, ProofVerifyError, R1CSError};
+use group::MultiScalarCheck;
+use layout::LayoutReader;
+use math::Math;
 use merlin::Transcript;
+use params::{decode_gens, encode_gens, GensHeader};
 use r1csinstance::{
   R1CSCommitment, R1CSCommitmentGens, R1CSDecommitment, R1CSEvalProof, R1CSInstance,
 };
 use r1csproof::{R1CSGens, R1CSProof};
+use rand_core::{CryptoRng, OsRng, RngCore};
 use random::RandomTape;
 use scalar::Scalar;
+
 use serde::{Deserialize, Serialize};
 use timer::Timer;
 use transcript::{AppendToTranscript, ProofTranscript};
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Internals used by `benches/msm.rs`; not part of the public API, so they
+/// only build with the `bench` feature.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub mod bench_internals {
+  use super::group::ProjectiveGroupElement;
+  use super::scalar::{Scalar, ScalarBytes, ScalarBytesFromScalar};
+  use rand_core::OsRng;
+  use secq256k1::affine::Group;
+
+  pub struct MsmInput {
+    scalars: Vec<Scalar>,
+    points: Vec<ProjectiveGroupElement>,
+  }
+
+  impl MsmInput {
+    pub fn random(n: usize) -> Self {
+      let mut csprng: OsRng = OsRng;
+      MsmInput {
+        scalars: (0..n).map(|_| Scalar::random(&mut csprng)).collect(),
+        points: (0..n)
+          .map(|_| ProjectiveGroupElement::random(&mut csprng))
+          .collect(),
+      }
+    }
+
+    pub fn multiscalar_mul(&self) -> ProjectiveGroupElement {
+      super::msm::multiscalar_mul(&self.scalars, &self.points)
+    }
+
+    /// The pairs the previous implementation built on every call before
+    /// handing them to `multiexp`.
+    pub fn to_multiexp_pairs(&self) -> Vec<(ScalarBytes, ProjectiveGroupElement)> {
+      Scalar::decompress_vector(&self.scalars)
+        .into_iter()
+        .zip(self.points.iter().copied())
+        .collect()
+    }
+  }
+}
 
 /// `ComputationCommitment` holds a public preprocessed NP statement (e.g., R1CS)
 pub struct ComputationCommitment {
@@ -53,8 +108,9 @@ pub struct ComputationDecommitment {
   decomm: R1CSDecommitment,
 }
 
-/// `Assignment` holds an assignment of values to either the inputs or variables in an `Instance`
-#[derive(Serialize, Deserialize, Clone)]
+/// `Assignment` holds an assignment of values to either the inputs or variables in an `Instance`.
+/// The values are wiped from memory when the assignment is dropped.
+#[derive(Serialize, Deserialize, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct Assignment {
   assignment: Vec<Scalar>,
 }
@@ -92,9 +148,12 @@ impl Assignment {
     // check that the new length is higher than current length
     assert!(len > self.assignment.len());
 
+    // allocated at its final size, so that growing it does not leave a copy
+    // of the assignment behind in freed memory
     let padded_assignment = {
-      let mut padded_assignment = self.assignment.clone();
-      padded_assignment.extend(vec![Scalar::zero(); len - self.assignment.len()]);
+      let mut padded_assignment = Vec::with_capacity(len);
+      padded_assignment.extend_from_slice(&self.assignment);
+      padded_assignment.resize(len, Scalar::zero());
       padded_assignment
     };
 
@@ -110,6 +169,20 @@ pub type VarsAssignment = Assignment;
 /// `InputsAssignment` holds an assignment of values to variables in an `Instance`
 pub type InputsAssignment = Assignment;
 
+/// A constraint `(A z) * (B z) = (C z)` that an assignment does not satisfy,
+/// with `z = (vars, 1, inputs)`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsatisfiedConstraint {
+  /// the row of the constraint in the matrices
+  pub index: usize,
+  /// `(A z)[index]`, in the little-endian byte order of `Assignment::new`
+  pub a: [u8; 32],
+  /// `(B z)[index]`
+  pub b: [u8; 32],
+  /// `(C z)[index]`
+  pub c: [u8; 32],
+}
+
 /// `Instance` holds the description of R1CS matrices and a hash of the matrices
 #[derive(Serialize, Deserialize)]
 pub struct Instance {
@@ -234,6 +307,16 @@ impl Instance {
     vars: &VarsAssignment,
     inputs: &InputsAssignment,
   ) -> Result<bool, R1CSError> {
+    Ok(self.check_sat_detailed(vars, inputs)?.is_empty())
+  }
+
+  /// Same as `is_sat`, but returns every constraint the assignments do not
+  /// satisfy, in the order of the rows of the matrices
+  pub fn check_sat_detailed(
+    &self,
+    vars: &VarsAssignment,
+    inputs: &InputsAssignment,
+  ) -> Result<Vec<UnsatisfiedConstraint>, R1CSError> {
     if vars.assignment.len() > self.inst.get_num_vars() {
       return Err(R1CSError::InvalidNumberOfInputs);
     }
@@ -253,10 +336,19 @@ impl Instance {
       }
     };
 
+    let unsatisfied = self
+      .inst
+      .unsatisfied_constraints(&padded_vars.assignment, &inputs.assignment);
     Ok(
-      self
-        .inst
-        .is_sat(&padded_vars.assignment, &inputs.assignment),
+      unsatisfied
+        .into_iter()
+        .map(|(index, a, b, c)| UnsatisfiedConstraint {
+          index,
+          a: a.to_bytes(),
+          b: b.to_bytes(),
+          c: c.to_bytes(),
+        })
+        .collect(),
     )
   }
 
@@ -277,7 +369,12 @@ impl Instance {
 }
 
 /// `SNARKGens` holds public parameters for producing and verifying proofs with the Spartan SNARK
+#[derive(Serialize, Deserialize)]
 pub struct SNARKGens {
+  num_cons: usize,
+  num_vars: usize,
+  num_inputs: usize,
+  num_nz_entries: usize,
   gens_r1cs_sat: R1CSGens,
   gens_r1cs_eval: R1CSCommitmentGens,
 }
@@ -303,10 +400,50 @@ impl SNARKGens {
       num_nz_entries,
     );
     SNARKGens {
+      num_cons,
+      num_vars,
+      num_inputs,
+      num_nz_entries,
       gens_r1cs_sat,
       gens_r1cs_eval,
     }
   }
+
+  fn label() -> &'static [u8] {
+    b"Spartan SNARK gens"
+  }
+
+  /// Encodes the generators in a versioned format whose header records
+  /// the size of the R1CS statement they were created for
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let header = GensHeader::new(
+      SNARKGens::label(),
+      self.num_cons,
+      self.num_vars,
+      self.num_inputs,
+      self.num_nz_entries,
+    );
+    encode_gens(&header, self)
+  }
+
+  /// Decodes generators produced by `to_bytes`, checking that they were
+  /// created for an R1CS statement of the given size
+  pub fn from_bytes(
+    bytes: &[u8],
+    num_cons: usize,
+    num_vars: usize,
+    num_inputs: usize,
+    num_nz_entries: usize,
+  ) -> Result<Self, GensError> {
+    let header = GensHeader::new(
+      SNARKGens::label(),
+      num_cons,
+      num_vars,
+      num_inputs,
+      num_nz_entries,
+    );
+    decode_gens(bytes, &header)
+  }
 }
 
 /// `SNARK` holds a proof produced by Spartan SNARK
@@ -346,15 +483,93 @@ impl SNARK {
     gens: &SNARKGens,
     transcript: &mut Transcript,
   ) -> Self {
-    let timer_prove = Timer::new("SNARK::prove");
+    SNARK::prove_with_rng(
+      inst, comm, decomm, vars, inputs, gens, transcript, &mut OsRng,
+    )
+  }
 
-    // we create a Transcript object seeded with a random Scalar
-    // to aid the prover produce its randomness
-    let mut random_tape = RandomTape::new(b"proof");
+  /// Same as `prove`, but draws the prover's randomness from `rng`
+  #[allow(clippy::too_many_arguments)]
+  pub fn prove_with_rng<R: RngCore + CryptoRng>(
+    inst: &Instance,
+    comm: &ComputationCommitment,
+    decomm: &ComputationDecommitment,
+    vars: VarsAssignment,
+    inputs: &InputsAssignment,
+    gens: &SNARKGens,
+    transcript: &mut Transcript,
+    rng: &mut R,
+  ) -> Self {
+    SNARK::prove_with_tape(
+      inst,
+      comm,
+      decomm,
+      vars,
+      inputs,
+      gens,
+      transcript,
+      |_, _| RandomTape::new_with_rng(b"proof", rng),
+    )
+  }
+
+  /// Same as `prove`, but with the prover's randomness derived from `seed`,
+  /// `extra_entropy` and the witness; see `NIZK::prove_deterministic`
+  #[allow(clippy::too_many_arguments)]
+  pub fn prove_deterministic(
+    inst: &Instance,
+    comm: &ComputationCommitment,
+    decomm: &ComputationDecommitment,
+    vars: VarsAssignment,
+    inputs: &InputsAssignment,
+    gens: &SNARKGens,
+    transcript: &mut Transcript,
+    seed: &[u8; 32],
+    extra_entropy: Option<&[u8]>,
+  ) -> Self {
+    SNARK::prove_with_tape(
+      inst,
+      comm,
+      decomm,
+      vars,
+      inputs,
+      gens,
+      transcript,
+      |transcript, vars| {
+        RandomTape::new_deterministic(
+          b"proof",
+          seed,
+          extra_entropy,
+          transcript,
+          &inputs.assignment,
+          &vars.assignment,
+        )
+      },
+    )
+  }
+
+  #[allow(clippy::too_many_arguments)]
+  fn prove_with_tape<F>(
+    inst: &Instance,
+    comm: &ComputationCommitment,
+    decomm: &ComputationDecommitment,
+    vars: VarsAssignment,
+    inputs: &InputsAssignment,
+    gens: &SNARKGens,
+    transcript: &mut Transcript,
+    random_tape: F,
+  ) -> Self
+  where
+    F: FnOnce(&Transcript, &VarsAssignment) -> RandomTape,
+  {
+    let timer_prove = Timer::new("SNARK::prove");
 
     transcript.append_protocol_name(SNARK::protocol_name());
     comm.comm.append_to_transcript(b"comm", transcript);
 
+    // we create a Transcript object seeded with a random Scalar
+    // to aid the prover produce its randomness
+    let mut random_tape = random_tape(transcript, &vars);
+
     let (r1cs_sat_proof, rx, ry) = {
       let (proof, rx, ry) = {
         // we might need to pad variables
@@ -370,7 +585,7 @@ impl SNARK {
 
         R1CSProof::prove(
           &inst.inst,
-          padded_vars.assignment,
+          &padded_vars.assignment,
           &inputs.assignment,
           &gens.gens_r1cs_sat,
           transcript,
@@ -420,6 +635,44 @@ impl SNARK {
     }
   }
 
+  fn check_layout(
+    reader: &mut LayoutReader,
+    comm: &ComputationCommitment,
+  ) -> Result<(), ProofVerifyError> {
+    R1CSProof::check_layout(reader, comm.comm.get_num_vars(), comm.comm.get_num_cons())?;
+    reader.scalars(3)?;
+    R1CSEvalProof::check_layout(reader, &comm.comm)
+  }
+
+  fn statement_digest(comm: &ComputationCommitment) -> [u8; 32] {
+    statement_digest(&bincode::serialize(&comm.comm).unwrap())
+  }
+
+  /// Encodes the proof in the canonical, versioned format described in
+  /// `encoding.rs`; `SNARK::from_bytes` reads it back
+  pub fn to_bytes(&self, comm: &ComputationCommitment) -> Vec<u8> {
+    encode_proof(
+      ProofKind::Snark,
+      &SNARK::statement_digest(comm),
+      &bincode::serialize(self).unwrap(),
+      |reader| SNARK::check_layout(reader, comm),
+    )
+  }
+
+  /// Decodes a proof produced for the computation committed to in `comm`,
+  /// in either the canonical format or the bincode encoding of earlier
+  /// releases. Every vector length is checked against the size of the
+  /// committed instance before anything is allocated, and trailing bytes are
+  /// rejected
+  pub fn from_bytes(bytes: &[u8], comm: &ComputationCommitment) -> Result<Self, ProofVerifyError> {
+    decode_proof(
+      bytes,
+      ProofKind::Snark,
+      &SNARK::statement_digest(comm),
+      |reader| SNARK::check_layout(reader, comm),
+    )
+  }
+
   /// A method to verify the SNARK proof of the satisfiability of an R1CS instance
   pub fn verify(
     &self,
@@ -435,7 +688,12 @@ impl SNARK {
     comm.comm.append_to_transcript(b"comm", transcript);
 
     let timer_sat_proof = Timer::new("verify_sat_proof");
-    assert_eq!(input.assignment.len(), comm.comm.get_num_inputs());
+    if input.assignment.len() != comm.comm.get_num_inputs() {
+      return Err(ProofVerifyError::InputLengthMismatch {
+        expected: comm.comm.get_num_inputs(),
+        actual: input.assignment.len(),
+      });
+    }
     let (rx, ry) = self.r1cs_sat_proof.verify(
       comm.comm.get_num_vars(),
       comm.comm.get_num_cons(),
@@ -466,7 +724,11 @@ impl SNARK {
 }
 
 /// `NIZKGens` holds public parameters for producing and verifying proofs with the Spartan NIZK
+#[derive(Serialize, Deserialize)]
 pub struct NIZKGens {
+  num_cons: usize,
+  num_vars: usize,
+  num_inputs: usize,
   gens_r1cs_sat: R1CSGens,
 }
 
@@ -482,7 +744,41 @@ impl NIZKGens {
     };
 
     let gens_r1cs_sat = R1CSGens::new(b"gens_r1cs_sat", num_cons, num_vars_padded);
-    NIZKGens { gens_r1cs_sat }
+    NIZKGens {
+      num_cons,
+      num_vars,
+      num_inputs,
+      gens_r1cs_sat,
+    }
+  }
+
+  fn label() -> &'static [u8] {
+    b"Spartan NIZK gens"
+  }
+
+  /// Encodes the generators in a versioned format whose header records
+  /// the size of the R1CS statement they were created for
+  pub fn to_bytes(&self) -> Vec<u8> {
+    let header = GensHeader::new(
+      NIZKGens::label(),
+      self.num_cons,
+      self.num_vars,
+      self.num_inputs,
+      0,
+    );
+    encode_gens(&header, self)
+  }
+
+  /// Decodes generators produced by `to_bytes`, checking that they were
+  /// created for an R1CS statement of the given size
+  pub fn from_bytes(
+    bytes: &[u8],
+    num_cons: usize,
+    num_vars: usize,
+    num_inputs: usize,
+  ) -> Result<Self, GensError> {
+    let header = GensHeader::new(NIZKGens::label(), num_cons, num_vars, num_inputs, 0);
+    decode_gens(bytes, &header)
   }
 }
 
@@ -506,14 +802,76 @@ impl NIZK {
     gens: &NIZKGens,
     transcript: &mut Transcript,
   ) -> Self {
+    NIZK::prove_with_rng(inst, vars, input, gens, transcript, &mut OsRng)
+  }
+
+  /// Same as `prove`, but draws the prover's randomness from `rng`
+  pub fn prove_with_rng<R: RngCore + CryptoRng>(
+    inst: &Instance,
+    vars: VarsAssignment,
+    input: &InputsAssignment,
+    gens: &NIZKGens,
+    transcript: &mut Transcript,
+    rng: &mut R,
+  ) -> Self {
+    NIZK::prove_with_tape(inst, vars, input, gens, transcript, |_, _| {
+      RandomTape::new_with_rng(b"proof", rng)
+    })
+  }
+
+  /// Same as `prove`, but deterministic: the prover's randomness is derived
+  /// from `seed`, the witness, the public inputs and the state of
+  /// `transcript`, in the manner of RFC 6979, so the same call always returns
+  /// the same proof. Proving a different statement or witness, or under a
+  /// different transcript, gives unrelated randomness. Keeping `seed` secret
+  /// and random hedges against a witness with little entropy; proofs are in
+  /// the same format as those from `prove`.
+  ///
+  /// `extra_entropy`, as the additional data of RFC 6979 §3.6, is mixed into
+  /// the randomness when given. Fresh random bytes make the proof hedged
+  /// instead: it no longer repeats, but stays safe if the random source is
+  /// weak, and `None` keeps the purely deterministic mode
+  pub fn prove_deterministic(
+    inst: &Instance,
+    vars: VarsAssignment,
+    input: &InputsAssignment,
+    gens: &NIZKGens,
+    transcript: &mut Transcript,
+    seed: &[u8; 32],
+    extra_entropy: Option<&[u8]>,
+  ) -> Self {
+    NIZK::prove_with_tape(inst, vars, input, gens, transcript, |transcript, vars| {
+      RandomTape::new_deterministic(
+        b"proof",
+        seed,
+        extra_entropy,
+        transcript,
+        &input.assignment,
+        &vars.assignment,
+      )
+    })
+  }
+
+  fn prove_with_tape<F>(
+    inst: &Instance,
+    vars: VarsAssignment,
+    input: &InputsAssignment,
+    gens: &NIZKGens,
+    transcript: &mut Transcript,
+    random_tape: F,
+  ) -> Self
+  where
+    F: FnOnce(&Transcript, &VarsAssignment) -> RandomTape,
+  {
     let timer_prove = Timer::new("NIZK::prove");
-    // we create a Transcript object seeded with a random Scalar
-    // to aid the prover produce its randomness
-    let mut random_tape = RandomTape::new(b"proof");
 
     transcript.append_protocol_name(NIZK::protocol_name());
     transcript.append_message(b"R1CSInstanceDigest", &inst.digest);
 
+    // we create a Transcript object seeded with a random Scalar
+    // to aid the prover produce its randomness
+    let mut random_tape = random_tape(transcript, &vars);
+
     let (r1cs_sat_proof, rx, ry) = {
       // we might need to pad variables
       let padded_vars = {
@@ -528,7 +886,7 @@ impl NIZK {
 
       let (proof, rx, ry) = R1CSProof::prove(
         &inst.inst,
-        padded_vars.assignment,
+        &padded_vars.assignment,
         &input.assignment,
         &gens.gens_r1cs_sat,
         transcript,
@@ -546,6 +904,37 @@ impl NIZK {
     }
   }
 
+  fn check_layout(reader: &mut LayoutReader, inst: &Instance) -> Result<(), ProofVerifyError> {
+    let (num_cons, num_vars) = (inst.inst.get_num_cons(), inst.inst.get_num_vars());
+    R1CSProof::check_layout(reader, num_vars, num_cons)?;
+    reader.scalar_vec(num_cons.log_2())?;
+    reader.scalar_vec((2 * num_vars).log_2())
+  }
+
+  /// Encodes the proof in the canonical, versioned format described in
+  /// `encoding.rs`; `NIZK::from_bytes` reads it back
+  pub fn to_bytes(&self, inst: &Instance) -> Vec<u8> {
+    encode_proof(
+      ProofKind::Nizk,
+      &statement_digest(&inst.digest),
+      &bincode::serialize(self).unwrap(),
+      |reader| NIZK::check_layout(reader, inst),
+    )
+  }
+
+  /// Decodes a proof produced for `inst`, in either the canonical format or
+  /// the bincode encoding of earlier releases. Every vector length is checked
+  /// against the dimensions of `inst` before anything is allocated, and
+  /// trailing bytes are rejected
+  pub fn from_bytes(bytes: &[u8], inst: &Instance) -> Result<Self, ProofVerifyError> {
+    decode_proof(
+      bytes,
+      ProofKind::Nizk,
+      &statement_digest(&inst.digest),
+      |reader| NIZK::check_layout(reader, inst),
+    )
+  }
+
   /// A method to verify a NIZK proof of the satisfiability of an R1CS instance
   pub fn verify(
     &self,
@@ -555,7 +944,68 @@ impl NIZK {
     gens: &NIZKGens,
   ) -> Result<(), ProofVerifyError> {
     let timer_verify = Timer::new("NIZK::verify");
+    for check in self.verify_deferred(inst, input, transcript, gens)? {
+      check.verify()?;
+    }
+    timer_verify.stop();
+
+    Ok(())
+  }
+
+  /// Verifies many proofs of satisfiability of the same instance. Each proof
+  /// is checked against its own copy of `transcript`, as `verify` would, except
+  /// that the final multi-scalar multiplication checks of all proofs are merged
+  /// with random weights and evaluated together. If the merged check fails,
+  /// the proofs' checks are evaluated one by one, and the first invalid proof
+  /// is returned as `BatchFailure`
+  pub fn batch_verify(
+    proofs: &[(&NIZK, &InputsAssignment)],
+    inst: &Instance,
+    transcript: &Transcript,
+    gens: &NIZKGens,
+  ) -> Result<(), ProofVerifyError> {
+    let timer_verify = Timer::new("NIZK::batch_verify");
+    let in_batch = |index: usize| {
+      move |e: ProofVerifyError| ProofVerifyError::BatchFailure {
+        index,
+        error: Box::new(e),
+      }
+    };
+
+    let mut batch = MultiScalarCheck::new("batched proofs");
+    let mut checks = Vec::with_capacity(proofs.len());
+    for (index, (proof, input)) in proofs.iter().enumerate() {
+      let proof_checks = proof
+        .verify_deferred(inst, input, &mut transcript.clone(), gens)
+        .map_err(in_batch(index))?;
+      for check in &proof_checks {
+        batch.merge(check, &Scalar::random(&mut OsRng));
+      }
+      checks.push(proof_checks);
+    }
 
+    if batch.verify().is_err() {
+      for (index, proof_checks) in checks.iter().enumerate() {
+        for check in proof_checks {
+          check.verify().map_err(in_batch(index))?;
+        }
+      }
+      // the merged check of valid proofs always passes, whatever the weights,
+      // so reaching this is a bug of the batching
+      return Err(ProofVerifyError::InternalError);
+    }
+    timer_verify.stop();
+
+    Ok(())
+  }
+
+  fn verify_deferred(
+    &self,
+    inst: &Instance,
+    input: &InputsAssignment,
+    transcript: &mut Transcript,
+    gens: &NIZKGens,
+  ) -> Result<Vec<MultiScalarCheck>, ProofVerifyError> {
     transcript.append_protocol_name(NIZK::protocol_name());
     transcript.append_message(b"R1CSInstanceDigest", &inst.digest);
 
@@ -563,12 +1013,24 @@ impl NIZK {
     // to enable the verifier complete the first sum-check
     let timer_eval = Timer::new("eval_sparse_polys");
     let (claimed_rx, claimed_ry) = &self.r;
+    if claimed_rx.len() != inst.inst.get_num_cons().log_2()
+      || claimed_ry.len() != (2 * inst.inst.get_num_vars()).log_2()
+    {
+      return Err(ProofVerifyError::MalformedProof(
+        "claimed evaluation point has the wrong length",
+      ));
+    }
     let inst_evals = inst.inst.evaluate(claimed_rx, claimed_ry);
     timer_eval.stop();
 
     let timer_sat_proof = Timer::new("verify_sat_proof");
-    assert_eq!(input.assignment.len(), inst.inst.get_num_inputs());
-    let (rx, ry) = self.r1cs_sat_proof.verify(
+    if input.assignment.len() != inst.inst.get_num_inputs() {
+      return Err(ProofVerifyError::InputLengthMismatch {
+        expected: inst.inst.get_num_inputs(),
+        actual: input.assignment.len(),
+      });
+    }
+    let (rx, ry, checks) = self.r1cs_sat_proof.verify_deferred(
       inst.inst.get_num_vars(),
       inst.inst.get_num_cons(),
       &input.assignment,
@@ -578,12 +1040,12 @@ impl NIZK {
     )?;
 
     // verify if claimed rx and ry are correct
-    assert_eq!(rx, *claimed_rx);
-    assert_eq!(ry, *claimed_ry);
+    if rx != *claimed_rx || ry != *claimed_ry {
+      return Err(ProofVerifyError::TranscriptPointMismatch);
+    }
     timer_sat_proof.stop();
-    timer_verify.stop();
 
-    Ok(())
+    Ok(checks)
   }
 }
 
@@ -625,6 +1087,392 @@ mod tests {
       .is_ok());
   }
 
+  #[test]
+  pub fn check_gens_serialization() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+
+    let gens_bytes = NIZKGens::new(num_cons, num_vars, num_inputs).to_bytes();
+    assert!(NIZKGens::from_bytes(&gens_bytes, num_cons, num_vars, num_inputs + 1).is_err());
+    assert!(NIZKGens::from_bytes(&gens_bytes[1..], num_cons, num_vars, num_inputs).is_err());
+    let gens = NIZKGens::from_bytes(&gens_bytes, num_cons, num_vars, num_inputs).unwrap();
+    assert_eq!(gens.to_bytes(), gens_bytes);
+
+    let mut prover_transcript = Transcript::new(b"example");
+    let proof = NIZK::prove(&inst, vars, &inputs, &gens, &mut prover_transcript);
+
+    // a proof made with the decoded gens verifies under freshly generated ones
+    let fresh_gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+    let mut verifier_transcript = Transcript::new(b"example");
+    assert!(proof
+      .verify(&inst, &inputs, &mut verifier_transcript, &fresh_gens)
+      .is_ok());
+
+    let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_cons);
+    let gens_bytes = gens.to_bytes();
+    assert!(NIZKGens::from_bytes(&gens_bytes, num_cons, num_vars, num_inputs).is_err());
+    let decoded = SNARKGens::from_bytes(&gens_bytes, num_cons, num_vars, num_inputs, num_cons);
+    assert_eq!(decoded.unwrap().to_bytes(), gens_bytes);
+  }
+
+  #[test]
+  pub fn check_proof_context() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+
+    let context = ProofContext::new(b"app")
+      .with_chain_id(1)
+      .with_external_nullifier(b"poll-1");
+    let proof = NIZK::prove(&inst, vars, &inputs, &gens, &mut context.transcript());
+    assert!(proof
+      .verify(&inst, &inputs, &mut context.clone().transcript(), &gens)
+      .is_ok());
+
+    // the proof does not verify for another application, chain or nullifier,
+    // nor without a context
+    for other in [
+      ProofContext::new(b"other app")
+        .with_chain_id(1)
+        .with_external_nullifier(b"poll-1"),
+      ProofContext::new(b"app")
+        .with_chain_id(2)
+        .with_external_nullifier(b"poll-1"),
+      ProofContext::new(b"app")
+        .with_chain_id(1)
+        .with_external_nullifier(b"poll-2"),
+      ProofContext::new(b"app").with_chain_id(1),
+      context.clone().with_message(b"extra"),
+    ] {
+      assert!(proof
+        .verify(&inst, &inputs, &mut other.transcript(), &gens)
+        .is_err());
+    }
+    let mut verifier_transcript = Transcript::new(b"example");
+    assert!(proof
+      .verify(&inst, &inputs, &mut verifier_transcript, &gens)
+      .is_err());
+  }
+
+  #[test]
+  pub fn check_malformed_proofs() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+    let short_inputs =
+      InputsAssignment::new(&vec![Scalar::zero().to_bytes(); num_inputs - 1]).unwrap();
+
+    let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_cons);
+    let (comm, decomm) = SNARK::encode(&inst, &gens);
+    let mut prover_transcript = Transcript::new(b"example");
+    let proof = SNARK::prove(
+      &inst,
+      &comm,
+      &decomm,
+      vars.clone(),
+      &inputs,
+      &gens,
+      &mut prover_transcript,
+    );
+    let mut verifier_transcript = Transcript::new(b"example");
+    assert!(matches!(
+      proof.verify(&comm, &short_inputs, &mut verifier_transcript, &gens),
+      Err(ProofVerifyError::InputLengthMismatch { .. })
+    ));
+
+    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+    let mut prover_transcript = Transcript::new(b"example");
+    let proof = NIZK::prove(&inst, vars, &inputs, &gens, &mut prover_transcript);
+    let mut verifier_transcript = Transcript::new(b"example");
+    assert!(matches!(
+      proof.verify(&inst, &short_inputs, &mut verifier_transcript, &gens),
+      Err(ProofVerifyError::InputLengthMismatch { .. })
+    ));
+
+    // flipping a bit anywhere in the encoded proof must not panic, and may only
+    // verify if it decodes to the very same proof
+    let proof_bytes = bincode::serialize(&proof).unwrap();
+    for i in (0..proof_bytes.len()).step_by(proof_bytes.len() / 64 + 1) {
+      let mut tampered = proof_bytes.clone();
+      tampered[i] ^= 1;
+      if let Ok(tampered) = bincode::deserialize::<NIZK>(&tampered) {
+        let mut verifier_transcript = Transcript::new(b"example");
+        if tampered
+          .verify(&inst, &inputs, &mut verifier_transcript, &gens)
+          .is_ok()
+        {
+          assert_eq!(bincode::serialize(&tampered).unwrap(), proof_bytes);
+        }
+      }
+    }
+
+    // truncated encodings fail to decode instead of panicking
+    for len in (0..proof_bytes.len()).step_by(proof_bytes.len() / 16 + 1) {
+      assert!(bincode::deserialize::<NIZK>(&proof_bytes[..len]).is_err());
+    }
+  }
+
+  #[test]
+  pub fn check_proof_from_bytes() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+    let (other_inst, _, _) = Instance::produce_synthetic_r1cs(2 * num_cons, num_vars, num_inputs);
+
+    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+    let mut prover_transcript = Transcript::new(b"example");
+    let proof = NIZK::prove(&inst, vars.clone(), &inputs, &gens, &mut prover_transcript);
+    let proof_bytes = proof.to_bytes(&inst);
+
+    let decoded = NIZK::from_bytes(&proof_bytes, &inst).unwrap();
+    let mut verifier_transcript = Transcript::new(b"example");
+    assert!(decoded
+      .verify(&inst, &inputs, &mut verifier_transcript, &gens)
+      .is_ok());
+    assert_eq!(decoded.to_bytes(&inst), proof_bytes);
+
+    assert!(matches!(
+      NIZK::from_bytes(&proof_bytes, &other_inst),
+      Err(ProofVerifyError::StatementMismatch)
+    ));
+    assert!(NIZK::from_bytes(&proof_bytes[..proof_bytes.len() - 1], &inst).is_err());
+    let mut extended = proof_bytes.clone();
+    extended.push(0);
+    assert!(NIZK::from_bytes(&extended, &inst).is_err());
+
+    // header: magic, version, curve id, proof kind
+    assert_eq!(&proof_bytes[..11], b"SPRF\x01\x00\x00\x00\x01\x00\x01");
+    let mut future = proof_bytes.clone();
+    future[4] = 2;
+    assert!(matches!(
+      NIZK::from_bytes(&future, &inst),
+      Err(ProofVerifyError::UnsupportedVersion(2))
+    ));
+
+    // the bincode encoding of earlier releases still decodes, with the same bounds
+    let legacy_bytes = bincode::serialize(&proof).unwrap();
+    let decoded = NIZK::from_bytes(&legacy_bytes, &inst).unwrap();
+    assert_eq!(decoded.to_bytes(&inst), proof_bytes);
+    assert!(NIZK::from_bytes(&legacy_bytes, &other_inst).is_err());
+    assert!(NIZK::from_bytes(&legacy_bytes[..legacy_bytes.len() - 1], &inst).is_err());
+
+    // a huge declared length is rejected without being allocated
+    let mut inflated = legacy_bytes.clone();
+    inflated[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(NIZK::from_bytes(&inflated, &inst).is_err());
+
+    let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_cons);
+    let (comm, decomm) = SNARK::encode(&inst, &gens);
+    let mut prover_transcript = Transcript::new(b"example");
+    let proof = SNARK::prove(
+      &inst,
+      &comm,
+      &decomm,
+      vars,
+      &inputs,
+      &gens,
+      &mut prover_transcript,
+    );
+    let proof_bytes = proof.to_bytes(&comm);
+
+    let decoded = SNARK::from_bytes(&proof_bytes, &comm).unwrap();
+    let mut verifier_transcript = Transcript::new(b"example");
+    assert!(decoded
+      .verify(&comm, &inputs, &mut verifier_transcript, &gens)
+      .is_ok());
+    assert!(SNARK::from_bytes(&proof_bytes[1..], &comm).is_err());
+    assert!(SNARK::from_bytes(&proof_bytes[..proof_bytes.len() - 1], &comm).is_err());
+
+    let legacy_bytes = bincode::serialize(&proof).unwrap();
+    let decoded = SNARK::from_bytes(&legacy_bytes, &comm).unwrap();
+    assert_eq!(decoded.to_bytes(&comm), proof_bytes);
+    assert!(SNARK::from_bytes(&legacy_bytes[1..], &comm).is_err());
+  }
+
+  #[test]
+  pub fn check_batch_verify() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+    let transcript = Transcript::new(b"example");
+
+    let proofs = (0..3)
+      .map(|_| NIZK::prove(&inst, vars.clone(), &inputs, &gens, &mut transcript.clone()))
+      .collect::<Vec<_>>();
+
+    let batch = proofs.iter().map(|p| (p, &inputs)).collect::<Vec<_>>();
+    assert!(NIZK::batch_verify(&batch, &inst, &transcript, &gens).is_ok());
+    assert!(NIZK::batch_verify(&[], &inst, &transcript, &gens).is_ok());
+
+    let wrong_inputs = InputsAssignment::new(&vec![Scalar::one().to_bytes(); num_inputs]).unwrap();
+    let mut batch = batch;
+    batch[1].1 = &wrong_inputs;
+    assert!(matches!(
+      NIZK::batch_verify(&batch, &inst, &transcript, &gens),
+      Err(ProofVerifyError::BatchFailure { index: 1, .. })
+    ));
+
+    // a proof made under a different transcript only fails in the final checks
+    // if it gets that far, and is still singled out
+    let other = NIZK::prove(&inst, vars, &inputs, &gens, &mut Transcript::new(b"other"));
+    let batch = vec![
+      (&proofs[0], &inputs),
+      (&proofs[1], &inputs),
+      (&other, &inputs),
+    ];
+    assert!(matches!(
+      NIZK::batch_verify(&batch, &inst, &transcript, &gens),
+      Err(ProofVerifyError::BatchFailure { index: 2, .. })
+    ));
+  }
+
+  #[test]
+  pub fn check_deterministic_proofs() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+
+    let prove = |seed: &[u8; 32], extra_entropy: Option<&[u8]>, label: &'static [u8]| {
+      let mut transcript = Transcript::new(label);
+      NIZK::prove_deterministic(
+        &inst,
+        vars.clone(),
+        &inputs,
+        &gens,
+        &mut transcript,
+        seed,
+        extra_entropy,
+      )
+      .to_bytes(&inst)
+    };
+    let bytes = prove(&[1u8; 32], None, b"example");
+    assert_eq!(bytes, prove(&[1u8; 32], None, b"example"));
+    assert_ne!(bytes, prove(&[2u8; 32], None, b"example"));
+
+    let proof = NIZK::from_bytes(&bytes, &inst).unwrap();
+    assert!(proof
+      .verify(&inst, &inputs, &mut Transcript::new(b"example"), &gens)
+      .is_ok());
+
+    // additional entropy gives other, still valid, proofs
+    let hedged = prove(&[1u8; 32], Some(&[3u8; 32]), b"example");
+    assert_ne!(bytes, hedged);
+    assert_ne!(hedged, prove(&[1u8; 32], Some(&[4u8; 32]), b"example"));
+    let proof = NIZK::from_bytes(&hedged, &inst).unwrap();
+    assert!(proof
+      .verify(&inst, &inputs, &mut Transcript::new(b"example"), &gens)
+      .is_ok());
+
+    let proof = NIZK::prove_with_rng(
+      &inst,
+      vars.clone(),
+      &inputs,
+      &gens,
+      &mut Transcript::new(b"example"),
+      &mut OsRng,
+    );
+    assert!(proof
+      .verify(&inst, &inputs, &mut Transcript::new(b"example"), &gens)
+      .is_ok());
+
+    let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_cons);
+    let (comm, decomm) = SNARK::encode(&inst, &gens);
+    let prove = || {
+      let mut transcript = Transcript::new(b"example");
+      SNARK::prove_deterministic(
+        &inst,
+        &comm,
+        &decomm,
+        vars.clone(),
+        &inputs,
+        &gens,
+        &mut transcript,
+        &[1u8; 32],
+        None,
+      )
+      .to_bytes(&comm)
+    };
+    let bytes = prove();
+    assert_eq!(bytes, prove());
+    let proof = SNARK::from_bytes(&bytes, &comm).unwrap();
+    assert!(proof
+      .verify(&comm, &inputs, &mut Transcript::new(b"example"), &gens)
+      .is_ok());
+  }
+
+  #[test]
+  pub fn check_reused_gens() {
+    let num_vars = 16;
+    let num_cons = num_vars;
+    let num_inputs = 2;
+    let (inst, vars, inputs) = Instance::produce_synthetic_r1cs(num_cons, num_vars, num_inputs);
+
+    // the first proof builds the tables, which only change how the later
+    // commitments are computed
+    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+    let prove = |gens: &NIZKGens| {
+      let mut transcript = Transcript::new(b"example");
+      NIZK::prove_deterministic(
+        &inst,
+        vars.clone(),
+        &inputs,
+        gens,
+        &mut transcript,
+        &[1u8; 32],
+        None,
+      )
+      .to_bytes(&inst)
+    };
+    let proof = prove(&gens);
+    assert_eq!(prove(&gens), proof);
+    let decoded = NIZKGens::from_bytes(&gens.to_bytes(), num_cons, num_vars, num_inputs).unwrap();
+    assert_eq!(prove(&decoded), proof);
+
+    let gens = SNARKGens::new(num_cons, num_vars, num_inputs, num_cons);
+    let (comm, decomm) = SNARK::encode(&inst, &gens);
+    let (reused_comm, _) = SNARK::encode(&inst, &gens);
+    assert_eq!(
+      bincode::serialize(&comm.comm).unwrap(),
+      bincode::serialize(&reused_comm.comm).unwrap()
+    );
+    let prove = |gens: &SNARKGens| {
+      let mut transcript = Transcript::new(b"example");
+      SNARK::prove_deterministic(
+        &inst,
+        &comm,
+        &decomm,
+        vars.clone(),
+        &inputs,
+        gens,
+        &mut transcript,
+        &[1u8; 32],
+        None,
+      )
+      .to_bytes(&comm)
+    };
+    let proof = prove(&gens);
+    assert_eq!(prove(&gens), proof);
+    let decoded =
+      SNARKGens::from_bytes(&gens.to_bytes(), num_cons, num_vars, num_inputs, num_cons).unwrap();
+    assert_eq!(prove(&decoded), proof);
+  }
+
   #[test]
   pub fn check_r1cs_invalid_index() {
     let num_cons = 4;
@@ -667,6 +1515,45 @@ mod tests {
     assert_eq!(inst.err(), Some(R1CSError::InvalidScalar));
   }
 
+  #[test]
+  fn check_sat_detailed() {
+    // a * b = c and a + b = d, with a, b, c, d the inputs
+    let (num_cons, num_vars, num_inputs) = (2, 0, 4);
+    let one = Scalar::one().to_bytes();
+    let A = vec![(0, 1, one), (1, 1, one), (1, 2, one)];
+    let B = vec![(0, 2, one), (1, 0, one)];
+    let C = vec![(0, 3, one), (1, 4, one)];
+    let inst = Instance::new(num_cons, num_vars, num_inputs, &A, &B, &C).unwrap();
+
+    let check = |inputs: [u64; 4]| {
+      let inputs = inputs.map(|x| Scalar::from(x).to_bytes());
+      inst
+        .check_sat_detailed(
+          &VarsAssignment::new(&[]).unwrap(),
+          &InputsAssignment::new(&inputs).unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(check([2, 3, 6, 5]), vec![]);
+    assert_eq!(
+      check([2, 3, 7, 6]),
+      vec![
+        UnsatisfiedConstraint {
+          index: 0,
+          a: Scalar::from(2u64).to_bytes(),
+          b: Scalar::from(3u64).to_bytes(),
+          c: Scalar::from(7u64).to_bytes(),
+        },
+        UnsatisfiedConstraint {
+          index: 1,
+          a: Scalar::from(5u64).to_bytes(),
+          b: one,
+          c: Scalar::from(6u64).to_bytes(),
+        },
+      ]
+    );
+  }
+
   #[test]
   fn test_padded_constraints() {
     // parameters of the R1CS instance
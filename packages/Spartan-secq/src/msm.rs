@@ -0,0 +1,336 @@
+//! Multi-scalar multiplication over secq256k1.
+//!
+//! Scalars are read straight from the limbs of Spartan's `Scalar`, so no
+//! conversion to `secq256k1::Scalar` is needed. `multiscalar_mul` is for
+//! public data and runs in variable time: small inputs use shared
+//! double-and-add (Straus), larger ones Pippenger's bucket method with a
+//! window width picked from a cost model of the input length.
+//!
+//! The prover's secret scalars go through `multiscalar_mul_ct` instead, or
+//! through a `FixedBaseTable` when the bases are commitment generators.
+use super::group::{GroupElement, ProjectiveGroupElement};
+use super::scalar::Scalar;
+use secq256k1::affine::Group;
+use subtle::{ConditionallySelectable, ConstantTimeEq};
+use zeroize::Zeroizing;
+
+#[cfg(feature = "multicore")]
+use rayon::prelude::*;
+
+const SCALAR_BITS: usize = 256;
+const MAX_WINDOW_BITS: usize = 16;
+// every constant-time bucket addition scans all 2^c buckets twice
+const CT_WINDOW_BITS: usize = 4;
+
+/// Returns `sum_i scalars[i] * points[i]`. Runs in variable time, so it must
+/// only be used on public data.
+pub fn multiscalar_mul(
+  scalars: &[Scalar],
+  points: &[ProjectiveGroupElement],
+) -> ProjectiveGroupElement {
+  assert_eq!(scalars.len(), points.len());
+  let limbs: Vec<[u64; 4]> = scalars.iter().map(Scalar::to_canonical_limbs).collect();
+
+  match window_bits(limbs.len()) {
+    None => straus(&limbs, points),
+    Some(c) => pippenger(&limbs, points, c),
+  }
+}
+
+/// Picks the bucket window width for `n` terms, or `None` when shared
+/// double-and-add is cheaper. Costs are counted in point additions: Pippenger
+/// pays `n` bucket additions plus about `2^(c+1)` to sum the buckets in each
+/// of its `256 / c` windows, while double-and-add pays one addition per set
+/// bit, half of the `256 * n` bits on average. Both pay 256 doublings.
+fn window_bits(n: usize) -> Option<usize> {
+  let straus_cost = n * SCALAR_BITS / 2;
+  (1..=MAX_WINDOW_BITS)
+    .map(|c| (num_windows(c) * (n + (1 << (c + 1))), c))
+    .min()
+    .filter(|(cost, _)| *cost < straus_cost)
+    .map(|(_, c)| c)
+}
+
+fn num_windows(c: usize) -> usize {
+  (SCALAR_BITS - 1) / c + 1
+}
+
+fn bit(limbs: &[u64; 4], i: usize) -> bool {
+  (limbs[i / 64] >> (i % 64)) & 1 == 1
+}
+
+/// Returns bits `start..start + c` of the scalar.
+fn window_digit(limbs: &[u64; 4], start: usize, c: usize) -> usize {
+  let limb = start / 64;
+  let shift = start % 64;
+  let mut digit = limbs[limb] >> shift;
+  if shift + c > 64 && limb + 1 < limbs.len() {
+    digit |= limbs[limb + 1] << (64 - shift);
+  }
+  (digit & ((1 << c) - 1)) as usize
+}
+
+fn straus(limbs: &[[u64; 4]], points: &[ProjectiveGroupElement]) -> ProjectiveGroupElement {
+  let mut acc = ProjectiveGroupElement::identity();
+  for i in (0..SCALAR_BITS).rev() {
+    acc = acc.double();
+    for (scalar, point) in limbs.iter().zip(points) {
+      if bit(scalar, i) {
+        acc += point;
+      }
+    }
+  }
+  acc
+}
+
+/// Returns `sum_i digit_i * points[i]`, where `digit_i` is the window of `c`
+/// bits of the `i`-th scalar starting at bit `start`.
+fn window_sum(
+  limbs: &[[u64; 4]],
+  points: &[ProjectiveGroupElement],
+  start: usize,
+  c: usize,
+) -> ProjectiveGroupElement {
+  let mut buckets = vec![ProjectiveGroupElement::identity(); (1 << c) - 1];
+  for (scalar, point) in limbs.iter().zip(points) {
+    let digit = window_digit(scalar, start, c);
+    if digit != 0 {
+      buckets[digit - 1] += point;
+    }
+  }
+
+  sum_buckets(&buckets)
+}
+
+/// Returns `sum_j (j + 1) * buckets[j]`.
+fn sum_buckets(buckets: &[ProjectiveGroupElement]) -> ProjectiveGroupElement {
+  // running sums weigh bucket j by j + 1 without any scalar multiplication
+  let mut running = ProjectiveGroupElement::identity();
+  let mut sum = ProjectiveGroupElement::identity();
+  for bucket in buckets.iter().rev() {
+    running += bucket;
+    sum += running;
+  }
+  sum
+}
+
+fn pippenger(
+  limbs: &[[u64; 4]],
+  points: &[ProjectiveGroupElement],
+  c: usize,
+) -> ProjectiveGroupElement {
+  #[cfg(feature = "multicore")]
+  let window_sums: Vec<ProjectiveGroupElement> = (0..num_windows(c))
+    .into_par_iter()
+    .map(|w| window_sum(limbs, points, w * c, c))
+    .collect();
+  #[cfg(not(feature = "multicore"))]
+  let window_sums: Vec<ProjectiveGroupElement> = (0..num_windows(c))
+    .map(|w| window_sum(limbs, points, w * c, c))
+    .collect();
+
+  window_sums
+    .iter()
+    .rev()
+    .fold(ProjectiveGroupElement::identity(), |acc, sum| {
+      (0..c).fold(acc, |acc, _| acc.double()) + sum
+    })
+}
+
+/// Constant-time counterpart of `multiscalar_mul`, for secret scalars such as
+/// the witness and commitment blinds. Digits are added into their buckets
+/// with a full scan and never branched on, and the addition formulas are
+/// complete, so the sequence of operations does not depend on the scalars.
+pub fn multiscalar_mul_ct(
+  scalars: &[Scalar],
+  points: &[ProjectiveGroupElement],
+) -> ProjectiveGroupElement {
+  assert_eq!(scalars.len(), points.len());
+  let limbs: Zeroizing<Vec<[u64; 4]>> =
+    Zeroizing::new(scalars.iter().map(Scalar::to_canonical_limbs).collect());
+
+  let c = CT_WINDOW_BITS;
+  let mut acc = ProjectiveGroupElement::identity();
+  for w in (0..num_windows(c)).rev() {
+    acc = (0..c).fold(acc, |acc, _| acc.double());
+    let mut buckets = vec![ProjectiveGroupElement::identity(); 1 << c];
+    for (scalar, point) in limbs.iter().zip(points) {
+      ct_bucket_add(&mut buckets, window_digit(scalar, w * c, c), point);
+    }
+    acc += sum_buckets(&buckets[1..]);
+  }
+  acc
+}
+
+/// Adds `point` to `buckets[digit]` without revealing `digit` through memory
+/// accesses or branches. Bucket 0 absorbs the zero digits and is ignored.
+fn ct_bucket_add(
+  buckets: &mut [ProjectiveGroupElement],
+  digit: usize,
+  point: &ProjectiveGroupElement,
+) {
+  let digit = digit as u64;
+  let mut selected = ProjectiveGroupElement::identity();
+  for (k, bucket) in buckets.iter().enumerate() {
+    selected.conditional_assign(bucket, (k as u64).ct_eq(&digit));
+  }
+  let sum = selected + point;
+  for (k, bucket) in buckets.iter_mut().enumerate() {
+    bucket.conditional_assign(&sum, (k as u64).ct_eq(&digit));
+  }
+}
+
+/// Precomputed multiples of a list of fixed bases, such as commitment
+/// generators. For each base `P` the table holds `2^(c * j) * P` for every
+/// `c`-bit window `j` of a scalar, so a product needs no doublings: the digits
+/// of all windows go into a single set of buckets. Products run in constant
+/// time, as in `multiscalar_mul_ct`.
+#[derive(Clone, Debug)]
+pub struct FixedBaseTable {
+  shifts: Vec<Vec<ProjectiveGroupElement>>,
+}
+
+impl FixedBaseTable {
+  pub fn new(bases: &[GroupElement]) -> Self {
+    let c = CT_WINDOW_BITS;
+    let shifts = bases
+      .iter()
+      .map(|base| {
+        let mut shift = ProjectiveGroupElement::from(base.0);
+        let mut shifts = Vec::with_capacity(num_windows(c));
+        for _ in 0..num_windows(c) {
+          shifts.push(shift);
+          shift = (0..c).fold(shift, |p, _| p.double());
+        }
+        shifts
+      })
+      .collect();
+
+    FixedBaseTable { shifts }
+  }
+
+  /// The table over the bases at `indices`, in that order.
+  pub fn select<I: IntoIterator<Item = usize>>(&self, indices: I) -> Self {
+    FixedBaseTable {
+      shifts: indices
+        .into_iter()
+        .map(|i| self.shifts[i].clone())
+        .collect(),
+    }
+  }
+
+  /// Returns `sum_i scalars[i] * bases[i]`.
+  pub fn multiscalar_mul(&self, scalars: &[Scalar]) -> ProjectiveGroupElement {
+    assert_eq!(scalars.len(), self.shifts.len());
+    self.bucket_sum(scalars.iter().zip(self.shifts.iter()))
+  }
+
+  /// Returns `scalar * bases[index]`.
+  pub fn mul(&self, index: usize, scalar: &Scalar) -> ProjectiveGroupElement {
+    self.bucket_sum(std::iter::once((scalar, &self.shifts[index])))
+  }
+
+  fn bucket_sum<'a, I>(&self, terms: I) -> ProjectiveGroupElement
+  where
+    I: Iterator<Item = (&'a Scalar, &'a Vec<ProjectiveGroupElement>)>,
+  {
+    let c = CT_WINDOW_BITS;
+    let mut buckets = vec![ProjectiveGroupElement::identity(); 1 << c];
+    for (scalar, shifts) in terms {
+      let limbs = Zeroizing::new(scalar.to_canonical_limbs());
+      for (j, shift) in shifts.iter().enumerate() {
+        ct_bucket_add(&mut buckets, window_digit(&limbs, j * c, c), shift);
+      }
+    }
+    sum_buckets(&buckets[1..])
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::scalar::ScalarBytesFromScalar;
+  use rand_core::OsRng;
+
+  fn naive(scalars: &[Scalar], points: &[ProjectiveGroupElement]) -> ProjectiveGroupElement {
+    scalars
+      .iter()
+      .zip(points)
+      .fold(ProjectiveGroupElement::identity(), |acc, (s, p)| {
+        acc + p * Scalar::decompress_scalar(s)
+      })
+  }
+
+  #[test]
+  fn matches_naive() {
+    let mut csprng: OsRng = OsRng;
+    // sizes on both sides of the switch to Pippenger and of several widths
+    for n in [0, 1, 2, 5, 31, 32, 64, 100, 257, 1000] {
+      let scalars: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut csprng)).collect();
+      let points: Vec<ProjectiveGroupElement> = (0..n)
+        .map(|_| ProjectiveGroupElement::random(&mut csprng))
+        .collect();
+      let expected = naive(&scalars, &points);
+      assert_eq!(multiscalar_mul(&scalars, &points), expected);
+      assert_eq!(multiscalar_mul_ct(&scalars, &points), expected);
+    }
+  }
+
+  #[test]
+  fn edge_cases() {
+    let mut csprng: OsRng = OsRng;
+    let g = ProjectiveGroupElement::generator();
+    for n in [3, 300] {
+      let mut scalars: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut csprng)).collect();
+      let mut points = vec![g; n];
+      scalars[0] = Scalar::zero();
+      scalars[1] = -Scalar::one();
+      points[2] = ProjectiveGroupElement::identity();
+      let expected = naive(&scalars, &points);
+      assert_eq!(multiscalar_mul(&scalars, &points), expected);
+      assert_eq!(multiscalar_mul_ct(&scalars, &points), expected);
+    }
+  }
+
+  #[test]
+  fn fixed_base_matches_naive() {
+    let mut csprng: OsRng = OsRng;
+    for n in [1, 2, 3, 64, 129] {
+      let bases: Vec<GroupElement> = (0..n).map(|_| GroupElement::random(&mut csprng)).collect();
+      let projective: Vec<ProjectiveGroupElement> = bases
+        .iter()
+        .map(|b| ProjectiveGroupElement::from(b.0))
+        .collect();
+      let mut scalars: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut csprng)).collect();
+      scalars[0] = -Scalar::one();
+
+      let table = FixedBaseTable::new(&bases);
+      assert_eq!(
+        table.multiscalar_mul(&scalars),
+        naive(&scalars, &projective)
+      );
+      assert_eq!(
+        table.mul(n - 1, &scalars[n - 1]),
+        naive(&scalars[n - 1..], &projective[n - 1..])
+      );
+
+      let reversed = table.select((0..n).rev());
+      scalars.reverse();
+      assert_eq!(
+        reversed.multiscalar_mul(&scalars),
+        table.multiscalar_mul(&scalars.iter().rev().copied().collect::<Vec<_>>())
+      );
+    }
+  }
+
+  #[test]
+  fn check_window_bits() {
+    assert_eq!(window_bits(1), None);
+    assert_eq!(window_bits(8), None);
+    let widths: Vec<usize> = [64, 256, 1024, 1 << 16]
+      .iter()
+      .map(|n| window_bits(*n).unwrap())
+      .collect();
+    assert!(widths.windows(2).all(|w| w[0] <= w[1]));
+  }
+}
@@ -4,14 +4,19 @@
 #![allow(clippy::type_complexity)]
 #![allow(clippy::too_many_arguments)]
 use super::super::errors::ProofVerifyError;
-use super::super::group::{CompressedGroup, GroupElement, VartimeMultiscalarMul};
+use super::super::group::{
+  compress_batch, normalize_batch, CompressedGroup, CompressedGroupExt, GroupElement,
+  ProjectiveGroupElement, VartimeMultiscalarMul,
+};
+use super::super::layout::LayoutReader;
 use super::super::math::Math;
+use super::super::msm::multiscalar_mul_ct;
 use super::super::scalar::Scalar;
 use super::super::transcript::ProofTranscript;
-use crate::group::DecompressEncodedPoint;
 use core::iter;
 use merlin::Transcript;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BulletReductionProof {
@@ -50,8 +55,19 @@ impl BulletReductionProof {
     // Create slices G, H, a, b backed by their respective
     // vectors.  This lets us reslice as we compress the lengths
     // of the vectors in the main loop below.
-    let mut G = &mut G_vec.to_owned()[..];
-    let mut a = &mut a_vec.to_owned()[..];
+    // G is folded in projective coordinates; only L, R and the final
+    // results are ever normalized
+    let mut G = &mut G_vec
+      .iter()
+      .map(|g| ProjectiveGroupElement::from(g.0))
+      .collect::<Vec<_>>()[..];
+    let (Q, H) = (
+      ProjectiveGroupElement::from(Q.0),
+      ProjectiveGroupElement::from(H.0),
+    );
+    // `a` is derived from the witness
+    let mut a_owned = Zeroizing::new(a_vec.to_owned());
+    let mut a = &mut a_owned[..];
     let mut b = &mut b_vec.to_owned()[..];
 
     // All of the input vectors must have a length that is a power of two.
@@ -81,38 +97,43 @@ impl BulletReductionProof {
 
       let (blind_L, blind_R) = blinds_iter.next().unwrap();
 
-      let L = GroupElement::vartime_multiscalar_mul(
-        a_L
+      // a, c and the blinds are secret, so L and R are computed in constant time
+      let L = multiscalar_mul_ct(
+        &a_L
           .iter()
           .chain(iter::once(&c_L))
           .chain(iter::once(blind_L))
-          .map(|s| *s)
-          .collect(),
-        G_R
+          .copied()
+          .collect::<Vec<Scalar>>(),
+        &G_R
           .iter()
-          .chain(iter::once(Q))
-          .chain(iter::once(H))
-          .map(|s| *s)
-          .collect(),
+          .chain(iter::once(&Q))
+          .chain(iter::once(&H))
+          .copied()
+          .collect::<Vec<ProjectiveGroupElement>>(),
       );
 
-      let R = GroupElement::vartime_multiscalar_mul(
-        a_R
+      let R = multiscalar_mul_ct(
+        &a_R
           .iter()
           .chain(iter::once(&c_R))
           .chain(iter::once(blind_R))
-          .map(|s| *s)
-          .collect(),
-        G_L
+          .copied()
+          .collect::<Vec<Scalar>>(),
+        &G_L
           .iter()
-          .chain(iter::once(Q))
-          .chain(iter::once(H))
-          .map(|s| *s)
-          .collect(),
+          .chain(iter::once(&Q))
+          .chain(iter::once(&H))
+          .copied()
+          .collect::<Vec<ProjectiveGroupElement>>(),
       );
 
-      transcript.append_point(b"L", &L.compress());
-      transcript.append_point(b"R", &R.compress());
+      let (L, R) = match compress_batch(&[L, R])[..] {
+        [L, R] => (L, R),
+        _ => unreachable!(),
+      };
+      transcript.append_point(b"L", &L);
+      transcript.append_point(b"R", &R);
 
       let u = transcript.challenge_scalar(b"u");
       let u_inv = u.invert().unwrap();
@@ -120,35 +141,41 @@ impl BulletReductionProof {
       for i in 0..n {
         a_L[i] = a_L[i] * u + u_inv * a_R[i];
         b_L[i] = b_L[i] * u_inv + u * b_R[i];
-        G_L[i] =
-          GroupElement::vartime_multiscalar_mul([u_inv, u].to_vec(), [G_L[i], G_R[i]].to_vec());
+        G_L[i] = ProjectiveGroupElement::vartime_multiscalar_mul(&[u_inv, u], &[G_L[i], G_R[i]]);
       }
 
       blind_fin = blind_fin + blind_L * u * u + blind_R * u_inv * u_inv;
 
-      L_vec.push(L.compress());
-      R_vec.push(R.compress());
+      L_vec.push(L);
+      R_vec.push(R);
 
       a = a_L;
       b = b_L;
       G = G_L;
     }
 
-    let Gamma_hat = GroupElement::vartime_multiscalar_mul(
-      [a[0], a[0] * b[0], blind_fin].to_vec(),
-      [G[0], *Q, *H].to_vec(),
-    );
+    let Gamma_hat = multiscalar_mul_ct(&[a[0], a[0] * b[0], blind_fin], &[G[0], Q, H]);
+    let (Gamma_hat, G_hat) = match normalize_batch(&[Gamma_hat, G[0]])[..] {
+      [Gamma_hat, G_hat] => (Gamma_hat, G_hat),
+      _ => unreachable!(),
+    };
 
     (
       BulletReductionProof { L_vec, R_vec },
       Gamma_hat,
       a[0],
       b[0],
-      G[0],
+      G_hat,
       blind_fin,
     )
   }
 
+  /// Checks the encoded layout of a proof for a vector of length \\(2^{lg\_n}\\).
+  pub fn check_layout(reader: &mut LayoutReader, lg_n: usize) -> Result<(), ProofVerifyError> {
+    reader.point_vec(lg_n)?;
+    reader.point_vec(lg_n)
+  }
+
   /// Computes three vectors of verification scalars \\([u\_{i}^{2}]\\), \\([u\_{i}^{-2}]\\) and \\([s\_{i}]\\) for combined multiscalar multiplication
   /// in a parent protocol. See [inner product protocol notes](index.html#verification-equation) for details.
   /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation within the inner product proof.
@@ -161,10 +188,14 @@ impl BulletReductionProof {
     if lg_n >= 32 {
       // 4 billion multiplications should be enough for anyone
       // and this check prevents overflow in 1<<lg_n below.
-      return Err(ProofVerifyError::InternalError);
+      return Err(ProofVerifyError::MalformedProof(
+        "inner product proof has too many rounds",
+      ));
     }
-    if n != (1 << lg_n) {
-      return Err(ProofVerifyError::InternalError);
+    if n != (1 << lg_n) || self.R_vec.len() != lg_n {
+      return Err(ProofVerifyError::MalformedProof(
+        "inner product proof has the wrong number of rounds",
+      ));
     }
 
     // 1. Recompute x_k,...,x_1 based on the proof transcript
@@ -202,50 +233,38 @@ impl BulletReductionProof {
     Ok((challenges_sq, challenges_inv_sq, s))
   }
 
-  /// This method is for testing that proof generation work,
-  /// but for efficiency the actual protocols would use `verification_scalars`
-  /// method to combine inner product verification with other checks
-  /// in a single multiscalar multiplication.
-  pub fn verify(
+  /// Replays the reduction and returns the scalars `s` with `G_hat = <s, G>`,
+  /// the terms over `L_vec` and `R_vec` that make up `Gamma_hat - Gamma`, and
+  /// `a_hat`, so that the caller can fold both multi-scalar multiplications
+  /// into a larger check
+  pub fn verification_terms(
     &self,
     n: usize,
     a: &[Scalar],
     transcript: &mut Transcript,
-    Gamma: &GroupElement,
-    G: &[GroupElement],
-  ) -> Result<(GroupElement, GroupElement, Scalar), ProofVerifyError> {
+  ) -> Result<(Vec<Scalar>, Vec<(Scalar, GroupElement)>, Scalar), ProofVerifyError> {
     let (u_sq, u_inv_sq, s) = self.verification_scalars(n, transcript)?;
 
     let Ls = self
       .L_vec
       .iter()
-      .map(|p| p.decompress().ok_or(ProofVerifyError::InternalError))
+      .map(|p| p.unpack())
       .collect::<Result<Vec<_>, _>>()?;
 
     let Rs = self
       .R_vec
       .iter()
-      .map(|p| p.decompress().ok_or(ProofVerifyError::InternalError))
+      .map(|p| p.unpack())
       .collect::<Result<Vec<_>, _>>()?;
 
-    let G_hat = GroupElement::vartime_multiscalar_mul(s.clone(), G.to_vec());
     let a_hat = inner_product(a, &s);
+    let terms = u_sq
+      .into_iter()
+      .chain(u_inv_sq)
+      .zip(Ls.into_iter().chain(Rs))
+      .collect();
 
-    let Gamma_hat = GroupElement::vartime_multiscalar_mul(
-      u_sq
-        .iter()
-        .chain(u_inv_sq.iter())
-        .chain(iter::once(&Scalar::one()))
-        .map(|s| *s)
-        .collect(),
-      Ls.iter()
-        .chain(Rs.iter())
-        .chain(iter::once(Gamma))
-        .map(|p| *p)
-        .collect(),
-    );
-
-    Ok((G_hat, Gamma_hat, a_hat))
+    Ok((s, terms, a_hat))
   }
 }
 
@@ -1,14 +1,17 @@
 #![allow(clippy::too_many_arguments)]
 use super::commitments::{Commitments, MultiCommitGens};
 use super::errors::ProofVerifyError;
-use super::group::{CompressedGroup, CompressedGroupExt};
+use super::group::{CompressedGroup, CompressedGroupExt, MultiScalarCheck};
+use super::layout::LayoutReader;
 use super::math::Math;
 use super::random::RandomTape;
 use super::scalar::Scalar;
 use super::transcript::{AppendToTranscript, ProofTranscript};
 use crate::group::DecompressEncodedPoint;
 use merlin::Transcript;
+use once_cell::sync::OnceCell;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
 mod bullet;
 use bullet::BulletReductionProof;
@@ -52,6 +55,11 @@ impl KnowledgeProof {
     (KnowledgeProof { alpha, z1, z2 }, C)
   }
 
+  pub fn check_layout(reader: &mut LayoutReader) -> Result<(), ProofVerifyError> {
+    reader.point()?;
+    reader.scalars(2)
+  }
+
   pub fn verify(
     &self,
     gens_n: &MultiCommitGens,
@@ -64,13 +72,13 @@ impl KnowledgeProof {
 
     let c = transcript.challenge_scalar(b"c");
 
-    let lhs = self.z1.commit(&self.z2, gens_n).compress();
+    let lhs = self.z1.commit_vartime(&self.z2, gens_n).compress();
     let rhs = (c * C.unpack()? + self.alpha.unpack()?).compress();
 
     if lhs == rhs {
       Ok(())
     } else {
-      Err(ProofVerifyError::InternalError)
+      Err(ProofVerifyError::SubProofFailure("knowledge proof"))
     }
   }
 }
@@ -106,7 +114,7 @@ impl EqualityProof {
     let C2 = v2.commit(s2, gens_n).compress();
     C2.append_to_transcript(b"C2", transcript);
 
-    let alpha = (r * gens_n.h).compress();
+    let alpha = gens_n.blind(&r).compress();
     alpha.append_to_transcript(b"alpha", transcript);
 
     let c = transcript.challenge_scalar(b"c");
@@ -116,6 +124,11 @@ impl EqualityProof {
     (EqualityProof { alpha, z }, C1, C2)
   }
 
+  pub fn check_layout(reader: &mut LayoutReader) -> Result<(), ProofVerifyError> {
+    reader.point()?;
+    reader.scalar()
+  }
+
   pub fn verify(
     &self,
     gens_n: &MultiCommitGens,
@@ -123,24 +136,33 @@ impl EqualityProof {
     C1: &CompressedGroup,
     C2: &CompressedGroup,
   ) -> Result<(), ProofVerifyError> {
+    self.verify_deferred(gens_n, transcript, C1, C2)?.verify()
+  }
+
+  /// Runs the verifier up to its final check, which is returned rather than
+  /// evaluated. The check's leading term is over `gens_n.h`.
+  pub fn verify_deferred(
+    &self,
+    gens_n: &MultiCommitGens,
+    transcript: &mut Transcript,
+    C1: &CompressedGroup,
+    C2: &CompressedGroup,
+  ) -> Result<MultiScalarCheck, ProofVerifyError> {
     transcript.append_protocol_name(EqualityProof::protocol_name());
     C1.append_to_transcript(b"C1", transcript);
     C2.append_to_transcript(b"C2", transcript);
     self.alpha.append_to_transcript(b"alpha", transcript);
 
     let c = transcript.challenge_scalar(b"c");
-    let rhs = {
-      let C = C1.unpack()? - C2.unpack()?;
-      (c * C + self.alpha.unpack()?).compress()
-    };
 
-    let lhs = (self.z * gens_n.h).compress();
+    // z * h == c * (C1 - C2) + alpha
+    let mut check = MultiScalarCheck::new("equality proof");
+    check.push(self.z, gens_n.h);
+    check.push(-c, C1.unpack()?);
+    check.push(c, C2.unpack()?);
+    check.push(-Scalar::one(), self.alpha.unpack()?);
 
-    if lhs == rhs {
-      Ok(())
-    } else {
-      Err(ProofVerifyError::InternalError)
-    }
+    Ok(check)
   }
 }
 
@@ -202,6 +224,7 @@ impl ProductProof {
         n: 1,
         G: vec![X.decompress().unwrap()],
         h: gens_n.h,
+        table: OnceCell::new(),
       };
       b3.commit(&b5, gens_X).compress()
     };
@@ -236,11 +259,16 @@ impl ProductProof {
     gens_n: &MultiCommitGens,
     z1: &Scalar,
     z2: &Scalar,
-  ) -> bool {
-    let lhs = (P.decompress().unwrap() + c * X.decompress().unwrap()).compress();
-    let rhs = z1.commit(z2, gens_n).compress();
+  ) -> Result<bool, ProofVerifyError> {
+    let lhs = (P.unpack()? + c * X.unpack()?).compress();
+    let rhs = z1.commit_vartime(z2, gens_n).compress();
 
-    lhs == rhs
+    Ok(lhs == rhs)
+  }
+
+  pub fn check_layout(reader: &mut LayoutReader) -> Result<(), ProofVerifyError> {
+    reader.points(3)?;
+    reader.scalars(5)
   }
 
   pub fn verify(
@@ -268,8 +296,8 @@ impl ProductProof {
 
     let c = transcript.challenge_scalar(b"c");
 
-    if ProductProof::check_equality(&self.alpha, X, &c, gens_n, &z1, &z2)
-      && ProductProof::check_equality(&self.beta, Y, &c, gens_n, &z3, &z4)
+    if ProductProof::check_equality(&self.alpha, X, &c, gens_n, &z1, &z2)?
+      && ProductProof::check_equality(&self.beta, Y, &c, gens_n, &z3, &z4)?
       && ProductProof::check_equality(
         &self.delta,
         Z,
@@ -278,14 +306,15 @@ impl ProductProof {
           n: 1,
           G: vec![X.unpack()?],
           h: gens_n.h,
+          table: OnceCell::new(),
         },
         &z3,
         &z5,
-      )
+      )?
     {
       Ok(())
     } else {
-      Err(ProofVerifyError::InternalError)
+      Err(ProofVerifyError::SubProofFailure("product proof"))
     }
   }
 }
@@ -370,6 +399,12 @@ impl DotProductProof {
     )
   }
 
+  pub fn check_layout(reader: &mut LayoutReader, n: usize) -> Result<(), ProofVerifyError> {
+    reader.points(2)?;
+    reader.scalar_vec(n)?;
+    reader.scalars(2)
+  }
+
   pub fn verify(
     &self,
     gens_1: &MultiCommitGens,
@@ -379,8 +414,16 @@ impl DotProductProof {
     Cx: &CompressedGroup,
     Cy: &CompressedGroup,
   ) -> Result<(), ProofVerifyError> {
-    assert_eq!(gens_n.n, a.len());
-    assert_eq!(gens_1.n, 1);
+    if gens_n.n != a.len() || gens_1.n != 1 {
+      return Err(ProofVerifyError::MalformedProof(
+        "dot product proof generators do not match the public vector",
+      ));
+    }
+    if self.z.len() != a.len() {
+      return Err(ProofVerifyError::MalformedProof(
+        "dot product proof response has the wrong length",
+      ));
+    }
 
     transcript.append_protocol_name(DotProductProof::protocol_name());
     Cx.append_to_transcript(b"Cx", transcript);
@@ -392,19 +435,21 @@ impl DotProductProof {
     let c = transcript.challenge_scalar(b"c");
 
     let mut result =
-      c * Cx.unpack()? + self.delta.unpack()? == self.z.commit(&self.z_delta, gens_n);
+      c * Cx.unpack()? + self.delta.unpack()? == self.z.commit_vartime(&self.z_delta, gens_n);
 
     let dotproduct_z_a = DotProductProof::compute_dotproduct(&self.z, a);
-    result &= c * Cy.unpack()? + self.beta.unpack()? == dotproduct_z_a.commit(&self.z_beta, gens_1);
+    result &=
+      c * Cy.unpack()? + self.beta.unpack()? == dotproduct_z_a.commit_vartime(&self.z_beta, gens_1);
 
     if result {
       Ok(())
     } else {
-      Err(ProofVerifyError::InternalError)
+      Err(ProofVerifyError::SubProofFailure("dot product proof"))
     }
   }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct DotProductProofGens {
   n: usize,
   pub gens_n: MultiCommitGens,
@@ -460,9 +505,11 @@ impl DotProductProofLog {
     let blinds_vec = {
       let v1 = random_tape.random_vector(b"blinds_vec_1", 2 * n.log_2());
       let v2 = random_tape.random_vector(b"blinds_vec_2", 2 * n.log_2());
-      (0..v1.len())
-        .map(|i| (v1[i], v2[i]))
-        .collect::<Vec<(Scalar, Scalar)>>()
+      Zeroizing::new(
+        (0..v1.len())
+          .map(|i| (v1[i], v2[i]))
+          .collect::<Vec<(Scalar, Scalar)>>(),
+      )
     };
 
     let Cx = x_vec.commit(blind_x, &gens.gens_n).compress();
@@ -497,6 +544,7 @@ impl DotProductProofLog {
         n: 1,
         G: vec![g_hat],
         h: gens.gens_1.h,
+        table: OnceCell::new(),
       };
       d.commit(&r_delta, &gens_hat).compress()
     };
@@ -523,7 +571,16 @@ impl DotProductProofLog {
     )
   }
 
-  pub fn verify(
+  pub fn check_layout(reader: &mut LayoutReader, n: usize) -> Result<(), ProofVerifyError> {
+    BulletReductionProof::check_layout(reader, n.log_2())?;
+    reader.points(2)?;
+    reader.scalars(2)
+  }
+
+  /// Runs the verifier up to its final check, which is returned rather than
+  /// evaluated so that it can be batched. The check's leading terms are over
+  /// the generators in `gens`.
+  pub fn verify_deferred(
     &self,
     n: usize,
     gens: &DotProductProofGens,
@@ -531,9 +588,12 @@ impl DotProductProofLog {
     a: &[Scalar],
     Cx: &CompressedGroup,
     Cy: &CompressedGroup,
-  ) -> Result<(), ProofVerifyError> {
-    assert_eq!(gens.n, n);
-    assert_eq!(a.len(), n);
+  ) -> Result<MultiScalarCheck, ProofVerifyError> {
+    if gens.n != n || a.len() != n {
+      return Err(ProofVerifyError::MalformedProof(
+        "dot product proof generators do not match the public vector",
+      ));
+    }
 
     transcript.append_protocol_name(DotProductProofLog::protocol_name());
     Cx.append_to_transcript(b"Cx", transcript);
@@ -543,36 +603,38 @@ impl DotProductProofLog {
     // sample a random base and scale the generator used for
     // the output of the inner product
     let r = transcript.challenge_scalar(b"r");
-    let gens_1_scaled = gens.gens_1.scale(&r);
-
-    let Gamma = Cx.unpack()? + r * Cy.unpack()?;
 
-    let (g_hat, Gamma_hat, a_hat) =
-      self
-        .bullet_reduction_proof
-        .verify(n, a, transcript, &Gamma, &gens.gens_n.G)?;
+    let (s, bullet_terms, a_hat) = self
+      .bullet_reduction_proof
+      .verification_terms(n, a, transcript)?;
     self.delta.append_to_transcript(b"delta", transcript);
     self.beta.append_to_transcript(b"beta", transcript);
 
     let c = transcript.challenge_scalar(b"c");
 
-    let c_s = &c;
-    let beta_s = self.beta.unpack()?;
-    let a_hat_s = &a_hat;
-    let delta_s = self.delta.unpack()?;
-    let z1_s = &self.z1;
-    let z2_s = &self.z2;
-
-    let lhs = ((Gamma_hat * c_s + beta_s) * a_hat_s + delta_s).compress();
-    let rhs = ((g_hat + gens_1_scaled.G[0] * a_hat_s) * z1_s + gens_1_scaled.h * z2_s).compress();
-
-    assert_eq!(lhs, rhs);
+    // with Gamma = Cx + r * Cy, Gamma_hat = Gamma + <bullet terms>, G_hat = <s, G>,
+    // the verifier checks that
+    // (c * Gamma_hat + beta) * a_hat + delta == (G_hat + r * g_1 * a_hat) * z1 + h * z2
+    let c_a_hat = c * a_hat;
+    let mut check = MultiScalarCheck::new("dot product proof (log)");
+    check.extend(
+      s.iter()
+        .map(|s_i| -(self.z1 * s_i))
+        .zip(gens.gens_n.G.iter().copied()),
+    );
+    check.push(-(self.z1 * a_hat * r), gens.gens_1.G[0]);
+    check.push(-self.z2, gens.gens_1.h);
+    check.extend(
+      bullet_terms
+        .into_iter()
+        .map(|(scalar, point)| (c_a_hat * scalar, point)),
+    );
+    check.push(c_a_hat, Cx.unpack()?);
+    check.push(c_a_hat * r, Cy.unpack()?);
+    check.push(a_hat, self.beta.unpack()?);
+    check.push(Scalar::one(), self.delta.unpack()?);
 
-    if lhs == rhs {
-      Ok(())
-    } else {
-      Err(ProofVerifyError::InternalError)
-    }
+    Ok(check)
   }
 }
 
@@ -729,7 +791,9 @@ mod tests {
 
     let mut verifier_transcript = Transcript::new(b"example");
     assert!(proof
-      .verify(n, &gens, &mut verifier_transcript, &a, &Cx, &Cy)
+      .verify_deferred(n, &gens, &mut verifier_transcript, &a, &Cx, &Cy)
+      .unwrap()
+      .verify()
       .is_ok());
   }
 }
@@ -0,0 +1,76 @@
+//! On-disk format for `NIZKGens` and `SNARKGens`.
+//!
+//! The encoding is a bincode `GensHeader` followed by the bincode encoding of
+//! the generators. The header records which kind of generators follow and the
+//! R1CS dimensions they were created for, so that stale or mismatched files
+//! are rejected before the (potentially large) body is decoded.
+use super::errors::GensError;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+const GENS_MAGIC: [u8; 4] = *b"SPGN";
+const GENS_FORMAT_VERSION: u32 = 1;
+
+#[derive(Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GensHeader {
+  magic: [u8; 4],
+  version: u32,
+  label: Vec<u8>,
+  num_cons: u64,
+  num_vars: u64,
+  num_inputs: u64,
+  num_nz_entries: u64,
+}
+
+impl GensHeader {
+  pub fn new(
+    label: &[u8],
+    num_cons: usize,
+    num_vars: usize,
+    num_inputs: usize,
+    num_nz_entries: usize,
+  ) -> Self {
+    GensHeader {
+      magic: GENS_MAGIC,
+      version: GENS_FORMAT_VERSION,
+      label: label.to_vec(),
+      num_cons: num_cons as u64,
+      num_vars: num_vars as u64,
+      num_inputs: num_inputs as u64,
+      num_nz_entries: num_nz_entries as u64,
+    }
+  }
+}
+
+pub fn encode_gens<T: Serialize>(header: &GensHeader, gens: &T) -> Vec<u8> {
+  let mut bytes = bincode::serialize(header).unwrap();
+  bytes.extend(bincode::serialize(gens).unwrap());
+  bytes
+}
+
+pub fn decode_gens<T: DeserializeOwned>(
+  bytes: &[u8],
+  expected: &GensHeader,
+) -> Result<T, GensError> {
+  if !bytes.starts_with(&GENS_MAGIC) {
+    return Err(GensError::InvalidMagic);
+  }
+
+  let mut reader = bytes;
+  let header: GensHeader =
+    bincode::deserialize_from(&mut reader).map_err(|e| GensError::Malformed(e.to_string()))?;
+
+  if header.version != GENS_FORMAT_VERSION {
+    return Err(GensError::UnsupportedVersion(header.version));
+  }
+  if header != *expected {
+    return Err(GensError::HeaderMismatch);
+  }
+
+  let gens: T =
+    bincode::deserialize_from(&mut reader).map_err(|e| GensError::Malformed(e.to_string()))?;
+  if !reader.is_empty() {
+    return Err(GensError::Malformed("trailing bytes".to_string()));
+  }
+  Ok(gens)
+}
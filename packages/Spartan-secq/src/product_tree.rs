@@ -1,11 +1,16 @@
 #![allow(dead_code)]
+#![allow(clippy::type_complexity)]
 use super::dense_mlpoly::DensePolynomial;
 use super::dense_mlpoly::EqPolynomial;
+use super::errors::ProofVerifyError;
+use super::layout::LayoutReader;
 use super::math::Math;
 use super::scalar::Scalar;
 use super::sumcheck::SumcheckInstanceProof;
 use super::transcript::ProofTranscript;
 use merlin::Transcript;
+#[cfg(feature = "multicore")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug)]
@@ -15,6 +20,28 @@ pub struct ProductCircuit {
 }
 
 impl ProductCircuit {
+  #[cfg(feature = "multicore")]
+  fn compute_layer(
+    inp_left: &DensePolynomial,
+    inp_right: &DensePolynomial,
+  ) -> (DensePolynomial, DensePolynomial) {
+    let len = inp_left.len() + inp_right.len();
+    let outp_left = (0..len / 4)
+      .into_par_iter()
+      .map(|i| inp_left[i] * inp_right[i])
+      .collect::<Vec<Scalar>>();
+    let outp_right = (len / 4..len / 2)
+      .into_par_iter()
+      .map(|i| inp_left[i] * inp_right[i])
+      .collect::<Vec<Scalar>>();
+
+    (
+      DensePolynomial::new(outp_left),
+      DensePolynomial::new(outp_right),
+    )
+  }
+
+  #[cfg(not(feature = "multicore"))]
   fn compute_layer(
     inp_left: &DensePolynomial,
     inp_right: &DensePolynomial,
@@ -122,11 +149,10 @@ impl LayerProof {
     num_rounds: usize,
     degree_bound: usize,
     transcript: &mut Transcript,
-  ) -> (Scalar, Vec<Scalar>) {
+  ) -> Result<(Scalar, Vec<Scalar>), ProofVerifyError> {
     self
       .proof
       .verify(claim, num_rounds, degree_bound, transcript)
-      .unwrap()
   }
 }
 
@@ -146,11 +172,10 @@ impl LayerProofBatched {
     num_rounds: usize,
     degree_bound: usize,
     transcript: &mut Transcript,
-  ) -> (Scalar, Vec<Scalar>) {
+  ) -> Result<(Scalar, Vec<Scalar>), ProofVerifyError> {
     self
       .proof
       .verify(claim, num_rounds, degree_bound, transcript)
-      .unwrap()
   }
 }
 
@@ -222,26 +247,36 @@ impl ProductCircuitEvalProof {
     eval: Scalar,
     len: usize,
     transcript: &mut Transcript,
-  ) -> (Scalar, Vec<Scalar>) {
+  ) -> Result<(Scalar, Vec<Scalar>), ProofVerifyError> {
     let num_layers = len.log_2();
     let mut claim = eval;
     let mut rand: Vec<Scalar> = Vec::new();
     //let mut num_rounds = 0;
-    assert_eq!(self.proof.len(), num_layers);
+    if self.proof.len() != num_layers {
+      return Err(ProofVerifyError::MalformedProof(
+        "product circuit proof has the wrong number of layers",
+      ));
+    }
     for (num_rounds, i) in (0..num_layers).enumerate() {
-      let (claim_last, rand_prod) = self.proof[i].verify(claim, num_rounds, 3, transcript);
+      let (claim_last, rand_prod) = self.proof[i].verify(claim, num_rounds, 3, transcript)?;
 
       let claims_prod = &self.proof[i].claims;
+      if claims_prod.len() != 2 {
+        return Err(ProofVerifyError::MalformedProof(
+          "product circuit layer has the wrong number of claims",
+        ));
+      }
       transcript.append_scalar(b"claim_prod_left", &claims_prod[0]);
       transcript.append_scalar(b"claim_prod_right", &claims_prod[1]);
 
-      assert_eq!(rand.len(), rand_prod.len());
       let eq: Scalar = (0..rand.len())
         .map(|i| {
           rand[i] * rand_prod[i] + (Scalar::one() - rand[i]) * (Scalar::one() - rand_prod[i])
         })
         .product();
-      assert_eq!(claims_prod[0] * claims_prod[1] * eq, claim_last);
+      if claims_prod[0] * claims_prod[1] * eq != claim_last {
+        return Err(ProofVerifyError::EvaluationMismatch);
+      }
 
       // produce a random challenge
       let r_layer = transcript.challenge_scalar(b"challenge_r_layer");
@@ -251,7 +286,7 @@ impl ProductCircuitEvalProof {
       rand = ext;
     }
 
-    (claim, rand)
+    Ok((claim, rand))
   }
 }
 
@@ -382,17 +417,53 @@ impl ProductCircuitEvalProofBatched {
     )
   }
 
+  // checks the encoded layout of a proof for circuits over `len` leaves, batching
+  // `num_prod` product circuits with `num_dotp` dot-product circuits
+  pub fn check_layout(
+    reader: &mut LayoutReader,
+    len: usize,
+    num_prod: usize,
+    num_dotp: usize,
+  ) -> Result<(), ProofVerifyError> {
+    let num_layers = len.log_2();
+    reader.vec_len(num_layers)?;
+    for num_rounds in 0..num_layers {
+      SumcheckInstanceProof::check_layout(reader, num_rounds, 3)?;
+      reader.scalar_vec(num_prod)?;
+      reader.scalar_vec(num_prod)?;
+    }
+
+    // the dot-product claims are only produced alongside the last layer
+    let num_dotp = if num_layers > 0 { num_dotp } else { 0 };
+    reader.scalar_vec(num_dotp)?;
+    reader.scalar_vec(num_dotp)?;
+    reader.scalar_vec(num_dotp)
+  }
+
   pub fn verify(
     &self,
     claims_prod_vec: &[Scalar],
     claims_dotp_vec: &[Scalar],
     len: usize,
     transcript: &mut Transcript,
-  ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>) {
+  ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofVerifyError> {
     let num_layers = len.log_2();
     let mut rand: Vec<Scalar> = Vec::new();
     //let mut num_rounds = 0;
-    assert_eq!(self.proof.len(), num_layers);
+    if self.proof.len() != num_layers {
+      return Err(ProofVerifyError::MalformedProof(
+        "product circuit proof has the wrong number of layers",
+      ));
+    }
+    let (claims_dotp_left, claims_dotp_right, claims_dotp_weight) = &self.claims_dotp;
+    if claims_dotp_left.len() != claims_dotp_vec.len()
+      || claims_dotp_right.len() != claims_dotp_vec.len()
+      || claims_dotp_weight.len() != claims_dotp_vec.len()
+    {
+      return Err(ProofVerifyError::MalformedProof(
+        "product circuit proof has the wrong number of dot product claims",
+      ));
+    }
 
     let mut claims_to_verify = claims_prod_vec.to_owned();
     let mut claims_to_verify_dotp: Vec<Scalar> = Vec::new();
@@ -410,19 +481,23 @@ impl ProductCircuitEvalProofBatched {
         .map(|i| claims_to_verify[i] * coeff_vec[i])
         .sum();
 
-      let (claim_last, rand_prod) = self.proof[i].verify(claim, num_rounds, 3, transcript);
+      let (claim_last, rand_prod) = self.proof[i].verify(claim, num_rounds, 3, transcript)?;
 
       let claims_prod_left = &self.proof[i].claims_prod_left;
       let claims_prod_right = &self.proof[i].claims_prod_right;
-      assert_eq!(claims_prod_left.len(), claims_prod_vec.len());
-      assert_eq!(claims_prod_right.len(), claims_prod_vec.len());
+      if claims_prod_left.len() != claims_prod_vec.len()
+        || claims_prod_right.len() != claims_prod_vec.len()
+      {
+        return Err(ProofVerifyError::MalformedProof(
+          "product circuit layer has the wrong number of claims",
+        ));
+      }
 
       for i in 0..claims_prod_vec.len() {
         transcript.append_scalar(b"claim_prod_left", &claims_prod_left[i]);
         transcript.append_scalar(b"claim_prod_right", &claims_prod_right[i]);
       }
 
-      assert_eq!(rand.len(), rand_prod.len());
       let eq: Scalar = (0..rand.len())
         .map(|i| {
           rand[i] * rand_prod[i] + (Scalar::one() - rand[i]) * (Scalar::one() - rand_prod[i])
@@ -435,7 +510,6 @@ impl ProductCircuitEvalProofBatched {
       // add claims from the dotp instances
       if i == num_layers - 1 {
         let num_prod_instances = claims_prod_vec.len();
-        let (claims_dotp_left, claims_dotp_right, claims_dotp_weight) = &self.claims_dotp;
         for i in 0..claims_dotp_left.len() {
           transcript.append_scalar(b"claim_dotp_left", &claims_dotp_left[i]);
           transcript.append_scalar(b"claim_dotp_right", &claims_dotp_right[i]);
@@ -448,7 +522,9 @@ impl ProductCircuitEvalProofBatched {
         }
       }
 
-      assert_eq!(claim_expected, claim_last);
+      if claim_expected != claim_last {
+        return Err(ProofVerifyError::EvaluationMismatch);
+      }
 
       // produce a random challenge
       let r_layer = transcript.challenge_scalar(b"challenge_r_layer");
@@ -459,8 +535,6 @@ impl ProductCircuitEvalProofBatched {
 
       // add claims to verify for dotp circuit
       if i == num_layers - 1 {
-        let (claims_dotp_left, claims_dotp_right, claims_dotp_weight) = &self.claims_dotp;
-
         for i in 0..claims_dotp_vec.len() / 2 {
           // combine left claims
           let claim_left = claims_dotp_left[2 * i]
@@ -481,6 +555,6 @@ impl ProductCircuitEvalProofBatched {
       ext.extend(rand_prod);
       rand = ext;
     }
-    (claims_to_verify, claims_to_verify_dotp, rand)
+    Ok((claims_to_verify, claims_to_verify_dotp, rand))
   }
 }
@@ -2,6 +2,7 @@ use crate::transcript::AppendToTranscript;
 
 use super::dense_mlpoly::DensePolynomial;
 use super::errors::ProofVerifyError;
+use super::layout::LayoutReader;
 use super::math::Math;
 use super::random::RandomTape;
 use super::scalar::Scalar;
@@ -25,6 +26,7 @@ pub struct R1CSInstance {
   C: SparseMatPolynomial,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct R1CSCommitmentGens {
   gens: SparseMatPolyCommitmentGens,
 }
@@ -233,6 +235,16 @@ impl R1CSInstance {
   }
 
   pub fn is_sat(&self, vars: &[Scalar], input: &[Scalar]) -> bool {
+    self.unsatisfied_constraints(vars, input).is_empty()
+  }
+
+  /// Returns the index and the values of `Az`, `Bz` and `Cz` for each
+  /// constraint with `Az * Bz != Cz`
+  pub fn unsatisfied_constraints(
+    &self,
+    vars: &[Scalar],
+    input: &[Scalar],
+  ) -> Vec<(usize, Scalar, Scalar, Scalar)> {
     assert_eq!(vars.len(), self.num_vars);
     assert_eq!(input.len(), self.num_inputs);
 
@@ -257,11 +269,10 @@ impl R1CSInstance {
     assert_eq!(Az.len(), self.num_cons);
     assert_eq!(Bz.len(), self.num_cons);
     assert_eq!(Cz.len(), self.num_cons);
-    let res: usize = (0..self.num_cons)
-      .map(|i| usize::from(Az[i] * Bz[i] != Cz[i]))
-      .sum();
-
-    res == 0
+    (0..self.num_cons)
+      .filter(|i| Az[*i] * Bz[*i] != Cz[*i])
+      .map(|i| (i, Az[i], Bz[i], Cz[i]))
+      .collect()
   }
 
   pub fn multiply_vec(
@@ -289,9 +300,23 @@ impl R1CSInstance {
     assert_eq!(num_rows, self.num_cons);
     assert!(num_cols > self.num_vars);
 
-    let evals_A = self.A.compute_eval_table_sparse(evals, num_rows, num_cols);
-    let evals_B = self.B.compute_eval_table_sparse(evals, num_rows, num_cols);
-    let evals_C = self.C.compute_eval_table_sparse(evals, num_rows, num_cols);
+    #[cfg(feature = "multicore")]
+    let (evals_A, (evals_B, evals_C)) = rayon::join(
+      || self.A.compute_eval_table_sparse(evals, num_rows, num_cols),
+      || {
+        rayon::join(
+          || self.B.compute_eval_table_sparse(evals, num_rows, num_cols),
+          || self.C.compute_eval_table_sparse(evals, num_rows, num_cols),
+        )
+      },
+    );
+
+    #[cfg(not(feature = "multicore"))]
+    let (evals_A, evals_B, evals_C) = (
+      self.A.compute_eval_table_sparse(evals, num_rows, num_cols),
+      self.B.compute_eval_table_sparse(evals, num_rows, num_cols),
+      self.C.compute_eval_table_sparse(evals, num_rows, num_cols),
+    );
 
     (evals_A, evals_B, evals_C)
   }
@@ -346,6 +371,13 @@ impl R1CSEvalProof {
     R1CSEvalProof { proof }
   }
 
+  pub fn check_layout(
+    reader: &mut LayoutReader,
+    comm: &R1CSCommitment,
+  ) -> Result<(), ProofVerifyError> {
+    SparseMatPolyEvalProof::check_layout(reader, &comm.comm)
+  }
+
   pub fn verify(
     &self,
     comm: &R1CSCommitment,
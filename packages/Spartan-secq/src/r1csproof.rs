@@ -4,7 +4,10 @@ use super::dense_mlpoly::{
   DensePolynomial, EqPolynomial, PolyCommitment, PolyCommitmentGens, PolyEvalProof,
 };
 use super::errors::ProofVerifyError;
-use super::group::{CompressedGroup, GroupElement, VartimeMultiscalarMul};
+use super::group::{
+  CompressedGroup, CompressedGroupExt, GroupElement, MultiScalarCheck, VartimeMultiscalarMul,
+};
+use super::layout::LayoutReader;
 use super::math::Math;
 use super::nizk::{EqualityProof, KnowledgeProof, ProductProof};
 use super::r1csinstance::R1CSInstance;
@@ -14,10 +17,10 @@ use super::sparse_mlpoly::{SparsePolyEntry, SparsePolynomial};
 use super::sumcheck::ZKSumcheckInstanceProof;
 use super::timer::Timer;
 use super::transcript::{AppendToTranscript, ProofTranscript};
-use crate::group::DecompressEncodedPoint;
 use core::iter;
 use merlin::Transcript;
 use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct R1CSProof {
@@ -37,6 +40,7 @@ pub struct R1CSProof {
   proof_eq_sc_phase2: EqualityProof,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct R1CSSumcheckGens {
   gens_1: MultiCommitGens,
   gens_3: MultiCommitGens,
@@ -58,6 +62,7 @@ impl R1CSSumcheckGens {
   }
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct R1CSGens {
   gens_sc: R1CSSumcheckGens,
   gens_pc: PolyCommitmentGens,
@@ -142,7 +147,7 @@ impl R1CSProof {
 
   pub fn prove(
     inst: &R1CSInstance,
-    vars: Vec<Scalar>,
+    vars: &[Scalar],
     input: &[Scalar],
     gens: &R1CSGens,
     transcript: &mut Transcript,
@@ -159,7 +164,7 @@ impl R1CSProof {
     let timer_commit = Timer::new("polycommit");
     let (poly_vars, comm_vars, blinds_vars) = {
       // create a multilinear polynomial using the supplied assignment for variables
-      let poly_vars = DensePolynomial::new(vars.clone());
+      let poly_vars = DensePolynomial::new(vars.to_vec());
 
       // produce a commitment to the satisfying assignment
       let (comm_vars, blinds_vars) = poly_vars.commit(&gens.gens_pc, Some(random_tape));
@@ -173,13 +178,15 @@ impl R1CSProof {
     let timer_sc_proof_phase1 = Timer::new("prove_sc_phase_one");
 
     // append input to variables to create a single vector z
-    let z = {
-      let num_inputs = input.len();
+    let mut z = {
       let num_vars = vars.len();
-      let mut z = vars;
-      z.extend(&vec![Scalar::one()]); // add constant term in z
+      // allocated at its final size, so that no reallocation leaves a copy of
+      // the witness behind in freed memory
+      let mut z = Zeroizing::new(Vec::with_capacity(2 * num_vars));
+      z.extend_from_slice(vars);
+      z.push(Scalar::one()); // add constant term in z
       z.extend(input);
-      z.extend(&vec![Scalar::zero(); num_vars - num_inputs - 1]); // we will pad with zeros
+      z.resize(2 * num_vars, Scalar::zero()); // we will pad with zeros
       z
     };
 
@@ -286,7 +293,8 @@ impl R1CSProof {
       num_rounds_y,
       &claim_phase2,
       &blind_claim_phase2,
-      &mut DensePolynomial::new(z),
+      // the polynomial takes the witness over and wipes it on drop
+      &mut DensePolynomial::new(std::mem::take(&mut *z)),
       &mut DensePolynomial::new(evals_ABC),
       &gens.gens_sc,
       transcript,
@@ -347,6 +355,26 @@ impl R1CSProof {
     )
   }
 
+  // checks the encoded layout of a proof for an instance of the given size, field by field
+  pub fn check_layout(
+    reader: &mut LayoutReader,
+    num_vars: usize,
+    num_cons: usize,
+  ) -> Result<(), ProofVerifyError> {
+    let (num_rounds_x, num_rounds_y) = (num_cons.log_2(), (2 * num_vars).log_2());
+
+    PolyCommitment::check_layout(reader, num_vars.log_2())?;
+    ZKSumcheckInstanceProof::check_layout(reader, num_rounds_x, 3)?;
+    reader.points(4)?;
+    KnowledgeProof::check_layout(reader)?;
+    ProductProof::check_layout(reader)?;
+    EqualityProof::check_layout(reader)?;
+    ZKSumcheckInstanceProof::check_layout(reader, num_rounds_y, 2)?;
+    reader.point()?;
+    PolyEvalProof::check_layout(reader, num_vars.log_2())?;
+    EqualityProof::check_layout(reader)
+  }
+
   pub fn verify(
     &self,
     num_vars: usize,
@@ -356,6 +384,26 @@ impl R1CSProof {
     transcript: &mut Transcript,
     gens: &R1CSGens,
   ) -> Result<(Vec<Scalar>, Vec<Scalar>), ProofVerifyError> {
+    let (rx, ry, checks) =
+      self.verify_deferred(num_vars, num_cons, input, evals, transcript, gens)?;
+    for check in checks {
+      check.verify()?;
+    }
+    Ok((rx, ry))
+  }
+
+  /// Runs the verifier but returns the final checks of the evaluation proof
+  /// for `Z(ry)` and of the last equality proof instead of evaluating them
+  #[allow(clippy::type_complexity)]
+  pub fn verify_deferred(
+    &self,
+    num_vars: usize,
+    num_cons: usize,
+    input: &[Scalar],
+    evals: &(Scalar, Scalar, Scalar),
+    transcript: &mut Transcript,
+    gens: &R1CSGens,
+  ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<MultiScalarCheck>), ProofVerifyError> {
     transcript.append_protocol_name(R1CSProof::protocol_name());
 
     input.append_to_transcript(b"input", transcript);
@@ -373,7 +421,7 @@ impl R1CSProof {
 
     // verify the first sum-check instance
     let claim_phase1 = Scalar::zero()
-      .commit(&Scalar::zero(), &gens.gens_sc.gens_1)
+      .commit_vartime(&Scalar::zero(), &gens.gens_sc.gens_1)
       .compress();
     let (comm_claim_post_phase1, rx) = self.sc_proof_phase1.verify(
       &claim_phase1,
@@ -404,9 +452,8 @@ impl R1CSProof {
     let taus_bound_rx: Scalar = (0..rx.len())
       .map(|i| rx[i] * tau[i] + (Scalar::one() - rx[i]) * (Scalar::one() - tau[i]))
       .product();
-    let expected_claim_post_phase1 = (taus_bound_rx
-      * (comm_prod_Az_Bz_claims.decompress().unwrap() - comm_Cz_claim.decompress().unwrap()))
-    .compress();
+    let expected_claim_post_phase1 =
+      (taus_bound_rx * (comm_prod_Az_Bz_claims.unpack()? - comm_Cz_claim.unpack()?)).compress();
 
     // verify proof that expected_claim_post_phase1 == claim_post_phase1
     self.proof_eq_sc_phase1.verify(
@@ -423,15 +470,12 @@ impl R1CSProof {
 
     // r_A * comm_Az_claim + r_B * comm_Bz_claim + r_C * comm_Cz_claim;
     let comm_claim_phase2 = GroupElement::vartime_multiscalar_mul(
-      iter::once(r_A)
-        .chain(iter::once(r_B))
-        .chain(iter::once(r_C))
-        .collect(),
-      iter::once(&comm_Az_claim)
+      &[r_A, r_B, r_C],
+      &iter::once(&comm_Az_claim)
         .chain(iter::once(&comm_Bz_claim))
         .chain(iter::once(&comm_Cz_claim))
-        .map(|pt| pt.decompress().unwrap())
-        .collect(),
+        .map(|pt| pt.unpack())
+        .collect::<Result<Vec<GroupElement>, ProofVerifyError>>()?,
     )
     .compress();
 
@@ -446,7 +490,7 @@ impl R1CSProof {
     )?;
 
     // verify Z(ry) proof against the initial commitment
-    self.proof_eval_vars_at_ry.verify(
+    let check_eval_vars_at_ry = self.proof_eval_vars_at_ry.verify_deferred(
       &gens.gens_pc,
       transcript,
       &ry[1..],
@@ -468,15 +512,11 @@ impl R1CSProof {
 
     // compute commitment to eval_Z_at_ry = (Scalar::one() - ry[0]) * self.eval_vars_at_ry + ry[0] * poly_input_eval
     let comm_eval_Z_at_ry = GroupElement::vartime_multiscalar_mul(
-      iter::once(Scalar::one() - ry[0])
-        .chain(iter::once(ry[0]))
-        .map(|s| s)
-        .collect(),
-      iter::once(self.comm_vars_at_ry.decompress().unwrap())
-        .chain(iter::once(
-          poly_input_eval.commit(&Scalar::zero(), &gens.gens_pc.gens.gens_1),
-        ))
-        .collect(),
+      &[Scalar::one() - ry[0], ry[0]],
+      &[
+        self.comm_vars_at_ry.unpack()?,
+        poly_input_eval.commit_vartime(&Scalar::zero(), &gens.gens_pc.gens.gens_1),
+      ],
     );
 
     // perform the final check in the second sum-check protocol
@@ -484,14 +524,14 @@ impl R1CSProof {
     let expected_claim_post_phase2 =
       ((r_A * eval_A_r + r_B * eval_B_r + r_C * eval_C_r) * comm_eval_Z_at_ry).compress();
     // verify proof that expected_claim_post_phase1 == claim_post_phase1
-    self.proof_eq_sc_phase2.verify(
+    let check_eq_sc_phase2 = self.proof_eq_sc_phase2.verify_deferred(
       &gens.gens_sc.gens_1,
       transcript,
       &expected_claim_post_phase2,
       &comm_claim_post_phase2,
     )?;
 
-    Ok((rx, ry))
+    Ok((rx, ry, vec![check_eval_vars_at_ry, check_eq_sc_phase2]))
   }
 }
 
@@ -584,7 +624,7 @@ mod tests {
     let mut prover_transcript = Transcript::new(b"example");
     let (proof, rx, ry) = R1CSProof::prove(
       &inst,
-      vars,
+      &vars,
       &input,
       &gens,
       &mut prover_transcript,
@@ -1,19 +1,48 @@
 use super::scalar::Scalar;
-use super::transcript::ProofTranscript;
+use super::transcript::{AppendToTranscript, ProofTranscript};
 use merlin::Transcript;
-use rand_core::OsRng;
+use rand_core::{CryptoRng, OsRng, RngCore};
+use zeroize::Zeroizing;
 pub struct RandomTape {
   tape: Transcript,
 }
 
 impl RandomTape {
   pub fn new(name: &'static [u8]) -> Self {
-    let tape = {
-      let mut rng = OsRng::default();
-      let mut tape = Transcript::new(name);
-      tape.append_scalar(b"init_randomness", &Scalar::random(&mut rng));
-      tape
-    };
+    RandomTape::new_with_rng(name, &mut OsRng)
+  }
+
+  pub fn new_with_rng<R: RngCore + CryptoRng>(name: &'static [u8], rng: &mut R) -> Self {
+    let mut tape = Transcript::new(name);
+    tape.append_scalar(b"init_randomness", &Scalar::random(rng));
+    Self { tape }
+  }
+
+  /// A tape derived from `seed`, the public `inputs`, the secret `witness`
+  /// and the prover's `transcript` alone. Starting from the transcript binds
+  /// the statement and any context the verifier's challenges depend on, so
+  /// two proofs never share randomness unless they are identical.
+  ///
+  /// `extra_entropy` is the additional input `k'` of RFC 6979 §3.6: fresh
+  /// randomness makes the tape hedged rather than deterministic, guarding
+  /// against faults and a leaked `seed`, while with `None` the tape only
+  /// depends on the seed, the transcript, the inputs and the witness.
+  pub fn new_deterministic(
+    name: &'static [u8],
+    seed: &[u8],
+    extra_entropy: Option<&[u8]>,
+    transcript: &Transcript,
+    inputs: &[Scalar],
+    witness: &[Scalar],
+  ) -> Self {
+    let mut tape = transcript.clone();
+    tape.append_message(b"random_tape", name);
+    tape.append_message(b"seed", seed);
+    if let Some(extra_entropy) = extra_entropy {
+      tape.append_message(b"extra_entropy", extra_entropy);
+    }
+    inputs.append_to_transcript(b"inputs", &mut tape);
+    witness.append_to_transcript(b"witness", &mut tape);
     Self { tape }
   }
 
@@ -21,7 +50,8 @@ impl RandomTape {
     self.tape.challenge_scalar(label)
   }
 
-  pub fn random_vector(&mut self, label: &'static [u8], len: usize) -> Vec<Scalar> {
-    self.tape.challenge_vector(label, len)
+  /// The vectors drawn from the tape are blinds, so they are wiped on drop.
+  pub fn random_vector(&mut self, label: &'static [u8], len: usize) -> Zeroizing<Vec<Scalar>> {
+    Zeroizing::new(self.tape.challenge_vector(label, len))
   }
 }
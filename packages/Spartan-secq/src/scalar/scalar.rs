@@ -234,7 +234,10 @@ impl<'de> Visitor<'de> for U64ArrayVisitor {
     for i in 0..4 {
       let mut val: u64 = 0;
       for j in 0..8 {
-        val += (seq.next_element::<u8>().unwrap().unwrap() as u64) * 256u64.pow(j)
+        let byte = seq
+          .next_element::<u8>()?
+          .ok_or_else(|| serde::de::Error::invalid_length(i * 8 + j as usize, &self))?;
+        val += (byte as u64) * 256u64.pow(j)
       }
       result[i] = val;
     }
@@ -481,19 +484,25 @@ impl Scalar {
   /// Converts an element of `Scalar` into a byte representation in
   /// little-endian byte order.
   pub fn to_bytes(&self) -> [u8; 32] {
+    let limbs = self.to_canonical_limbs();
+
+    let mut res = [0; 32];
+    res[..8].copy_from_slice(&limbs[0].to_le_bytes());
+    res[8..16].copy_from_slice(&limbs[1].to_le_bytes());
+    res[16..24].copy_from_slice(&limbs[2].to_le_bytes());
+    res[24..32].copy_from_slice(&limbs[3].to_le_bytes());
+
+    res
+  }
+
+  /// Returns the canonical (non-Montgomery) value as little-endian limbs.
+  pub(crate) fn to_canonical_limbs(&self) -> [u64; 4] {
     // Turn into canonical form by computing
     // (a.R) / R = a
     let tmp = Scalar::montgomery_reduce(
       self.0[0], self.0[1], self.0[2], self.0[3], self.0[4], 0, 0, 0, 0,
     );
-
-    let mut res = [0; 32];
-    res[..8].copy_from_slice(&tmp.0[0].to_le_bytes());
-    res[8..16].copy_from_slice(&tmp.0[1].to_le_bytes());
-    res[16..24].copy_from_slice(&tmp.0[2].to_le_bytes());
-    res[24..32].copy_from_slice(&tmp.0[3].to_le_bytes());
-
-    res
+    [tmp.0[0], tmp.0[1], tmp.0[2], tmp.0[3]]
   }
 
   /// Converts a 512-bit little endian integer into
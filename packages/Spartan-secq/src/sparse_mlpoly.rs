@@ -6,6 +6,7 @@ use super::dense_mlpoly::{
   EqPolynomial, IdentityPolynomial, PolyCommitment, PolyCommitmentGens, PolyEvalProof,
 };
 use super::errors::ProofVerifyError;
+use super::layout::LayoutReader;
 use super::math::Math;
 use super::product_tree::{DotProductCircuit, ProductCircuit, ProductCircuitEvalProofBatched};
 use super::random::RandomTape;
@@ -14,6 +15,8 @@ use super::timer::Timer;
 use super::transcript::{AppendToTranscript, ProofTranscript};
 use core::cmp::Ordering;
 use merlin::Transcript;
+#[cfg(feature = "multicore")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -283,6 +286,7 @@ pub struct MultiSparseMatPolynomialAsDense {
   comb_mem: DensePolynomial,
 }
 
+#[derive(Serialize, Deserialize)]
 pub struct SparseMatPolyCommitmentGens {
   gens_ops: PolyCommitmentGens,
   gens_mem: PolyCommitmentGens,
@@ -470,6 +474,39 @@ impl SparseMatPolynomial {
       })
   }
 
+  #[cfg(feature = "multicore")]
+  pub fn compute_eval_table_sparse(
+    &self,
+    rx: &[Scalar],
+    num_rows: usize,
+    num_cols: usize,
+  ) -> Vec<Scalar> {
+    assert_eq!(rx.len(), num_rows);
+
+    // each worker accumulates into its own table; field addition is exact,
+    // so summing the tables gives the same result as the sequential loop
+    self
+      .M
+      .par_iter()
+      .fold(
+        || vec![Scalar::zero(); num_cols],
+        |mut M_evals, entry| {
+          M_evals[entry.col] += rx[entry.row] * entry.val;
+          M_evals
+        },
+      )
+      .reduce(
+        || vec![Scalar::zero(); num_cols],
+        |mut M_evals, other| {
+          for (eval, other_eval) in M_evals.iter_mut().zip(other.iter()) {
+            *eval += other_eval;
+          }
+          M_evals
+        },
+      )
+  }
+
+  #[cfg(not(feature = "multicore"))]
   pub fn compute_eval_table_sparse(
     &self,
     rx: &[Scalar],
@@ -856,18 +893,33 @@ impl HashLayerProof {
     let (rand_mem, _rand_ops) = rand;
     let (claim_init, claim_read, claim_write, claim_audit) = claims;
 
+    let num_ops = eval_ops_addr.len();
+    if eval_ops_val.len() != num_ops
+      || eval_read_ts.len() != num_ops
+      || claim_read.len() != num_ops
+      || claim_write.len() != num_ops
+    {
+      return Err(ProofVerifyError::MalformedProof(
+        "hash layer proof has the wrong number of evaluations",
+      ));
+    }
+
     // init
     let eval_init_addr = IdentityPolynomial::new(rand_mem.len()).evaluate(rand_mem);
     let eval_init_val = EqPolynomial::new(r.to_vec()).evaluate(rand_mem);
     let hash_init_at_rand_mem =
       hash_func(&eval_init_addr, &eval_init_val, &Scalar::zero()) - r_multiset_check; // verify the claim_last of init chunk
-    assert_eq!(&hash_init_at_rand_mem, claim_init);
+    if &hash_init_at_rand_mem != claim_init {
+      return Err(ProofVerifyError::EvaluationMismatch);
+    }
 
     // read
     for i in 0..eval_ops_addr.len() {
       let hash_read_at_rand_ops =
         hash_func(&eval_ops_addr[i], &eval_ops_val[i], &eval_read_ts[i]) - r_multiset_check; // verify the claim_last of init chunk
-      assert_eq!(&hash_read_at_rand_ops, &claim_read[i]);
+      if hash_read_at_rand_ops != claim_read[i] {
+        return Err(ProofVerifyError::EvaluationMismatch);
+      }
     }
 
     // write: shares addr, val component; only decommit write_ts
@@ -875,7 +927,9 @@ impl HashLayerProof {
       let eval_write_ts = eval_read_ts[i] + Scalar::one();
       let hash_write_at_rand_ops =
         hash_func(&eval_ops_addr[i], &eval_ops_val[i], &eval_write_ts) - r_multiset_check; // verify the claim_last of init chunk
-      assert_eq!(&hash_write_at_rand_ops, &claim_write[i]);
+      if hash_write_at_rand_ops != claim_write[i] {
+        return Err(ProofVerifyError::EvaluationMismatch);
+      }
     }
 
     // audit: shares addr and val with init
@@ -883,11 +937,37 @@ impl HashLayerProof {
     let eval_audit_val = eval_init_val;
     let hash_audit_at_rand_mem =
       hash_func(&eval_audit_addr, &eval_audit_val, eval_audit_ts) - r_multiset_check;
-    assert_eq!(&hash_audit_at_rand_mem, claim_audit); // verify the last step of the sum-check for audit
+    // verify the last step of the sum-check for audit
+    if &hash_audit_at_rand_mem != claim_audit {
+      return Err(ProofVerifyError::EvaluationMismatch);
+    }
 
     Ok(())
   }
 
+  fn check_layout(
+    reader: &mut LayoutReader,
+    batch_size: usize,
+    num_ops: usize,
+    num_mem_cells: usize,
+  ) -> Result<(), ProofVerifyError> {
+    // eval_row and eval_col: addr and read_ts per instance, then audit_ts
+    for _ in 0..2 {
+      reader.scalar_vec(batch_size)?;
+      reader.scalar_vec(batch_size)?;
+      reader.scalar()?;
+    }
+    reader.scalar_vec(batch_size)?;
+    reader.scalar_vec(batch_size)?;
+    reader.scalar_vec(batch_size)?;
+
+    let num_vars_ops = (5 * batch_size).next_power_of_two().log_2() + num_ops.log_2();
+    PolyEvalProof::check_layout(reader, num_vars_ops)?;
+    PolyEvalProof::check_layout(reader, 1 + num_mem_cells.log_2())?;
+    let num_vars_derefs = (2 * batch_size).next_power_of_two().log_2() + num_ops.log_2();
+    PolyEvalProof::check_layout(reader, num_vars_derefs)
+  }
+
   fn verify(
     &self,
     rand: (&Vec<Scalar>, &Vec<Scalar>),
@@ -910,7 +990,11 @@ impl HashLayerProof {
 
     // verify derefs at rand_ops
     let (eval_row_ops_val, eval_col_ops_val) = &self.eval_derefs;
-    assert_eq!(eval_row_ops_val.len(), eval_col_ops_val.len());
+    if eval_row_ops_val.len() != eval_col_ops_val.len() {
+      return Err(ProofVerifyError::MalformedProof(
+        "hash layer proof has the wrong number of evaluations",
+      ));
+    }
     self.proof_derefs.verify(
       rand_ops,
       eval_row_ops_val,
@@ -922,15 +1006,24 @@ impl HashLayerProof {
 
     // verify the decommitments used in evaluation sum-check
     let eval_val_vec = &self.eval_val;
-    assert_eq!(claims_dotp.len(), 3 * eval_row_ops_val.len());
+    if claims_dotp.len() != 3 * eval_row_ops_val.len()
+      || eval_val_vec.len() != eval_row_ops_val.len()
+    {
+      return Err(ProofVerifyError::MalformedProof(
+        "hash layer proof has the wrong number of evaluations",
+      ));
+    }
     for i in 0..claims_dotp.len() / 3 {
       let claim_row_ops_val = claims_dotp[3 * i];
       let claim_col_ops_val = claims_dotp[3 * i + 1];
       let claim_val = claims_dotp[3 * i + 2];
 
-      assert_eq!(claim_row_ops_val, eval_row_ops_val[i]);
-      assert_eq!(claim_col_ops_val, eval_col_ops_val[i]);
-      assert_eq!(claim_val, eval_val_vec[i]);
+      if claim_row_ops_val != eval_row_ops_val[i]
+        || claim_col_ops_val != eval_col_ops_val[i]
+        || claim_val != eval_val_vec[i]
+      {
+        return Err(ProofVerifyError::EvaluationMismatch);
+      }
     }
 
     // verify addr-timestamps using comm_comb_ops at rand_ops
@@ -1215,6 +1308,26 @@ impl ProductLayerProof {
     (product_layer_proof, rand_mem, rand_ops)
   }
 
+  fn check_layout(
+    reader: &mut LayoutReader,
+    batch_size: usize,
+    num_ops: usize,
+    num_mem_cells: usize,
+  ) -> Result<(), ProofVerifyError> {
+    // eval_row and eval_col: init, read and write per instance, then audit
+    for _ in 0..2 {
+      reader.scalar()?;
+      reader.scalar_vec(batch_size)?;
+      reader.scalar_vec(batch_size)?;
+      reader.scalar()?;
+    }
+    reader.scalar_vec(batch_size)?;
+    reader.scalar_vec(batch_size)?;
+
+    ProductCircuitEvalProofBatched::check_layout(reader, num_mem_cells, 4, 0)?;
+    ProductCircuitEvalProofBatched::check_layout(reader, num_ops, 4 * batch_size, 2 * batch_size)
+  }
+
   pub fn verify(
     &self,
     num_ops: usize,
@@ -1238,13 +1351,18 @@ impl ProductLayerProof {
 
     // subset check
     let (row_eval_init, row_eval_read, row_eval_write, row_eval_audit) = &self.eval_row;
-    assert_eq!(row_eval_write.len(), num_instances);
-    assert_eq!(row_eval_read.len(), num_instances);
+    if row_eval_write.len() != num_instances || row_eval_read.len() != num_instances {
+      return Err(ProofVerifyError::MalformedProof(
+        "product layer proof has the wrong number of row claims",
+      ));
+    }
     let ws: Scalar = (0..row_eval_write.len())
       .map(|i| row_eval_write[i])
       .product();
     let rs: Scalar = (0..row_eval_read.len()).map(|i| row_eval_read[i]).product();
-    assert_eq!(row_eval_init * ws, rs * row_eval_audit);
+    if row_eval_init * ws != rs * row_eval_audit {
+      return Err(ProofVerifyError::EvaluationMismatch);
+    }
 
     row_eval_init.append_to_transcript(b"claim_row_eval_init", transcript);
     row_eval_read.append_to_transcript(b"claim_row_eval_read", transcript);
@@ -1253,13 +1371,18 @@ impl ProductLayerProof {
 
     // subset check
     let (col_eval_init, col_eval_read, col_eval_write, col_eval_audit) = &self.eval_col;
-    assert_eq!(col_eval_write.len(), num_instances);
-    assert_eq!(col_eval_read.len(), num_instances);
+    if col_eval_write.len() != num_instances || col_eval_read.len() != num_instances {
+      return Err(ProofVerifyError::MalformedProof(
+        "product layer proof has the wrong number of column claims",
+      ));
+    }
     let ws: Scalar = (0..col_eval_write.len())
       .map(|i| col_eval_write[i])
       .product();
     let rs: Scalar = (0..col_eval_read.len()).map(|i| col_eval_read[i]).product();
-    assert_eq!(col_eval_init * ws, rs * col_eval_audit);
+    if col_eval_init * ws != rs * col_eval_audit {
+      return Err(ProofVerifyError::EvaluationMismatch);
+    }
 
     col_eval_init.append_to_transcript(b"claim_col_eval_init", transcript);
     col_eval_read.append_to_transcript(b"claim_col_eval_read", transcript);
@@ -1268,11 +1391,16 @@ impl ProductLayerProof {
 
     // verify the evaluation of the sparse polynomial
     let (eval_dotp_left, eval_dotp_right) = &self.eval_val;
-    assert_eq!(eval_dotp_left.len(), eval_dotp_left.len());
-    assert_eq!(eval_dotp_left.len(), num_instances);
+    if eval_dotp_left.len() != num_instances || eval_dotp_right.len() != num_instances {
+      return Err(ProofVerifyError::MalformedProof(
+        "product layer proof has the wrong number of evaluation claims",
+      ));
+    }
     let mut claims_dotp_circuit: Vec<Scalar> = Vec::new();
     for i in 0..num_instances {
-      assert_eq!(eval_dotp_left[i] + eval_dotp_right[i], eval[i]);
+      if eval_dotp_left[i] + eval_dotp_right[i] != eval[i] {
+        return Err(ProofVerifyError::EvaluationMismatch);
+      }
       eval_dotp_left[i].append_to_transcript(b"claim_eval_dotp_left", transcript);
       eval_dotp_right[i].append_to_transcript(b"claim_eval_dotp_right", transcript);
 
@@ -1292,7 +1420,7 @@ impl ProductLayerProof {
       &claims_dotp_circuit,
       num_ops,
       transcript,
-    );
+    )?;
     // verify the correctness of claim_row_eval_init and claim_row_eval_audit
     let (claims_mem, _claims_mem_dotp, rand_mem) = self.proof_mem.verify(
       &[
@@ -1304,7 +1432,7 @@ impl ProductLayerProof {
       &Vec::new(),
       num_cells,
       transcript,
-    );
+    )?;
     timer.stop();
 
     Ok((claims_mem, rand_mem, claims_ops, claims_dotp, rand_ops))
@@ -1377,15 +1505,22 @@ impl PolyEvalNetworkProof {
     let (r_hash, r_multiset_check) = r_mem_check;
 
     let num_ops = nz.next_power_of_two();
+    if rx.len() != ry.len() {
+      return Err(ProofVerifyError::InternalError);
+    }
     let num_cells = rx.len().pow2();
-    assert_eq!(rx.len(), ry.len());
 
     let (claims_mem, rand_mem, mut claims_ops, claims_dotp, rand_ops) = self
       .proof_prod_layer
       .verify(num_ops, num_cells, evals, transcript)?;
-    assert_eq!(claims_mem.len(), 4);
-    assert_eq!(claims_ops.len(), 4 * num_instances);
-    assert_eq!(claims_dotp.len(), 3 * num_instances);
+    if claims_mem.len() != 4
+      || claims_ops.len() != 4 * num_instances
+      || claims_dotp.len() != 3 * num_instances
+    {
+      return Err(ProofVerifyError::MalformedProof(
+        "product layer proof has the wrong number of claims",
+      ));
+    }
 
     let (claims_ops_row, claims_ops_col) = claims_ops.split_at_mut(2 * num_instances);
     let (claims_ops_row_read, claims_ops_row_write) = claims_ops_row.split_at_mut(num_instances);
@@ -1520,6 +1655,21 @@ impl SparseMatPolyEvalProof {
     }
   }
 
+  // checks the encoded layout of a proof against the commitment it will be verified with
+  pub fn check_layout(
+    reader: &mut LayoutReader,
+    comm: &SparseMatPolyCommitment,
+  ) -> Result<(), ProofVerifyError> {
+    let batch_size = comm.batch_size;
+    let num_ops = comm.num_ops.next_power_of_two();
+    let num_mem_cells = comm.num_mem_cells;
+
+    let num_vars_derefs = (2 * batch_size).next_power_of_two().log_2() + num_ops.log_2();
+    PolyCommitment::check_layout(reader, num_vars_derefs)?;
+    ProductLayerProof::check_layout(reader, batch_size, num_ops, num_mem_cells)?;
+    HashLayerProof::check_layout(reader, batch_size, num_ops, num_mem_cells)
+  }
+
   pub fn verify(
     &self,
     comm: &SparseMatPolyCommitment,
@@ -1535,7 +1685,11 @@ impl SparseMatPolyEvalProof {
     let (rx_ext, ry_ext) = SparseMatPolyEvalProof::equalize(rx, ry);
 
     let (nz, num_mem_cells) = (comm.num_ops, comm.num_mem_cells);
-    assert_eq!(rx_ext.len().pow2(), num_mem_cells);
+    if rx_ext.len().pow2() != num_mem_cells {
+      return Err(ProofVerifyError::MalformedProof(
+        "evaluation point does not match the commitment size",
+      ));
+    }
 
     // add claims to transcript and obtain challenges for randomized mem-check circuit
     self
@@ -1610,6 +1764,46 @@ impl SparsePolynomial {
 mod tests {
   use super::*;
   use rand_core::{RngCore, OsRng};
+
+  #[test]
+  fn check_eval_table_sparse() {
+    let mut csprng: OsRng = OsRng;
+
+    let (num_rows, num_cols) = (64, 32);
+    let (num_vars_x, num_vars_y) = (num_rows.log_2(), num_cols.log_2());
+
+    // more entries than columns, so that columns are hit repeatedly
+    let M = (0..4 * num_cols)
+      .map(|_| {
+        SparseMatEntry::new(
+          (csprng.next_u64() % (num_rows as u64)) as usize,
+          (csprng.next_u64() % (num_cols as u64)) as usize,
+          Scalar::random(&mut csprng),
+        )
+      })
+      .collect::<Vec<_>>();
+    let poly_M = SparseMatPolynomial::new(num_vars_x, num_vars_y, M);
+
+    let rx = (0..num_vars_x)
+      .map(|_| Scalar::random(&mut csprng))
+      .collect::<Vec<_>>();
+    let ry = (0..num_vars_y)
+      .map(|_| Scalar::random(&mut csprng))
+      .collect::<Vec<_>>();
+
+    let table =
+      poly_M.compute_eval_table_sparse(&EqPolynomial::new(rx.clone()).evals(), num_rows, num_cols);
+    let eval: Scalar = table
+      .iter()
+      .zip(EqPolynomial::new(ry.clone()).evals())
+      .map(|(t, e)| t * e)
+      .sum();
+    assert_eq!(
+      eval,
+      SparseMatPolynomial::multi_evaluate(&[&poly_M], &rx, &ry)[0]
+    );
+  }
+
   #[test]
   fn check_sparse_polyeval_proof() {
     let mut csprng: OsRng = OsRng;
@@ -3,7 +3,8 @@
 use super::commitments::{Commitments, MultiCommitGens};
 use super::dense_mlpoly::DensePolynomial;
 use super::errors::ProofVerifyError;
-use super::group::{CompressedGroup, GroupElement, VartimeMultiscalarMul};
+use super::group::{CompressedGroup, CompressedGroupExt, GroupElement, VartimeMultiscalarMul};
+use super::layout::LayoutReader;
 use super::nizk::DotProductProof;
 use super::random::RandomTape;
 use super::scalar::Scalar;
@@ -13,8 +14,39 @@ use crate::group::DecompressEncodedPoint;
 use core::iter;
 use itertools::izip;
 use merlin::Transcript;
+#[cfg(feature = "multicore")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
+fn add_evals<const N: usize>(mut acc: [Scalar; N], evals: [Scalar; N]) -> [Scalar; N] {
+  for (a, e) in acc.iter_mut().zip(evals.iter()) {
+    *a += e;
+  }
+  acc
+}
+
+// Sums the evaluations a round polynomial gets from each pair of hypercube
+// points. Field addition is exact, so splitting the work across threads gives
+// the same result as the sequential loop.
+#[cfg(feature = "multicore")]
+fn sum_round_evals<const N: usize, F>(len: usize, evals_at: F) -> [Scalar; N]
+where
+  F: Fn(usize) -> [Scalar; N] + Sync + Send,
+{
+  (0..len)
+    .into_par_iter()
+    .map(evals_at)
+    .reduce(|| [Scalar::zero(); N], add_evals)
+}
+
+#[cfg(not(feature = "multicore"))]
+fn sum_round_evals<const N: usize, F>(len: usize, evals_at: F) -> [Scalar; N]
+where
+  F: Fn(usize) -> [Scalar; N] + Sync + Send,
+{
+  (0..len).map(evals_at).fold([Scalar::zero(); N], add_evals)
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct SumcheckInstanceProof {
   compressed_polys: Vec<CompressedUniPoly>,
@@ -25,6 +57,16 @@ impl SumcheckInstanceProof {
     SumcheckInstanceProof { compressed_polys }
   }
 
+  pub fn check_layout(
+    reader: &mut LayoutReader,
+    num_rounds: usize,
+    degree_bound: usize,
+  ) -> Result<(), ProofVerifyError> {
+    reader.vec_len(num_rounds)?;
+    // each round is a CompressedUniPoly, which omits the linear term
+    (0..num_rounds).try_for_each(|_| reader.scalar_vec(degree_bound))
+  }
+
   pub fn verify(
     &self,
     claim: Scalar,
@@ -36,15 +78,23 @@ impl SumcheckInstanceProof {
     let mut r: Vec<Scalar> = Vec::new();
 
     // verify that there is a univariate polynomial for each round
-    assert_eq!(self.compressed_polys.len(), num_rounds);
+    if self.compressed_polys.len() != num_rounds {
+      return Err(ProofVerifyError::MalformedProof(
+        "sum-check proof has the wrong number of rounds",
+      ));
+    }
     for i in 0..self.compressed_polys.len() {
-      let poly = self.compressed_polys[i].decompress(&e);
-
       // verify degree bound
-      assert_eq!(poly.degree(), degree_bound);
+      if degree_bound == 0 || self.compressed_polys[i].degree() != degree_bound {
+        return Err(ProofVerifyError::SumcheckRoundFailure(i));
+      }
+
+      let poly = self.compressed_polys[i].decompress(&e);
 
       // check if G_k(0) + G_k(1) = e
-      assert_eq!(poly.eval_at_zero() + poly.eval_at_one(), e);
+      if poly.eval_at_zero() + poly.eval_at_one() != e {
+        return Err(ProofVerifyError::SumcheckRoundFailure(i));
+      }
 
       // append the prover's message to the transcript
       poly.append_to_transcript(b"poly", transcript);
@@ -82,6 +132,17 @@ impl ZKSumcheckInstanceProof {
     }
   }
 
+  pub fn check_layout(
+    reader: &mut LayoutReader,
+    num_rounds: usize,
+    degree_bound: usize,
+  ) -> Result<(), ProofVerifyError> {
+    reader.point_vec(num_rounds)?;
+    reader.point_vec(num_rounds)?;
+    reader.vec_len(num_rounds)?;
+    (0..num_rounds).try_for_each(|_| DotProductProof::check_layout(reader, degree_bound + 1))
+  }
+
   pub fn verify(
     &self,
     comm_claim: &CompressedGroup,
@@ -92,11 +153,22 @@ impl ZKSumcheckInstanceProof {
     transcript: &mut Transcript,
   ) -> Result<(CompressedGroup, Vec<Scalar>), ProofVerifyError> {
     // verify degree bound
-    assert_eq!(gens_n.n, degree_bound + 1);
+    if gens_n.n != degree_bound + 1 {
+      return Err(ProofVerifyError::MalformedProof(
+        "sum-check degree bound does not match the generators",
+      ));
+    }
 
     // verify that there is a univariate polynomial for each round
-    assert_eq!(self.comm_polys.len(), num_rounds);
-    assert_eq!(self.comm_evals.len(), num_rounds);
+    if num_rounds == 0
+      || self.comm_polys.len() != num_rounds
+      || self.comm_evals.len() != num_rounds
+      || self.proofs.len() != num_rounds
+    {
+      return Err(ProofVerifyError::MalformedProof(
+        "sum-check proof has the wrong number of rounds",
+      ));
+    }
 
     let mut r: Vec<Scalar> = Vec::new();
     for i in 0..self.comm_polys.len() {
@@ -126,11 +198,11 @@ impl ZKSumcheckInstanceProof {
 
         // compute a weighted sum of the RHS
         let comm_target = GroupElement::vartime_multiscalar_mul(
-          w.clone(),
-          iter::once(&comm_claim_per_round)
+          &w,
+          &iter::once(&comm_claim_per_round)
             .chain(iter::once(&comm_eval))
-            .map(|pt| pt.decompress().unwrap())
-            .collect(),
+            .map(|pt| pt.unpack())
+            .collect::<Result<Vec<GroupElement>, ProofVerifyError>>()?,
         )
         .compress();
 
@@ -158,19 +230,17 @@ impl ZKSumcheckInstanceProof {
             .collect::<Vec<Scalar>>()
         };
 
-        self.proofs[i]
-          .verify(
-            gens_1,
-            gens_n,
-            transcript,
-            &a,
-            &self.comm_polys[i],
-            &comm_target,
-          )
-          .is_ok()
+        self.proofs[i].verify(
+          gens_1,
+          gens_n,
+          transcript,
+          &a,
+          &self.comm_polys[i],
+          &comm_target,
+        )
       };
-      if !res {
-        return Err(ProofVerifyError::InternalError);
+      if res.is_err() {
+        return Err(ProofVerifyError::SumcheckRoundFailure(i));
       }
 
       r.push(r_i);
@@ -191,42 +261,40 @@ impl SumcheckInstanceProof {
     transcript: &mut Transcript,
   ) -> (Self, Vec<Scalar>, Vec<Scalar>)
   where
-    F: Fn(&Scalar, &Scalar, &Scalar) -> Scalar,
+    F: Fn(&Scalar, &Scalar, &Scalar) -> Scalar + Sync,
   {
     let mut e = *claim;
     let mut r: Vec<Scalar> = Vec::new();
     let mut cubic_polys: Vec<CompressedUniPoly> = Vec::new();
     for _j in 0..num_rounds {
-      let mut eval_point_0 = Scalar::zero();
-      let mut eval_point_2 = Scalar::zero();
-      let mut eval_point_3 = Scalar::zero();
-
       let len = poly_A.len() / 2;
-      for i in 0..len {
+      let (A, B, C) = (&*poly_A, &*poly_B, &*poly_C);
+      let [eval_point_0, eval_point_2, eval_point_3] = sum_round_evals(len, |i| {
         // eval 0: bound_func is A(low)
-        eval_point_0 += comb_func(&poly_A[i], &poly_B[i], &poly_C[i]);
+        let eval_point_0 = comb_func(&A[i], &B[i], &C[i]);
 
         // eval 2: bound_func is -A(low) + 2*A(high)
-        let poly_A_bound_point = poly_A[len + i] + poly_A[len + i] - poly_A[i];
-        let poly_B_bound_point = poly_B[len + i] + poly_B[len + i] - poly_B[i];
-        let poly_C_bound_point = poly_C[len + i] + poly_C[len + i] - poly_C[i];
-        eval_point_2 += comb_func(
+        let poly_A_bound_point = A[len + i] + A[len + i] - A[i];
+        let poly_B_bound_point = B[len + i] + B[len + i] - B[i];
+        let poly_C_bound_point = C[len + i] + C[len + i] - C[i];
+        let eval_point_2 = comb_func(
           &poly_A_bound_point,
           &poly_B_bound_point,
           &poly_C_bound_point,
         );
 
         // eval 3: bound_func is -2A(low) + 3A(high); computed incrementally with bound_func applied to eval(2)
-        let poly_A_bound_point = poly_A_bound_point + poly_A[len + i] - poly_A[i];
-        let poly_B_bound_point = poly_B_bound_point + poly_B[len + i] - poly_B[i];
-        let poly_C_bound_point = poly_C_bound_point + poly_C[len + i] - poly_C[i];
-
-        eval_point_3 += comb_func(
+        let poly_A_bound_point = poly_A_bound_point + A[len + i] - A[i];
+        let poly_B_bound_point = poly_B_bound_point + B[len + i] - B[i];
+        let poly_C_bound_point = poly_C_bound_point + C[len + i] - C[i];
+        let eval_point_3 = comb_func(
           &poly_A_bound_point,
           &poly_B_bound_point,
           &poly_C_bound_point,
         );
-      }
+
+        [eval_point_0, eval_point_2, eval_point_3]
+      });
 
       let evals = vec![eval_point_0, e - eval_point_0, eval_point_2, eval_point_3];
       let poly = UniPoly::from_evals(&evals);
@@ -275,7 +343,7 @@ impl SumcheckInstanceProof {
     (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>),
   )
   where
-    F: Fn(&Scalar, &Scalar, &Scalar) -> Scalar,
+    F: Fn(&Scalar, &Scalar, &Scalar) -> Scalar + Sync,
   {
     let (poly_A_vec_par, poly_B_vec_par, poly_C_par) = poly_vec_par;
     let (poly_A_vec_seq, poly_B_vec_seq, poly_C_vec_seq) = poly_vec_seq;
@@ -289,36 +357,34 @@ impl SumcheckInstanceProof {
       let mut evals: Vec<(Scalar, Scalar, Scalar)> = Vec::new();
 
       for (poly_A, poly_B) in poly_A_vec_par.iter().zip(poly_B_vec_par.iter()) {
-        let mut eval_point_0 = Scalar::zero();
-        let mut eval_point_2 = Scalar::zero();
-        let mut eval_point_3 = Scalar::zero();
-
         let len = poly_A.len() / 2;
-        for i in 0..len {
+        let (A, B, C) = (&**poly_A, &**poly_B, &*poly_C_par);
+        let [eval_point_0, eval_point_2, eval_point_3] = sum_round_evals(len, |i| {
           // eval 0: bound_func is A(low)
-          eval_point_0 += comb_func(&poly_A[i], &poly_B[i], &poly_C_par[i]);
+          let eval_point_0 = comb_func(&A[i], &B[i], &C[i]);
 
           // eval 2: bound_func is -A(low) + 2*A(high)
-          let poly_A_bound_point = poly_A[len + i] + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B[len + i] + poly_B[len + i] - poly_B[i];
-          let poly_C_bound_point = poly_C_par[len + i] + poly_C_par[len + i] - poly_C_par[i];
-          eval_point_2 += comb_func(
+          let poly_A_bound_point = A[len + i] + A[len + i] - A[i];
+          let poly_B_bound_point = B[len + i] + B[len + i] - B[i];
+          let poly_C_bound_point = C[len + i] + C[len + i] - C[i];
+          let eval_point_2 = comb_func(
             &poly_A_bound_point,
             &poly_B_bound_point,
             &poly_C_bound_point,
           );
 
           // eval 3: bound_func is -2A(low) + 3A(high); computed incrementally with bound_func applied to eval(2)
-          let poly_A_bound_point = poly_A_bound_point + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B_bound_point + poly_B[len + i] - poly_B[i];
-          let poly_C_bound_point = poly_C_bound_point + poly_C_par[len + i] - poly_C_par[i];
-
-          eval_point_3 += comb_func(
+          let poly_A_bound_point = poly_A_bound_point + A[len + i] - A[i];
+          let poly_B_bound_point = poly_B_bound_point + B[len + i] - B[i];
+          let poly_C_bound_point = poly_C_bound_point + C[len + i] - C[i];
+          let eval_point_3 = comb_func(
             &poly_A_bound_point,
             &poly_B_bound_point,
             &poly_C_bound_point,
           );
-        }
+
+          [eval_point_0, eval_point_2, eval_point_3]
+        });
 
         evals.push((eval_point_0, eval_point_2, eval_point_3));
       }
@@ -328,32 +394,31 @@ impl SumcheckInstanceProof {
         poly_B_vec_seq.iter(),
         poly_C_vec_seq.iter()
       ) {
-        let mut eval_point_0 = Scalar::zero();
-        let mut eval_point_2 = Scalar::zero();
-        let mut eval_point_3 = Scalar::zero();
         let len = poly_A.len() / 2;
-        for i in 0..len {
+        let (A, B, C) = (&**poly_A, &**poly_B, &**poly_C);
+        let [eval_point_0, eval_point_2, eval_point_3] = sum_round_evals(len, |i| {
           // eval 0: bound_func is A(low)
-          eval_point_0 += comb_func(&poly_A[i], &poly_B[i], &poly_C[i]);
+          let eval_point_0 = comb_func(&A[i], &B[i], &C[i]);
           // eval 2: bound_func is -A(low) + 2*A(high)
-          let poly_A_bound_point = poly_A[len + i] + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B[len + i] + poly_B[len + i] - poly_B[i];
-          let poly_C_bound_point = poly_C[len + i] + poly_C[len + i] - poly_C[i];
-          eval_point_2 += comb_func(
+          let poly_A_bound_point = A[len + i] + A[len + i] - A[i];
+          let poly_B_bound_point = B[len + i] + B[len + i] - B[i];
+          let poly_C_bound_point = C[len + i] + C[len + i] - C[i];
+          let eval_point_2 = comb_func(
             &poly_A_bound_point,
             &poly_B_bound_point,
             &poly_C_bound_point,
           );
           // eval 3: bound_func is -2A(low) + 3A(high); computed incrementally with bound_func applied to eval(2)
-          let poly_A_bound_point = poly_A_bound_point + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B_bound_point + poly_B[len + i] - poly_B[i];
-          let poly_C_bound_point = poly_C_bound_point + poly_C[len + i] - poly_C[i];
-          eval_point_3 += comb_func(
+          let poly_A_bound_point = poly_A_bound_point + A[len + i] - A[i];
+          let poly_B_bound_point = poly_B_bound_point + B[len + i] - B[i];
+          let poly_C_bound_point = poly_C_bound_point + C[len + i] - C[i];
+          let eval_point_3 = comb_func(
             &poly_A_bound_point,
             &poly_B_bound_point,
             &poly_C_bound_point,
           );
-        }
+          [eval_point_0, eval_point_2, eval_point_3]
+        });
         evals.push((eval_point_0, eval_point_2, eval_point_3));
       }
 
@@ -439,7 +504,7 @@ impl ZKSumcheckInstanceProof {
     random_tape: &mut RandomTape,
   ) -> (Self, Vec<Scalar>, Vec<Scalar>, Scalar)
   where
-    F: Fn(&Scalar, &Scalar) -> Scalar,
+    F: Fn(&Scalar, &Scalar) -> Scalar + Sync,
   {
     let (blinds_poly, blinds_evals) = (
       random_tape.random_vector(b"blinds_poly", num_rounds),
@@ -455,19 +520,19 @@ impl ZKSumcheckInstanceProof {
 
     for j in 0..num_rounds {
       let (poly, comm_poly) = {
-        let mut eval_point_0 = Scalar::zero();
-        let mut eval_point_2 = Scalar::zero();
-
         let len = poly_A.len() / 2;
-        for i in 0..len {
+        let (A, B) = (&*poly_A, &*poly_B);
+        let [eval_point_0, eval_point_2] = sum_round_evals(len, |i| {
           // eval 0: bound_func is A(low)
-          eval_point_0 += comb_func(&poly_A[i], &poly_B[i]);
+          let eval_point_0 = comb_func(&A[i], &B[i]);
 
           // eval 2: bound_func is -A(low) + 2*A(high)
-          let poly_A_bound_point = poly_A[len + i] + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B[len + i] + poly_B[len + i] - poly_B[i];
-          eval_point_2 += comb_func(&poly_A_bound_point, &poly_B_bound_point);
-        }
+          let poly_A_bound_point = A[len + i] + A[len + i] - A[i];
+          let poly_B_bound_point = B[len + i] + B[len + i] - B[i];
+          let eval_point_2 = comb_func(&poly_A_bound_point, &poly_B_bound_point);
+
+          [eval_point_0, eval_point_2]
+        });
 
         let evals = vec![eval_point_0, claim_per_round - eval_point_0, eval_point_2];
         let poly = UniPoly::from_evals(&evals);
@@ -510,11 +575,11 @@ impl ZKSumcheckInstanceProof {
         // compute a weighted sum of the RHS
         let target = w[0] * claim_per_round + w[1] * eval;
         let comm_target = GroupElement::vartime_multiscalar_mul(
-          w.clone(),
-          iter::once(&comm_claim_per_round)
+          &w,
+          &iter::once(&comm_claim_per_round)
             .chain(iter::once(&comm_eval))
             .map(|pt| pt.decompress().unwrap())
-            .collect(),
+            .collect::<Vec<GroupElement>>(),
         )
         .compress();
 
@@ -601,7 +666,7 @@ impl ZKSumcheckInstanceProof {
     random_tape: &mut RandomTape,
   ) -> (Self, Vec<Scalar>, Vec<Scalar>, Scalar)
   where
-    F: Fn(&Scalar, &Scalar, &Scalar, &Scalar) -> Scalar,
+    F: Fn(&Scalar, &Scalar, &Scalar, &Scalar) -> Scalar + Sync,
   {
     let (blinds_poly, blinds_evals) = (
       random_tape.random_vector(b"blinds_poly", num_rounds),
@@ -618,21 +683,18 @@ impl ZKSumcheckInstanceProof {
 
     for j in 0..num_rounds {
       let (poly, comm_poly) = {
-        let mut eval_point_0 = Scalar::zero();
-        let mut eval_point_2 = Scalar::zero();
-        let mut eval_point_3 = Scalar::zero();
-
         let len = poly_A.len() / 2;
-        for i in 0..len {
+        let (A, B, C, D) = (&*poly_A, &*poly_B, &*poly_C, &*poly_D);
+        let [eval_point_0, eval_point_2, eval_point_3] = sum_round_evals(len, |i| {
           // eval 0: bound_func is A(low)
-          eval_point_0 += comb_func(&poly_A[i], &poly_B[i], &poly_C[i], &poly_D[i]);
+          let eval_point_0 = comb_func(&A[i], &B[i], &C[i], &D[i]);
 
           // eval 2: bound_func is -A(low) + 2*A(high)
-          let poly_A_bound_point = poly_A[len + i] + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B[len + i] + poly_B[len + i] - poly_B[i];
-          let poly_C_bound_point = poly_C[len + i] + poly_C[len + i] - poly_C[i];
-          let poly_D_bound_point = poly_D[len + i] + poly_D[len + i] - poly_D[i];
-          eval_point_2 += comb_func(
+          let poly_A_bound_point = A[len + i] + A[len + i] - A[i];
+          let poly_B_bound_point = B[len + i] + B[len + i] - B[i];
+          let poly_C_bound_point = C[len + i] + C[len + i] - C[i];
+          let poly_D_bound_point = D[len + i] + D[len + i] - D[i];
+          let eval_point_2 = comb_func(
             &poly_A_bound_point,
             &poly_B_bound_point,
             &poly_C_bound_point,
@@ -640,17 +702,19 @@ impl ZKSumcheckInstanceProof {
           );
 
           // eval 3: bound_func is -2A(low) + 3A(high); computed incrementally with bound_func applied to eval(2)
-          let poly_A_bound_point = poly_A_bound_point + poly_A[len + i] - poly_A[i];
-          let poly_B_bound_point = poly_B_bound_point + poly_B[len + i] - poly_B[i];
-          let poly_C_bound_point = poly_C_bound_point + poly_C[len + i] - poly_C[i];
-          let poly_D_bound_point = poly_D_bound_point + poly_D[len + i] - poly_D[i];
-          eval_point_3 += comb_func(
+          let poly_A_bound_point = poly_A_bound_point + A[len + i] - A[i];
+          let poly_B_bound_point = poly_B_bound_point + B[len + i] - B[i];
+          let poly_C_bound_point = poly_C_bound_point + C[len + i] - C[i];
+          let poly_D_bound_point = poly_D_bound_point + D[len + i] - D[i];
+          let eval_point_3 = comb_func(
             &poly_A_bound_point,
             &poly_B_bound_point,
             &poly_C_bound_point,
             &poly_D_bound_point,
           );
-        }
+
+          [eval_point_0, eval_point_2, eval_point_3]
+        });
 
         let evals = vec![
           eval_point_0,
@@ -700,8 +764,8 @@ impl ZKSumcheckInstanceProof {
         // compute a weighted sum of the RHS
         let target = w[0] * claim_per_round + w[1] * eval;
         let comm_target = GroupElement::vartime_multiscalar_mul(
-          w.clone(),
-          iter::once(&comm_claim_per_round)
+          &w,
+          &iter::once(&comm_claim_per_round)
             .chain(iter::once(&comm_eval))
             .map(|pt| pt.decompress().unwrap())
             .collect::<Vec<GroupElement>>(),
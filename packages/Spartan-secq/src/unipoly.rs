@@ -93,6 +93,11 @@ impl UniPoly {
 }
 
 impl CompressedUniPoly {
+  // the degree of the polynomial this decompresses to; decompress requires it to be non-zero
+  pub fn degree(&self) -> usize {
+    self.coeffs_except_linear_term.len()
+  }
+
   // we require eval(0) + eval(1) = hint, so we can solve for the linear term as:
   // linear_term = hint - 2 * constant_term - deg2 term - deg3 term
   pub fn decompress(&self, hint: &Scalar) -> UniPoly {
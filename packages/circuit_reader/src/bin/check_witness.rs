@@ -0,0 +1,49 @@
+use circuit_reader::{
+    load_circuit, load_witness_from_bin_file, to_spartan_assignments, SignalNames,
+};
+use group::Group;
+use secq256k1::AffinePoint;
+use std::env::{args, current_dir};
+use std::fmt::Display;
+use std::process::exit;
+
+fn fail(e: impl Display) -> ! {
+    eprintln!("{}", e);
+    exit(1);
+}
+
+fn main() {
+    let circom_r1cs_path = args().nth(1).unwrap();
+    let witness_path = args().nth(2).unwrap();
+    let sym_path = args().nth(3);
+
+    let root = current_dir().unwrap();
+    let circuit = load_circuit(&root.join(circom_r1cs_path)).unwrap_or_else(|e| fail(e));
+    let witness = load_witness_from_bin_file(&root.join(witness_path)).unwrap_or_else(|e| fail(e));
+    let names = match sym_path {
+        Some(sym_path) => {
+            SignalNames::load(&root.join(sym_path), &circuit.layout).unwrap_or_else(|e| fail(e))
+        }
+        None => SignalNames::default(),
+    };
+
+    let (vars, inputs) =
+        to_spartan_assignments::<<AffinePoint as Group>::Scalar>(&circuit.layout, &witness)
+            .unwrap_or_else(|e| fail(e));
+    let unsatisfied = circuit
+        .instance
+        .check_sat_detailed(&vars, &inputs)
+        .unwrap_or_else(|e| fail(format!("{:?}", e)));
+
+    if unsatisfied.is_empty() {
+        println!("The witness satisfies the circuit");
+        return;
+    }
+    for constraint in &unsatisfied {
+        println!("{}", circuit.describe(constraint, &names));
+    }
+    fail(format!(
+        "The witness does not satisfy {} constraints",
+        unsatisfied.len()
+    ));
+}
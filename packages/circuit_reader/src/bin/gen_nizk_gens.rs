@@ -0,0 +1,26 @@
+use libspartan::{Instance, NIZKGens};
+use std::env::{args, current_dir};
+use std::fs::{self, File};
+use std::io::Write;
+
+fn main() {
+    let spartan_inst_path = args().nth(1).unwrap();
+    let output_path = args().nth(2).unwrap();
+
+    let root = current_dir().unwrap();
+    let spartan_inst_bytes = fs::read(root.join(spartan_inst_path)).unwrap();
+    let spartan_inst: Instance = bincode::deserialize(&spartan_inst_bytes).unwrap();
+
+    let gens = NIZKGens::new(
+        spartan_inst.inst.get_num_cons(),
+        spartan_inst.inst.get_num_vars(),
+        spartan_inst.inst.get_num_inputs(),
+    );
+
+    File::create(root.join(output_path.clone()))
+        .unwrap()
+        .write_all(gens.to_bytes().as_slice())
+        .unwrap();
+
+    println!("Written NIZK public parameters to {}", output_path);
+}
@@ -1,6 +1,5 @@
 #![allow(non_snake_case)]
-use bincode;
-use circuit_reader::load_as_spartan_inst;
+use circuit_reader::{load_circuit, load_optimized_circuit};
 use std::env::{args, current_dir};
 use std::fs::File;
 use std::io::Write;
@@ -8,12 +7,28 @@ use std::io::Write;
 fn main() {
     let circom_r1cs_path = args().nth(1).unwrap();
     let output_path = args().nth(2).unwrap();
-    let num_pub_inputs = args().nth(3).unwrap().parse::<usize>().unwrap();
+    // `--optimize` shrinks the instance, whose layout is written with it so
+    // that the provers still take the witnesses computed by circom
+    let optimize = args().nth(3).as_deref() == Some("--optimize");
 
     let root = current_dir().unwrap();
     let circom_r1cs_path = root.join(circom_r1cs_path);
-    let spartan_inst = load_as_spartan_inst(circom_r1cs_path, num_pub_inputs);
-    let sparta_inst_bytes = bincode::serialize(&spartan_inst).unwrap();
+    let circuit = if optimize {
+        load_optimized_circuit(&circom_r1cs_path).map(|(circuit, stats)| {
+            println!("{}", stats);
+            circuit
+        })
+    } else {
+        load_circuit(&circom_r1cs_path)
+    };
+    let circuit = match circuit {
+        Ok(circuit) => circuit,
+        Err(e) => {
+            eprintln!("{}: {}", circom_r1cs_path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let sparta_inst_bytes = circuit.to_bytes();
 
     File::create(root.join(output_path.clone()))
         .unwrap()
@@ -21,4 +36,7 @@ fn main() {
         .unwrap();
 
     println!("Written Spartan circuit to {}", output_path);
+    for (i, signal) in circuit.layout.public_signals().iter().enumerate() {
+        println!("Spartan input {}: {}", i, signal);
+    }
 }
@@ -3,15 +3,71 @@ use byteorder::{LittleEndian, ReadBytesExt};
 use ff::PrimeField;
 use group::Group;
 use itertools::Itertools;
+use libspartan::R1CSError;
 use std::{
     collections::HashMap,
-    io::{BufReader, Error, ErrorKind, Read, Result, Seek, SeekFrom},
+    io::{BufReader, Read, Seek, SeekFrom},
 };
+use thiserror::Error;
+
+/// Errors returned when reading a circom `.r1cs` file
+#[derive(Error, Debug)]
+pub enum R1CSFileError {
+    /// the file could not be opened or ended early
+    #[error("failed to read the R1CS file: {0}")]
+    Io(#[from] std::io::Error),
+    /// the file does not start with "r1cs"
+    #[error("invalid magic number {0:?}, not an R1CS file")]
+    InvalidMagic([u8; 4]),
+    /// the format version is not 1
+    #[error("unsupported R1CS format version: {0}")]
+    UnsupportedVersion(u32),
+    /// a section the reader needs is not in the file
+    #[error("missing R1CS section of type {0}")]
+    MissingSection(u32),
+    /// a section appears more than once
+    #[error("duplicate R1CS section of type {0}")]
+    DuplicateSection(u32),
+    /// the size of a section does not match its contents
+    #[error("R1CS section of type {section} has {actual} bytes, expected {expected}")]
+    SectionSize {
+        section: u32,
+        expected: u64,
+        actual: u64,
+    },
+    /// the header declares field elements of another size than the field's
+    #[error("field elements of {0} bytes are not supported")]
+    FieldSize(u32),
+    /// the circuit was compiled for another field, e.g. with circom's
+    /// default BN254 prime instead of `--prime secq256k1`
+    #[error("the circuit is over the prime 0x{0}, not the scalar field of secq256k1")]
+    WrongPrime(String),
+    /// the header declares more input and output wires than wires
+    #[error("the header declares {signals} input and output wires out of {wires}")]
+    InvalidHeader { signals: u64, wires: u32 },
+    /// a constraint refers to a wire the header does not declare
+    #[error("wire {wire} is out of range, the circuit has {wires} wires")]
+    WireOutOfRange { wire: u32, wires: u32 },
+    /// a coefficient is not the encoding of a field element
+    #[error("invalid coefficient in constraint {0}")]
+    InvalidCoefficient(usize),
+    /// the wire of the constant 1 is not mapped to the label 0
+    #[error("wire 0 should always be mapped to 0")]
+    InvalidWireMap,
+    /// the constraints do not fit the Spartan instance they are loaded as
+    #[error("invalid Spartan instance: {0:?}")]
+    InvalidInstance(R1CSError),
+}
+
+type Result<T> = std::result::Result<T, R1CSFileError>;
 
 pub type Constraint<Fr> = (Vec<(usize, Fr)>, Vec<(usize, Fr)>, Vec<(usize, Fr)>);
 
 #[derive(Clone)]
 pub struct R1CS<Fr: PrimeField> {
+    pub num_pub_out: usize,
+    pub num_pub_in: usize,
+    pub num_prv_in: usize,
     pub num_inputs: usize,
     pub num_aux: usize,
     pub num_variables: usize,
@@ -42,52 +98,85 @@ pub struct R1CSFile<Fr: PrimeField> {
 use std::fs::OpenOptions;
 use std::path::Path;
 
-pub fn load_r1cs_from_bin_file<G1: Group>(filename: &Path) -> (R1CS<G1::Scalar>, Vec<usize>) {
-    let reader = OpenOptions::new()
-        .read(true)
-        .open(filename)
-        .expect("unable to open.");
+pub fn load_r1cs_from_bin_file<G1: Group>(
+    filename: &Path,
+) -> Result<(R1CS<G1::Scalar>, Vec<usize>)> {
+    let reader = OpenOptions::new().read(true).open(filename)?;
     load_r1cs_from_bin::<G1, _>(BufReader::new(reader))
 }
 
-pub fn load_r1cs_from_bin<G1: Group, R: Read + Seek>(reader: R) -> (R1CS<G1::Scalar>, Vec<usize>) {
-    let file = from_reader::<G1, R>(reader).expect("unable to read.");
+pub fn load_r1cs_from_bin<G1: Group, R: Read + Seek>(
+    reader: R,
+) -> Result<(R1CS<G1::Scalar>, Vec<usize>)> {
+    let file = from_reader::<G1, R>(reader)?;
     let num_inputs = (1 + file.header.n_pub_in + file.header.n_pub_out) as usize;
     let num_variables = file.header.n_wires as usize;
     let num_aux = num_variables - num_inputs;
-    (
+    Ok((
         R1CS {
+            num_pub_out: file.header.n_pub_out as usize,
+            num_pub_in: file.header.n_pub_in as usize,
+            num_prv_in: file.header.n_prv_in as usize,
             num_aux,
             num_inputs,
             num_variables,
             constraints: file.constraints,
         },
         file.wire_mapping.iter().map(|e| *e as usize).collect_vec(),
-    )
+    ))
+}
+
+// circom encodes field elements in little-endian, while `PrimeField` leaves
+// the byte order of `Repr` to each field
+fn is_little_endian<Fr: PrimeField>() -> bool {
+    Fr::one().to_repr().as_ref()[0] == 1
 }
 
-pub(crate) fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Fr> {
-    let mut repr = Fr::zero().to_repr();
-    for digit in repr.as_mut().iter_mut() {
-        // TODO: may need to reverse order?
-        *digit = reader.read_u8()?;
+pub(crate) fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> std::io::Result<Option<Fr>> {
+    let mut repr = Fr::Repr::default();
+    reader.read_exact(repr.as_mut())?;
+    if !is_little_endian::<Fr>() {
+        repr.as_mut().reverse();
     }
-    let fr = Fr::from_repr(repr).unwrap();
-    Ok(fr)
+    Ok(Fr::from_repr(repr).into())
+}
+
+// the encoding of `value` in circom files, which is also the one Spartan reads
+pub(crate) fn to_le_bytes<Fr: PrimeField>(value: &Fr) -> Fr::Repr {
+    let mut repr = value.to_repr();
+    if !is_little_endian::<Fr>() {
+        repr.as_mut().reverse();
+    }
+    repr
+}
+
+// checks that the little-endian `prime` is the modulus of `Fr`: as the modulus
+// is above 2^255, it is the only non-zero multiple of it on 32 bytes
+pub(crate) fn is_modulus<Fr: PrimeField>(prime: &[u8]) -> bool {
+    let value = prime.iter().rev().fold(Fr::zero(), |acc, byte| {
+        acc * Fr::from(256) + Fr::from(*byte as u64)
+    });
+    prime.len() == 32 && prime.iter().any(|byte| *byte != 0) && bool::from(value.is_zero())
 }
 
-fn read_header<R: Read>(mut reader: R, size: u64) -> Result<Header> {
+fn read_header<R: Read, Fr: PrimeField>(mut reader: R, size: u64) -> Result<Header> {
     let field_size = reader.read_u32::<LittleEndian>()?;
-    let mut prime_size = vec![0u8; field_size as usize];
-    reader.read_exact(&mut prime_size)?;
-    if size != 32 + field_size as u64 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Invalid header section size",
-        ));
+    // the prime is checked against the field before being read, to bound it
+    let repr_size = Fr::Repr::default().as_ref().len();
+    if size != 32 + repr_size as u64 {
+        return Err(R1CSFileError::SectionSize {
+            section: HEADER_TYPE,
+            expected: 32 + repr_size as u64,
+            actual: size,
+        });
     }
+    if field_size as usize != repr_size {
+        return Err(R1CSFileError::FieldSize(field_size));
+    }
+    let mut prime_size = vec![0u8; repr_size];
+    reader.read_exact(&mut prime_size)?;
 
-    Ok(Header {
+    let header = Header {
         field_size,
         prime_size,
         n_wires: reader.read_u32::<LittleEndian>()?,
@@ -96,115 +185,150 @@ fn read_header<R: Read>(mut reader: R, size: u64) -> Result<Header> {
         n_prv_in: reader.read_u32::<LittleEndian>()?,
         n_labels: reader.read_u64::<LittleEndian>()?,
         n_constraints: reader.read_u32::<LittleEndian>()?,
-    })
+    };
+    // the constant 1 and the input and output wires
+    let signals = 1 + header.n_pub_out as u64 + header.n_pub_in as u64 + header.n_prv_in as u64;
+    if signals > header.n_wires as u64 {
+        return Err(R1CSFileError::InvalidHeader {
+            signals,
+            wires: header.n_wires,
+        });
+    }
+    Ok(header)
 }
 
-fn read_constraint_vec<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Vec<(usize, Fr)>> {
+fn read_constraint_vec<R: Read, Fr: PrimeField>(
+    mut reader: R,
+    header: &Header,
+    constraint: usize,
+) -> Result<Vec<(usize, Fr)>> {
     let n_vec = reader.read_u32::<LittleEndian>()? as usize;
-    let mut vec = Vec::with_capacity(n_vec);
+    let mut vec = Vec::with_capacity(n_vec.min(header.n_wires as usize));
     for _ in 0..n_vec {
-        vec.push((
-            reader.read_u32::<LittleEndian>()? as usize,
-            read_field::<&mut R, Fr>(&mut reader)?,
-        ));
+        let wire = reader.read_u32::<LittleEndian>()?;
+        if wire >= header.n_wires {
+            return Err(R1CSFileError::WireOutOfRange {
+                wire,
+                wires: header.n_wires,
+            });
+        }
+        let coeff = read_field::<&mut R, Fr>(&mut reader)?
+            .ok_or(R1CSFileError::InvalidCoefficient(constraint))?;
+        vec.push((wire as usize, coeff));
     }
     Ok(vec)
 }
 
-fn read_constraints<R: Read, Fr: PrimeField>(
+fn read_constraints<R: Read + Seek, Fr: PrimeField>(
     mut reader: R,
+    size: u64,
     header: &Header,
 ) -> Result<Vec<Constraint<Fr>>> {
-    // todo check section size
-    let mut vec = Vec::with_capacity(header.n_constraints as usize);
-    for _ in 0..header.n_constraints {
+    let start = reader.stream_position()?;
+    let mut vec = Vec::new();
+    for i in 0..header.n_constraints as usize {
         vec.push((
-            read_constraint_vec::<&mut R, Fr>(&mut reader)?,
-            read_constraint_vec::<&mut R, Fr>(&mut reader)?,
-            read_constraint_vec::<&mut R, Fr>(&mut reader)?,
+            read_constraint_vec::<&mut R, Fr>(&mut reader, header, i)?,
+            read_constraint_vec::<&mut R, Fr>(&mut reader, header, i)?,
+            read_constraint_vec::<&mut R, Fr>(&mut reader, header, i)?,
         ));
     }
+
+    let read = reader.stream_position()? - start;
+    if read != size {
+        return Err(R1CSFileError::SectionSize {
+            section: CONSTRAINT_TYPE,
+            expected: size,
+            actual: read,
+        });
+    }
     Ok(vec)
 }
 
 fn read_map<R: Read>(mut reader: R, size: u64, header: &Header) -> Result<Vec<u64>> {
     if size != header.n_wires as u64 * 8 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Invalid map section size",
-        ));
+        return Err(R1CSFileError::SectionSize {
+            section: WIRE2LABEL_TYPE,
+            expected: header.n_wires as u64 * 8,
+            actual: size,
+        });
     }
     let mut vec = Vec::with_capacity(header.n_wires as usize);
     for _ in 0..header.n_wires {
         vec.push(reader.read_u64::<LittleEndian>()?);
     }
+    // the header has at least the wire of the constant 1
     if vec[0] != 0 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "Wire 0 should always be mapped to 0",
-        ));
+        return Err(R1CSFileError::InvalidWireMap);
     }
     Ok(vec)
 }
 
+// moves to the start of a section and returns its size
+fn seek_section<R: Seek>(
+    mut reader: R,
+    sections: &HashMap<u32, (u64, u64)>,
+    section_type: u32,
+) -> Result<u64> {
+    let (offset, size) = *sections
+        .get(&section_type)
+        .ok_or(R1CSFileError::MissingSection(section_type))?;
+    reader.seek(SeekFrom::Start(offset))?;
+    Ok(size)
+}
+
+const HEADER_TYPE: u32 = 1;
+const CONSTRAINT_TYPE: u32 = 2;
+const WIRE2LABEL_TYPE: u32 = 3;
+
 pub fn from_reader<G1: Group, R: Read + Seek>(mut reader: R) -> Result<R1CSFile<G1::Scalar>> {
     let mut magic = [0u8; 4];
     reader.read_exact(&mut magic)?;
     if magic != [0x72, 0x31, 0x63, 0x73] {
         // magic = "r1cs"
-        return Err(Error::new(ErrorKind::InvalidData, "Invalid magic number"));
+        return Err(R1CSFileError::InvalidMagic(magic));
     }
 
     let version = reader.read_u32::<LittleEndian>()?;
     if version != 1 {
-        return Err(Error::new(ErrorKind::InvalidData, "Unsupported version"));
+        return Err(R1CSFileError::UnsupportedVersion(version));
     }
 
     let num_sections = reader.read_u32::<LittleEndian>()?;
 
-    // section type -> file offset
-    let mut section_offsets = HashMap::<u32, u64>::new();
-    let mut section_sizes = HashMap::<u32, u64>::new();
+    // section type -> file offset and size
+    let mut sections = HashMap::<u32, (u64, u64)>::new();
 
     // get file offset of each section
     for _ in 0..num_sections {
         let section_type = reader.read_u32::<LittleEndian>()?;
         let section_size = reader.read_u64::<LittleEndian>()?;
-        let offset = reader.seek(SeekFrom::Current(0))?;
-        section_offsets.insert(section_type, offset);
-        section_sizes.insert(section_type, section_size);
+        let offset = reader.stream_position()?;
+        if sections
+            .insert(section_type, (offset, section_size))
+            .is_some()
+        {
+            return Err(R1CSFileError::DuplicateSection(section_type));
+        }
         reader.seek(SeekFrom::Current(section_size as i64))?;
     }
 
-    let header_type = 1;
-    let constraint_type = 2;
-    let wire2label_type = 3;
-
-    reader.seek(SeekFrom::Start(*section_offsets.get(&header_type).unwrap()))?;
-    let header = read_header(&mut reader, *section_sizes.get(&header_type).unwrap())?;
-    if header.field_size != 32 {
-        return Err(Error::new(
-            ErrorKind::InvalidData,
-            "This parser only supports 32-byte fields",
-        ));
+    let size = seek_section(&mut reader, &sections, HEADER_TYPE)?;
+    let header = read_header::<_, G1::Scalar>(&mut reader, size)?;
+    if !is_modulus::<G1::Scalar>(&header.prime_size) {
+        let prime = header
+            .prime_size
+            .iter()
+            .rev()
+            .map(|byte| format!("{:02x}", byte));
+        return Err(R1CSFileError::WrongPrime(prime.collect()));
     }
-    // if header.prime_size != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
-    //     return Err(Error::new(ErrorKind::InvalidData, "This parser only supports bn256"));
-    // }
 
-    reader.seek(SeekFrom::Start(
-        *section_offsets.get(&constraint_type).unwrap(),
-    ))?;
-    let constraints = read_constraints::<&mut R, <G1 as Group>::Scalar>(&mut reader, &header)?;
+    let size = seek_section(&mut reader, &sections, CONSTRAINT_TYPE)?;
+    let constraints = read_constraints::<&mut R, G1::Scalar>(&mut reader, size, &header)?;
 
-    reader.seek(SeekFrom::Start(
-        *section_offsets.get(&wire2label_type).unwrap(),
-    ))?;
-    let wire_mapping = read_map(
-        &mut reader,
-        *section_sizes.get(&wire2label_type).unwrap(),
-        &header,
-    )?;
+    let size = seek_section(&mut reader, &sections, WIRE2LABEL_TYPE)?;
+    let wire_mapping = read_map(&mut reader, size, &header)?;
 
     Ok(R1CSFile {
         version,
@@ -213,3 +337,195 @@ pub fn from_reader<G1: Group, R: Read + Seek>(mut reader: R) -> Result<R1CSFile<
         wire_mapping,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ff::Field;
+    use secq256k1::AffinePoint;
+    use std::io::Cursor;
+
+    type Fr = <AffinePoint as Group>::Scalar;
+
+    // the modulus of the scalar field of secq256k1, little-endian
+    const SECQ_PRIME: [u8; 32] = [
+        0x2f, 0xfc, 0xff, 0xff, 0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+        0xff, 0xff,
+    ];
+    // circom's default prime, the scalar field of BN254, little-endian
+    const BN254_PRIME: [u8; 32] = [
+        0x01, 0x00, 0x00, 0xf0, 0x93, 0xf5, 0xe1, 0x43, 0x91, 0x70, 0xb9, 0x79, 0x48, 0xe8, 0x33,
+        0x28, 0x5d, 0x58, 0x81, 0x81, 0xb6, 0x45, 0x50, 0xb8, 0x29, 0xa0, 0x31, 0xe1, 0x72, 0x4e,
+        0x64, 0x30,
+    ];
+
+    // the header of a circuit with the wires `[1, out, in]`
+    fn header(prime: &[u8]) -> Vec<u8> {
+        let mut section = 32u32.to_le_bytes().to_vec();
+        section.extend(prime);
+        for n in [3u32, 1, 1, 0] {
+            section.extend(n.to_le_bytes());
+        }
+        section.extend(3u64.to_le_bytes());
+        section.extend(1u32.to_le_bytes());
+        section
+    }
+
+    // the constraint `in * in = out`, with `in` the wire `input_wire`
+    fn constraints(input_wire: u32) -> Vec<u8> {
+        let one: [u8; 32] = to_le_bytes(&Fr::one()).into();
+        let mut section = Vec::new();
+        for wire in [input_wire, input_wire, 1] {
+            section.extend(1u32.to_le_bytes());
+            section.extend(wire.to_le_bytes());
+            section.extend(one);
+        }
+        section
+    }
+
+    fn wire_map() -> Vec<u8> {
+        [0u64, 1, 2]
+            .iter()
+            .flat_map(|label| label.to_le_bytes())
+            .collect()
+    }
+
+    fn file(sections: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut file = b"r1cs".to_vec();
+        file.extend(1u32.to_le_bytes());
+        file.extend((sections.len() as u32).to_le_bytes());
+        for (section_type, section) in sections {
+            file.extend(section_type.to_le_bytes());
+            file.extend((section.len() as u64).to_le_bytes());
+            file.extend(section);
+        }
+        file
+    }
+
+    fn load(file: Vec<u8>) -> Result<(R1CS<Fr>, Vec<usize>)> {
+        load_r1cs_from_bin::<AffinePoint, _>(Cursor::new(file))
+    }
+
+    #[test]
+    fn check_load() {
+        let (r1cs, wire_map) = load(file(&[
+            (HEADER_TYPE, header(&SECQ_PRIME)),
+            (CONSTRAINT_TYPE, constraints(2)),
+            (WIRE2LABEL_TYPE, wire_map()),
+        ]))
+        .unwrap();
+
+        assert_eq!(
+            (r1cs.num_inputs, r1cs.num_aux, r1cs.num_variables),
+            (3, 0, 3)
+        );
+        assert_eq!(
+            r1cs.constraints[0],
+            (
+                vec![(2, Fr::one())],
+                vec![(2, Fr::one())],
+                vec![(1, Fr::one())]
+            )
+        );
+        assert_eq!(wire_map, vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn check_errors() {
+        let sections = || {
+            vec![
+                (HEADER_TYPE, header(&SECQ_PRIME)),
+                (CONSTRAINT_TYPE, constraints(2)),
+                (WIRE2LABEL_TYPE, wire_map()),
+            ]
+        };
+        let valid = file(&sections());
+
+        let mut bad_magic = valid.clone();
+        bad_magic[..4].copy_from_slice(b"wtns");
+        assert!(matches!(
+            load(bad_magic),
+            Err(R1CSFileError::InvalidMagic(_))
+        ));
+
+        let mut version_2 = valid.clone();
+        version_2[4] = 2;
+        assert!(matches!(
+            load(version_2),
+            Err(R1CSFileError::UnsupportedVersion(2))
+        ));
+
+        assert!(matches!(
+            load(valid[..valid.len() - 1].to_vec()),
+            Err(R1CSFileError::Io(_))
+        ));
+        assert!(matches!(
+            load(file(&sections()[..2])),
+            Err(R1CSFileError::MissingSection(WIRE2LABEL_TYPE))
+        ));
+
+        let mut duplicate = sections();
+        duplicate.push((HEADER_TYPE, header(&SECQ_PRIME)));
+        assert!(matches!(
+            load(file(&duplicate)),
+            Err(R1CSFileError::DuplicateSection(HEADER_TYPE))
+        ));
+
+        let mut padded = sections();
+        padded[1].1.push(0);
+        assert!(matches!(
+            load(file(&padded)),
+            Err(R1CSFileError::SectionSize {
+                section: CONSTRAINT_TYPE,
+                expected: 121,
+                actual: 120
+            })
+        ));
+
+        let mut bn254 = sections();
+        bn254[0].1 = header(&BN254_PRIME);
+        match load(file(&bn254)) {
+            Err(R1CSFileError::WrongPrime(prime)) => assert_eq!(
+                prime,
+                "30644e72e131a029b85045b68181585d2833e84879b9709143e1f593f0000001"
+            ),
+            _ => panic!("expected a wrong prime"),
+        }
+
+        let mut out_of_range = sections();
+        out_of_range[1].1 = constraints(3);
+        assert!(matches!(
+            load(file(&out_of_range)),
+            Err(R1CSFileError::WireOutOfRange { wire: 3, wires: 3 })
+        ));
+
+        let mut too_many_inputs = sections();
+        too_many_inputs[0].1[40..44].copy_from_slice(&3u32.to_le_bytes());
+        assert!(matches!(
+            load(file(&too_many_inputs)),
+            Err(R1CSFileError::InvalidHeader {
+                signals: 5,
+                wires: 3
+            })
+        ));
+
+        let mut too_many_private = sections();
+        too_many_private[0].1[48..52].copy_from_slice(&2u32.to_le_bytes());
+        assert!(matches!(
+            load(file(&too_many_private)),
+            Err(R1CSFileError::InvalidHeader {
+                signals: 5,
+                wires: 3
+            })
+        ));
+
+        // a field size is rejected before the prime is read
+        let mut huge_field = sections();
+        huge_field[0].1[..4].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert!(matches!(
+            load(file(&huge_field)),
+            Err(R1CSFileError::FieldSize(u32::MAX))
+        ));
+    }
+}
@@ -0,0 +1,254 @@
+//! The correspondence between circom's wires and Spartan's variables.
+//!
+//! circom numbers the wires of a circuit as
+//! `[1, public outputs, public inputs, private inputs, internal wires]`, while
+//! Spartan satisfies its constraints over `z = (vars, 1, inputs)`. A circuit is
+//! loaded with its public outputs and inputs as the Spartan inputs, in circom's
+//! order, and with the private inputs followed by the internal wires as the
+//! variables, but the wires the optimizer removes.
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Whether a public signal is an output or an input of the circuit
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignalKind {
+    /// a `signal output` of the main component
+    Output,
+    /// a `signal input` of the main component listed as public
+    Input,
+}
+
+/// A public signal of a circom circuit, as a Spartan input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicSignal {
+    /// whether the signal is an output or an input
+    pub kind: SignalKind,
+    /// the position of the signal among the public outputs or inputs
+    pub index: usize,
+    /// the circom wire of the signal
+    pub wire: usize,
+    /// the label of the wire, the line of the signal in the `.sym` file
+    pub label: usize,
+}
+
+impl fmt::Display for PublicSignal {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = match self.kind {
+            SignalKind::Output => "output",
+            SignalKind::Input => "input",
+        };
+        write!(
+            f,
+            "public {} {} (wire {}, label {})",
+            kind, self.index, self.wire, self.label
+        )
+    }
+}
+
+/// The wires of a circom circuit, read from the header of its `.r1cs` file
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WireLayout {
+    num_pub_out: usize,
+    num_pub_in: usize,
+    num_prv_in: usize,
+    wire_mapping: Vec<usize>,
+    // the wire of each variable, in increasing order
+    vars: Vec<usize>,
+}
+
+impl WireLayout {
+    /// `wire_mapping` maps each wire to its label, so that its length is the
+    /// number of wires, which must cover the constant 1 and the inputs and
+    /// outputs
+    pub fn new(
+        num_pub_out: usize,
+        num_pub_in: usize,
+        num_prv_in: usize,
+        wire_mapping: Vec<usize>,
+    ) -> Self {
+        assert!(
+            1 + num_pub_out + num_pub_in + num_prv_in <= wire_mapping.len(),
+            "more signals than wires"
+        );
+
+        let vars = (1 + num_pub_out + num_pub_in..wire_mapping.len()).collect();
+        Self {
+            num_pub_out,
+            num_pub_in,
+            num_prv_in,
+            wire_mapping,
+            vars,
+        }
+    }
+
+    // the same layout with the variables `vars` only
+    pub(crate) fn with_vars(self, vars: Vec<usize>) -> Self {
+        debug_assert!(vars.windows(2).all(|pair| pair[0] < pair[1]));
+        debug_assert!(vars.iter().all(|wire| *wire > self.num_inputs()));
+        Self { vars, ..self }
+    }
+
+    // whether the layout holds the invariants of `new` and `with_vars`, as a
+    // decoded one may not
+    pub(crate) fn is_valid(&self) -> bool {
+        1 + self.num_inputs() + self.num_prv_in <= self.num_wires()
+            && self.vars.windows(2).all(|pair| pair[0] < pair[1])
+            && self
+                .vars
+                .iter()
+                .all(|wire| *wire > self.num_inputs() && *wire < self.num_wires())
+    }
+
+    /// Returns the number of circom wires, the constant 1 included
+    pub fn num_wires(&self) -> usize {
+        self.wire_mapping.len()
+    }
+
+    /// Returns the number of Spartan inputs, the public outputs and inputs
+    pub fn num_inputs(&self) -> usize {
+        self.num_pub_out + self.num_pub_in
+    }
+
+    /// Returns the number of Spartan variables, all the wires but the
+    /// constant 1, the public signals and the wires the optimizer removes
+    pub fn num_vars(&self) -> usize {
+        self.vars.len()
+    }
+
+    /// Returns the wire of each Spartan variable
+    pub fn var_wires(&self) -> &[usize] {
+        &self.vars
+    }
+
+    /// Returns the number of private inputs of the circuit
+    pub fn num_prv_in(&self) -> usize {
+        self.num_prv_in
+    }
+
+    /// Returns the label of `wire`, its line in the `.sym` file
+    pub fn label(&self, wire: usize) -> usize {
+        self.wire_mapping[wire]
+    }
+
+    /// Returns the column of `wire` in `z = (vars, 1, inputs)`, or `None` if
+    /// the optimizer removed it
+    pub fn column(&self, wire: usize) -> Option<usize> {
+        assert!(wire < self.num_wires(), "wire {} is out of range", wire);
+
+        if wire <= self.num_inputs() {
+            Some(self.num_vars() + wire)
+        } else {
+            self.vars.binary_search(&wire).ok()
+        }
+    }
+
+    /// Returns the signal behind each Spartan input, in the order of the inputs
+    pub fn public_signals(&self) -> Vec<PublicSignal> {
+        (1..=self.num_inputs())
+            .map(|wire| {
+                let (kind, index) = if wire <= self.num_pub_out {
+                    (SignalKind::Output, wire - 1)
+                } else {
+                    (SignalKind::Input, wire - 1 - self.num_pub_out)
+                };
+                PublicSignal {
+                    kind,
+                    index,
+                    wire,
+                    label: self.label(wire),
+                }
+            })
+            .collect()
+    }
+
+    /// Splits a witness in circom's wire order into the Spartan variables and
+    /// inputs, dropping the constant 1 and the wires the optimizer removed
+    pub fn split_witness<T: Clone>(&self, witness: &[T]) -> (Vec<T>, Vec<T>) {
+        assert_eq!(
+            witness.len(),
+            self.num_wires(),
+            "the witness is not one of the circuit"
+        );
+
+        let inputs = witness[1..=self.num_inputs()].to_vec();
+        let vars = self
+            .vars
+            .iter()
+            .map(|wire| witness[*wire].clone())
+            .collect();
+        (vars, inputs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `[1, out, in_0, in_1, prv, internal]`
+    fn layout() -> WireLayout {
+        WireLayout::new(1, 2, 1, vec![0, 1, 2, 3, 4, 7])
+    }
+
+    #[test]
+    fn check_columns() {
+        let layout = layout();
+        assert_eq!((layout.num_vars(), layout.num_inputs()), (2, 3));
+
+        // z = (prv, internal, 1, out, in_0, in_1)
+        let columns: Vec<usize> = (0..6).map(|wire| layout.column(wire).unwrap()).collect();
+        assert_eq!(columns, vec![2, 3, 4, 5, 0, 1]);
+
+        let witness = ["1", "out", "in_0", "in_1", "prv", "internal"];
+        let (vars, inputs) = layout.split_witness(&witness);
+        assert_eq!(vars, ["prv", "internal"]);
+        assert_eq!(inputs, ["out", "in_0", "in_1"]);
+        for (wire, value) in witness.iter().enumerate().skip(1) {
+            let column = layout.column(wire).unwrap();
+            let z = if column < layout.num_vars() {
+                vars[column]
+            } else {
+                inputs[column - layout.num_vars() - 1]
+            };
+            assert_eq!(z, *value);
+        }
+
+        // z = (internal, 1, out, in_0, in_1) once `prv` is removed
+        let layout = layout.with_vars(vec![5]);
+        assert_eq!(layout.column(4), None);
+        assert_eq!(layout.column(5), Some(0));
+        assert_eq!(layout.column(1), Some(2));
+        assert_eq!(
+            layout.split_witness(&witness),
+            (vec!["internal"], vec!["out", "in_0", "in_1"])
+        );
+    }
+
+    #[test]
+    fn check_public_signals() {
+        let signals = layout().public_signals();
+        assert_eq!(
+            signals,
+            vec![
+                PublicSignal {
+                    kind: SignalKind::Output,
+                    index: 0,
+                    wire: 1,
+                    label: 1
+                },
+                PublicSignal {
+                    kind: SignalKind::Input,
+                    index: 0,
+                    wire: 2,
+                    label: 2
+                },
+                PublicSignal {
+                    kind: SignalKind::Input,
+                    index: 1,
+                    wire: 3,
+                    label: 3
+                },
+            ]
+        );
+        assert_eq!(signals[1].to_string(), "public input 0 (wire 2, label 2)");
+    }
+}
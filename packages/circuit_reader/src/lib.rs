@@ -1,51 +1,207 @@
 mod circom_reader;
+mod layout;
+mod optimize;
+mod sym;
+mod wtns;
 
-use circom_reader::{load_r1cs_from_bin_file, R1CS};
+pub use circom_reader::R1CSFileError;
+use circom_reader::{load_r1cs_from_bin_file, to_le_bytes, Constraint, R1CS};
 use ff::PrimeField;
-use libspartan::Instance;
+use itertools::Itertools;
+pub use layout::{PublicSignal, SignalKind, WireLayout};
+use libspartan::{Instance, UnsatisfiedConstraint};
+pub use optimize::{OptimizationStats, R1CSStats};
 use secq256k1::AffinePoint;
 use secq256k1::FieldBytes;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+pub use sym::{SignalNames, SymFileError};
+pub use wtns::{
+    load_witness_from_bin, load_witness_from_bin_file, to_spartan_assignments,
+    write_witness_to_bin, write_witness_to_bin_file, WitnessFileError,
+};
 
-pub fn load_as_spartan_inst(circuit_file: PathBuf, num_pub_inputs: usize) -> Instance {
-    let (r1cs, _) = load_r1cs_from_bin_file::<AffinePoint>(&circuit_file);
-    let spartan_inst = convert_to_spartan_r1cs(&r1cs, num_pub_inputs);
-    spartan_inst
+type Fr = <AffinePoint as group::Group>::Scalar;
+
+/// A circom circuit loaded as a Spartan instance, with the layout that maps its
+/// wires to the Spartan variables and inputs
+pub struct SpartanCircuit {
+    pub instance: Instance,
+    pub layout: WireLayout,
+    // the wires each constraint involves, but the constant 1
+    constraint_wires: Vec<Vec<usize>>,
+}
+
+impl SpartanCircuit {
+    /// Encodes the circuit as the `.circuit` files the provers load: the
+    /// bincode instance, followed by the bincode layout, which files written
+    /// before the optimizer do not have and the provers reject
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = bincode::serialize(&self.instance).unwrap();
+        bincode::serialize_into(&mut bytes, &self.layout).unwrap();
+        bytes
+    }
+
+    /// Returns the wires of the constraint `index`, but the constant 1
+    pub fn constraint_wires(&self, index: usize) -> &[usize] {
+        self.constraint_wires
+            .get(index)
+            .map(Vec::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Describes a constraint a witness does not satisfy, by the signals it
+    /// involves and the values of its terms
+    pub fn describe(&self, constraint: &UnsatisfiedConstraint, names: &SignalNames) -> String {
+        let signals = self
+            .constraint_wires(constraint.index)
+            .iter()
+            .map(|wire| names.describe(*wire))
+            .join(", ");
+        format!(
+            "constraint {} over {}: A·z = {}, B·z = {}, C·z = {}",
+            constraint.index,
+            signals,
+            to_hex(&constraint.a),
+            to_hex(&constraint.b),
+            to_hex(&constraint.c)
+        )
+    }
+}
+
+/// Decodes a `.circuit` file written by `SpartanCircuit::to_bytes`, with its
+/// layout if the file has one. Files written before the layouts have none,
+/// and their variables cannot be told from the instance alone
+pub fn read_circuit(bytes: &[u8]) -> Result<(Instance, Option<WireLayout>), bincode::Error> {
+    let mut reader = bytes;
+    let instance: Instance = bincode::deserialize_from(&mut reader)?;
+    if reader.is_empty() {
+        return Ok((instance, None));
+    }
+
+    let layout: WireLayout = bincode::deserialize(reader)?;
+    if !layout.is_valid()
+        || layout.num_inputs() != instance.inst.get_num_inputs()
+        || layout.num_vars() > instance.inst.get_num_vars()
+    {
+        return Err(Box::new(bincode::ErrorKind::Custom(
+            "the layout does not match the instance".to_string(),
+        )));
+    }
+    Ok((instance, Some(layout)))
+}
+
+// the little-endian `bytes` as a hex number
+fn to_hex(bytes: &[u8; 32]) -> String {
+    let hex: String = bytes
+        .iter()
+        .rev()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    match hex.trim_start_matches('0') {
+        "" => "0x0".to_string(),
+        digits => format!("0x{}", digits),
+    }
+}
+
+/// Loads a circom `.r1cs` file compiled with `--prime secq256k1`, with its
+/// public outputs and inputs as the Spartan inputs
+pub fn load_circuit(circuit_file: &Path) -> Result<SpartanCircuit, R1CSFileError> {
+    let (r1cs, layout) = load_r1cs(circuit_file)?;
+    to_spartan_circuit(r1cs.constraints, layout)
+}
+
+/// Loads a circom `.r1cs` file as `load_circuit` does, once `optimize` has
+/// removed its linear constraints, duplicate constraints and unused wires.
+///
+/// The instance has fewer variables than the circuit has private wires, so
+/// that a witness computed by circom is split with `circuit.layout`, as
+/// `to_spartan_assignments` does.
+pub fn load_optimized_circuit(
+    circuit_file: &Path,
+) -> Result<(SpartanCircuit, OptimizationStats), R1CSFileError> {
+    let (r1cs, layout) = load_r1cs(circuit_file)?;
+    let (constraints, layout, stats) = optimize::optimize(r1cs.constraints, layout);
+    Ok((to_spartan_circuit(constraints, layout)?, stats))
+}
+
+fn load_r1cs(circuit_file: &Path) -> Result<(R1CS<Fr>, WireLayout), R1CSFileError> {
+    let (r1cs, wire_mapping) = load_r1cs_from_bin_file::<AffinePoint>(circuit_file)?;
+    let layout = WireLayout::new(
+        r1cs.num_pub_out,
+        r1cs.num_pub_in,
+        r1cs.num_prv_in,
+        wire_mapping,
+    );
+    Ok((r1cs, layout))
+}
+
+fn to_spartan_circuit(
+    constraints: Vec<Constraint<Fr>>,
+    layout: WireLayout,
+) -> Result<SpartanCircuit, R1CSFileError> {
+    let instance = convert_to_spartan_r1cs(&constraints, &layout)?;
+    let constraint_wires = constraints
+        .iter()
+        .map(|(a, b, c)| {
+            a.iter()
+                .chain(b)
+                .chain(c)
+                .map(|(wire, _)| *wire)
+                .filter(|wire| *wire != 0)
+                .sorted()
+                .dedup()
+                .collect()
+        })
+        .collect();
+    Ok(SpartanCircuit {
+        instance,
+        layout,
+        constraint_wires,
+    })
+}
+
+/// Loads a circom `.r1cs` file compiled with `--prime secq256k1` as a Spartan
+/// instance, as `load_circuit` does
+pub fn load_as_spartan_inst(circuit_file: PathBuf) -> Result<Instance, R1CSFileError> {
+    Ok(load_circuit(&circuit_file)?.instance)
 }
 
 fn convert_to_spartan_r1cs<F: PrimeField<Repr = FieldBytes>>(
-    r1cs: &R1CS<F>,
-    num_pub_inputs: usize,
-) -> Instance {
-    let num_cons = r1cs.constraints.len();
-    let num_vars = r1cs.num_variables;
-    let num_inputs = num_pub_inputs;
+    constraints: &[Constraint<F>],
+    layout: &WireLayout,
+) -> Result<Instance, R1CSFileError> {
+    let num_cons = constraints.len();
+    let num_vars = layout.num_vars();
+    let num_inputs = layout.num_inputs();
+
+    // the optimizer only removes the wires no constraint uses
+    let column = |wire: &usize| layout.column(*wire).unwrap();
 
     let mut A = vec![];
     let mut B = vec![];
     let mut C = vec![];
 
-    for (i, constraint) in r1cs.constraints.iter().enumerate() {
+    for (i, constraint) in constraints.iter().enumerate() {
         let (a, b, c) = constraint;
 
         for (j, coeff) in a.iter() {
-            let bytes: [u8; 32] = coeff.to_repr().into();
+            let bytes: [u8; 32] = to_le_bytes(coeff).into();
 
-            A.push((i, *j, bytes));
+            A.push((i, column(j), bytes));
         }
 
         for (j, coeff) in b.iter() {
-            let bytes: [u8; 32] = coeff.to_repr().into();
-            B.push((i, *j, bytes));
+            let bytes: [u8; 32] = to_le_bytes(coeff).into();
+            B.push((i, column(j), bytes));
         }
 
         for (j, coeff) in c.iter() {
-            let bytes: [u8; 32] = coeff.to_repr().into();
-            C.push((i, *j, bytes));
+            let bytes: [u8; 32] = to_le_bytes(coeff).into();
+            C.push((i, column(j), bytes));
         }
     }
 
-    let inst = Instance::new(
+    Instance::new(
         num_cons,
         num_vars,
         num_inputs,
@@ -53,7 +209,110 @@ fn convert_to_spartan_r1cs<F: PrimeField<Repr = FieldBytes>>(
         B.as_slice(),
         C.as_slice(),
     )
-    .unwrap();
+    .map_err(R1CSFileError::InvalidInstance)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use libspartan::Assignment;
+
+    #[test]
+    fn check_test_circuit() {
+        // `c <== a * b[0] * b[1]` with `a` and `b` public, from spartan_wasm
+        let circuit =
+            load_circuit(Path::new("../spartan_wasm/test_circuit/test_circuit.r1cs")).unwrap();
+        let layout = &circuit.layout;
+        assert_eq!((layout.num_vars(), layout.num_inputs()), (1, 4));
 
-    inst
+        let kinds: Vec<(SignalKind, usize)> = layout
+            .public_signals()
+            .iter()
+            .map(|signal| (signal.kind, signal.index))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (SignalKind::Output, 0),
+                (SignalKind::Input, 0),
+                (SignalKind::Input, 1),
+                (SignalKind::Input, 2)
+            ]
+        );
+
+        // `[1, c, a, b[0], b[1], b[0] * b[1]]`, little-endian as Spartan reads it
+        let encode = |x: u8| {
+            let mut bytes = [0u8; 32];
+            bytes[0] = x;
+            bytes
+        };
+        let is_sat = |witness: [u8; 6]| {
+            let (vars, inputs) = layout.split_witness(&witness.map(encode));
+            circuit
+                .instance
+                .is_sat(
+                    &Assignment::new(&vars).unwrap(),
+                    &Assignment::new(&inputs).unwrap(),
+                )
+                .unwrap()
+        };
+        assert!(is_sat([1, 12, 2, 3, 2, 6]));
+        assert!(!is_sat([1, 11, 2, 3, 2, 6]));
+        assert!(!is_sat([1, 12, 2, 3, 2, 5]));
+    }
+
+    #[test]
+    fn check_circuit_bytes() {
+        let (circuit, _) = load_optimized_circuit(Path::new(
+            "../spartan_wasm/test_circuit/linear_circuit.r1cs",
+        ))
+        .unwrap();
+        let bytes = circuit.to_bytes();
+        let (instance, layout) = read_circuit(&bytes).unwrap();
+        assert_eq!(
+            bincode::serialize(&instance).unwrap(),
+            bincode::serialize(&circuit.instance).unwrap()
+        );
+        assert_eq!(layout, Some(circuit.layout.clone()));
+
+        // the instance alone, as written before the layouts
+        let old = bincode::serialize(&circuit.instance).unwrap();
+        assert!(read_circuit(&old).unwrap().1.is_none());
+
+        // the layout of another circuit
+        let other =
+            load_circuit(Path::new("../spartan_wasm/test_circuit/test_circuit.r1cs")).unwrap();
+        let mut mismatched = bincode::serialize(&circuit.instance).unwrap();
+        bincode::serialize_into(&mut mismatched, &other.layout).unwrap();
+        assert!(read_circuit(&mismatched).is_err());
+        assert!(read_circuit(&bytes[..bytes.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn check_describe() {
+        let circuit =
+            load_circuit(Path::new("../spartan_wasm/test_circuit/test_circuit.r1cs")).unwrap();
+        let mut witness = load_witness_from_bin_file::<<AffinePoint as group::Group>::Scalar>(
+            Path::new("../spartan_wasm/test_circuit/witness.wtns"),
+        )
+        .unwrap();
+        witness[1] = 3u64.into();
+        let (vars, inputs) = to_spartan_assignments(&circuit.layout, &witness).unwrap();
+
+        let unsatisfied = circuit.instance.check_sat_detailed(&vars, &inputs).unwrap();
+        assert_eq!(unsatisfied.len(), 1);
+
+        let sym =
+            "1,1,0,main.c\n2,2,0,main.a\n3,3,0,main.b[0]\n4,4,0,main.b[1]\n5,5,0,main.b_prod\n";
+        let names = SignalNames::from_reader(sym.as_bytes(), &circuit.layout).unwrap();
+        // circom writes `a * b_prod = c` as `-a * b_prod = -c`, so that the
+        // terms are `p - 1`, 2 and `p - 3`
+        assert_eq!(
+            circuit.describe(&unsatisfied[0], &names),
+            "constraint 1 over main.c, main.a, main.b_prod: A·z = 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2e, B·z = 0x2, C·z = 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2c"
+        );
+        assert!(circuit
+            .describe(&unsatisfied[0], &SignalNames::default())
+            .starts_with("constraint 1 over wire 1, wire 2, wire 5: "));
+    }
 }
@@ -0,0 +1,447 @@
+//! Shrinking a circom R1CS before it becomes a Spartan instance.
+//!
+//! The cost of Spartan grows with the number of constraints and variables,
+//! both padded to powers of two, and with the number of non-zero entries.
+//! `optimize` reduces all three while keeping the public signals:
+//!
+//! * the terms of each linear combination are merged by wire, and the zero
+//!   ones dropped;
+//! * the constraints identical up to the order of `A` and `B` are kept once;
+//! * a constraint whose `A` or `B` side is a constant is linear, so that it
+//!   is removed by expressing its private wire used the least with the others
+//!   and substituting this expression in the remaining constraints, unless
+//!   this adds more non-zero entries than the constraint has;
+//! * the private wires no constraint uses any more are dropped from the
+//!   variables.
+//!
+//! A witness of the original circuit satisfies the optimized one once
+//! restricted to the remaining wires, which `WireLayout::split_witness` does.
+use crate::circom_reader::Constraint;
+use crate::layout::WireLayout;
+use ff::PrimeField;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+type LinearCombination<Fr> = Vec<(usize, Fr)>;
+
+/// The sizes of an R1CS that the cost of Spartan depends on
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct R1CSStats {
+    /// the number of constraints
+    pub num_cons: usize,
+    /// the number of variables, the constant 1 and the public signals aside
+    pub num_vars: usize,
+    /// the number of non-zero entries of `A`, `B` and `C` together
+    pub num_non_zero: usize,
+}
+
+impl R1CSStats {
+    fn new<Fr: PrimeField>(constraints: &[Constraint<Fr>], num_vars: usize) -> Self {
+        Self {
+            num_cons: constraints.len(),
+            num_vars,
+            num_non_zero: constraints
+                .iter()
+                .map(|(a, b, c)| a.len() + b.len() + c.len())
+                .sum(),
+        }
+    }
+}
+
+/// The sizes of an R1CS before and after `optimize`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OptimizationStats {
+    /// the sizes of the constraints read from the `.r1cs` file
+    pub before: R1CSStats,
+    /// the sizes of the optimized constraints
+    pub after: R1CSStats,
+}
+
+impl fmt::Display for OptimizationStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (before, after) = (&self.before, &self.after);
+        writeln!(
+            f,
+            "constraints: {} -> {} (padded {} -> {})",
+            before.num_cons,
+            after.num_cons,
+            before.num_cons.next_power_of_two(),
+            after.num_cons.next_power_of_two()
+        )?;
+        writeln!(
+            f,
+            "variables: {} -> {} (padded {} -> {})",
+            before.num_vars,
+            after.num_vars,
+            before.num_vars.next_power_of_two(),
+            after.num_vars.next_power_of_two()
+        )?;
+        write!(
+            f,
+            "non-zero entries: {} -> {}",
+            before.num_non_zero, after.num_non_zero
+        )
+    }
+}
+
+/// Optimizes `constraints`, and returns them with the layout of the wires they
+/// still use
+pub(crate) fn optimize<Fr: PrimeField>(
+    constraints: Vec<Constraint<Fr>>,
+    layout: WireLayout,
+) -> (Vec<Constraint<Fr>>, WireLayout, OptimizationStats) {
+    let before = R1CSStats::new(&constraints, layout.num_vars());
+    let num_inputs = layout.num_inputs();
+
+    // the duplicates go first, so that they do not count in the cost of the
+    // substitutions
+    let mut constraints = dedup(
+        constraints
+            .into_iter()
+            .map(|(a, b, c)| (normalize(a), normalize(b), normalize(c)))
+            .collect(),
+    )
+    .into_iter()
+    .map(Some)
+    .collect::<Vec<_>>();
+    // wire -> the constraints that use it
+    let mut uses: HashMap<usize, HashSet<usize>> = HashMap::new();
+    for (i, constraint) in constraints.iter().enumerate() {
+        for wire in wires(constraint.as_ref().unwrap()) {
+            uses.entry(wire).or_default().insert(i);
+        }
+    }
+
+    // a constraint can become linear, or cheaper to remove, once the wires of
+    // another one are substituted, hence the passes until none is removed
+    loop {
+        let mut removed = false;
+        for i in 0..constraints.len() {
+            let linear = match &constraints[i] {
+                Some(constraint) => linear(constraint),
+                None => continue,
+            };
+            let substitution = match linear {
+                // `0 = 0`
+                Some(linear) if linear.is_empty() => Some(Vec::new()),
+                Some(linear) => substitution(&constraints, &uses, i, &linear, num_inputs),
+                None => None,
+            };
+            let substitution = match substitution {
+                Some(substitution) => substitution,
+                None => continue,
+            };
+
+            for (j, constraint) in substitution {
+                replace(&mut constraints, &mut uses, j, Some(constraint));
+            }
+            replace(&mut constraints, &mut uses, i, None);
+            removed = true;
+        }
+        if !removed {
+            break;
+        }
+    }
+    let constraints = dedup(constraints.into_iter().flatten().collect());
+
+    let used: HashSet<usize> = constraints.iter().flat_map(wires).collect();
+    let vars = layout
+        .var_wires()
+        .iter()
+        .copied()
+        .filter(|wire| used.contains(wire))
+        .collect();
+    let layout = layout.with_vars(vars);
+
+    let after = R1CSStats::new(&constraints, layout.num_vars());
+    (constraints, layout, OptimizationStats { before, after })
+}
+
+// `linear = 0` if the `A` or `B` side of `constraint` is a constant
+fn linear<Fr: PrimeField>((a, b, c): &Constraint<Fr>) -> Option<LinearCombination<Fr>> {
+    match (constant(a), constant(b)) {
+        (Some(k), _) => Some(add(&scale(b, k), &scale(c, -Fr::one()))),
+        (_, Some(k)) => Some(add(&scale(a, k), &scale(c, -Fr::one()))),
+        _ => None,
+    }
+}
+
+// the constraints that use a private wire of the constraint `i`, which is
+// `linear = 0`, once this wire is substituted, if this does not add more
+// non-zero entries than the constraint `i` has
+fn substitution<Fr: PrimeField>(
+    constraints: &[Option<Constraint<Fr>>],
+    uses: &HashMap<usize, HashSet<usize>>,
+    i: usize,
+    linear: &LinearCombination<Fr>,
+    num_inputs: usize,
+) -> Option<Vec<(usize, Constraint<Fr>)>> {
+    // each use of the wire is replaced by the other terms of `linear`, so that
+    // the wire used the least adds the fewest entries, and ties go to the last
+    // one in circom's order, which is usually an intermediate signal
+    let occurrences = |wire: usize| {
+        uses[&wire]
+            .iter()
+            .filter(|j| **j != i)
+            .map(|j| {
+                let (a, b, c) = constraints[*j].as_ref().unwrap();
+                [a, b, c]
+                    .iter()
+                    .filter(|lc| lc.binary_search_by_key(&wire, |(w, _)| *w).is_ok())
+                    .count()
+            })
+            .sum::<usize>()
+    };
+    let wire = linear
+        .iter()
+        .map(|(wire, _)| *wire)
+        .filter(|wire| *wire > num_inputs)
+        .min_by_key(|wire| (occurrences(*wire), std::cmp::Reverse(*wire)))?;
+
+    let coeff = linear.iter().find(|(w, _)| *w == wire).unwrap().1;
+    let others: LinearCombination<Fr> =
+        linear.iter().filter(|(w, _)| *w != wire).cloned().collect();
+    let expression = scale(&others, -coeff.invert().unwrap());
+
+    let (a, b, c) = constraints[i].as_ref().unwrap();
+    let mut growth = -((a.len() + b.len() + c.len()) as isize);
+    let mut substitution = Vec::new();
+    for j in uses[&wire].iter().filter(|j| **j != i) {
+        let (a, b, c) = constraints[*j].as_ref().unwrap();
+        let constraint = (
+            substitute(a, wire, &expression),
+            substitute(b, wire, &expression),
+            substitute(c, wire, &expression),
+        );
+        growth += (constraint.0.len() + constraint.1.len() + constraint.2.len()) as isize;
+        growth -= (a.len() + b.len() + c.len()) as isize;
+        substitution.push((*j, constraint));
+    }
+    if growth > 0 {
+        return None;
+    }
+    Some(substitution)
+}
+
+// replaces the constraint `i`, keeping `uses` up to date
+fn replace<Fr: PrimeField>(
+    constraints: &mut [Option<Constraint<Fr>>],
+    uses: &mut HashMap<usize, HashSet<usize>>,
+    i: usize,
+    constraint: Option<Constraint<Fr>>,
+) {
+    if let Some(old) = &constraints[i] {
+        for wire in wires(old) {
+            uses.get_mut(&wire).unwrap().remove(&i);
+        }
+    }
+    if let Some(new) = &constraint {
+        for wire in wires(new) {
+            uses.entry(wire).or_default().insert(i);
+        }
+    }
+    constraints[i] = constraint;
+}
+
+// the wires of `constraint`, with repetitions
+fn wires<Fr: PrimeField>((a, b, c): &Constraint<Fr>) -> impl Iterator<Item = usize> + '_ {
+    a.iter().chain(b).chain(c).map(|(wire, _)| *wire)
+}
+
+// keeps the first of the constraints left identical, up to the order of `A`
+// and `B`
+fn dedup<Fr: PrimeField>(mut constraints: Vec<Constraint<Fr>>) -> Vec<Constraint<Fr>> {
+    let mut seen = HashSet::new();
+    constraints.retain(|(a, b, c)| {
+        let (a, b) = (encode(a), encode(b));
+        let (a, b) = if a <= b { (a, b) } else { (b, a) };
+        seen.insert((a, b, encode(c)))
+    });
+    constraints
+}
+
+// `lc` with `wire` replaced by `expression`
+fn substitute<Fr: PrimeField>(
+    lc: &LinearCombination<Fr>,
+    wire: usize,
+    expression: &LinearCombination<Fr>,
+) -> LinearCombination<Fr> {
+    let mut result = Vec::with_capacity(lc.len() + expression.len());
+    for (w, coeff) in lc {
+        if *w == wire {
+            result.extend(scale(expression, *coeff));
+        } else {
+            result.push((*w, *coeff));
+        }
+    }
+    normalize(result)
+}
+
+// the value of `lc` if it only has the constant 1
+fn constant<Fr: PrimeField>(lc: &LinearCombination<Fr>) -> Option<Fr> {
+    match lc.as_slice() {
+        [] => Some(Fr::zero()),
+        [(0, k)] => Some(*k),
+        _ => None,
+    }
+}
+
+// sorts the terms by wire, merges those of a wire and drops the zero ones
+fn normalize<Fr: PrimeField>(mut lc: LinearCombination<Fr>) -> LinearCombination<Fr> {
+    lc.sort_by_key(|(wire, _)| *wire);
+    let mut result: LinearCombination<Fr> = Vec::with_capacity(lc.len());
+    for (wire, coeff) in lc {
+        match result.last_mut() {
+            Some((last, sum)) if *last == wire => *sum += coeff,
+            _ => result.push((wire, coeff)),
+        }
+    }
+    result.retain(|(_, coeff)| !bool::from(coeff.is_zero()));
+    result
+}
+
+fn scale<Fr: PrimeField>(lc: &LinearCombination<Fr>, k: Fr) -> LinearCombination<Fr> {
+    if bool::from(k.is_zero()) {
+        return Vec::new();
+    }
+    lc.iter().map(|(wire, coeff)| (*wire, *coeff * k)).collect()
+}
+
+fn add<Fr: PrimeField>(
+    a: &LinearCombination<Fr>,
+    b: &LinearCombination<Fr>,
+) -> LinearCombination<Fr> {
+    normalize(a.iter().chain(b).cloned().collect())
+}
+
+// a key for a normalized linear combination
+fn encode<Fr: PrimeField>(lc: &LinearCombination<Fr>) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for (wire, coeff) in lc {
+        bytes.extend((*wire as u64).to_le_bytes());
+        bytes.extend(coeff.to_repr().as_ref());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{load_optimized_circuit, load_witness_from_bin_file, to_spartan_assignments};
+    use crate::{to_spartan_circuit, Fr};
+    use std::path::Path;
+
+    fn lc(terms: &[(usize, i64)]) -> LinearCombination<Fr> {
+        terms
+            .iter()
+            .map(|(wire, coeff)| {
+                let value = Fr::from(coeff.unsigned_abs());
+                (*wire, if *coeff < 0 { -value } else { value })
+            })
+            .collect()
+    }
+
+    // `[1, out, in, x, y, z]` with `x = in + 1`, `y = x * x`, `z = 2 * y + x`
+    // and `out = z * in`
+    fn constraints() -> Vec<Constraint<Fr>> {
+        vec![
+            // `z` is substituted by an expression that uses `x`, removed after
+            (lc(&[]), lc(&[]), lc(&[(5, 1), (4, -2), (3, -1)])),
+            (lc(&[]), lc(&[]), lc(&[(3, 1), (2, -1), (0, -1)])),
+            (lc(&[(3, 1)]), lc(&[(3, 1)]), lc(&[(4, 1)])),
+            (lc(&[(5, 1)]), lc(&[(2, 1)]), lc(&[(1, 1)])),
+            // `y = x * x` again, with a split term and a zero one
+            (lc(&[(3, 1)]), lc(&[(3, 2), (2, 0), (3, -1)]), lc(&[(4, 1)])),
+        ]
+    }
+
+    #[test]
+    fn check_optimize() {
+        let layout = WireLayout::new(1, 1, 0, (0..6).collect());
+        let (constraints, layout, stats) = optimize(constraints(), layout);
+
+        // `(in + 1) * (in + 1) = y` and `(2 * y + in + 1) * in = out`
+        assert_eq!(constraints.len(), 2);
+        assert_eq!(layout.var_wires(), [4]);
+        assert_eq!(
+            stats,
+            OptimizationStats {
+                before: R1CSStats {
+                    num_cons: 5,
+                    num_vars: 3,
+                    num_non_zero: 17
+                },
+                after: R1CSStats {
+                    num_cons: 2,
+                    num_vars: 1,
+                    num_non_zero: 10
+                },
+            }
+        );
+        assert_eq!(
+            stats.to_string(),
+            "constraints: 5 -> 2 (padded 8 -> 2)\n\
+             variables: 3 -> 1 (padded 4 -> 1)\n\
+             non-zero entries: 17 -> 10"
+        );
+
+        let circuit = to_spartan_circuit(constraints, layout).unwrap();
+        let is_sat = |witness: [u64; 6]| {
+            let witness = witness.map(Fr::from);
+            let (vars, inputs) = to_spartan_assignments(&circuit.layout, &witness).unwrap();
+            circuit.instance.is_sat(&vars, &inputs).unwrap()
+        };
+        assert!(is_sat([1, 108, 3, 4, 16, 36]));
+        assert!(!is_sat([1, 107, 3, 4, 16, 36]));
+        assert!(!is_sat([1, 108, 3, 4, 17, 36]));
+        // the removed wires are computed from the others
+        assert!(is_sat([1, 108, 3, 0, 16, 0]));
+    }
+
+    #[test]
+    fn check_fill_in() {
+        // `[1, out, in, p, q, r, t]` with `t = p + q + r`, where each of the
+        // wires is used three times elsewhere: substituting any of them would
+        // add 6 entries and remove 4
+        let constraints = vec![
+            (lc(&[]), lc(&[]), lc(&[(3, -1), (4, -1), (5, -1), (6, 1)])),
+            (lc(&[(3, 1)]), lc(&[(3, 1)]), lc(&[(1, 1)])),
+            (lc(&[(4, 1)]), lc(&[(4, 1)]), lc(&[(1, 1)])),
+            (lc(&[(5, 1)]), lc(&[(5, 1)]), lc(&[(1, 1)])),
+            (lc(&[(6, 1)]), lc(&[(6, 1)]), lc(&[(1, 1)])),
+            (lc(&[(3, 1)]), lc(&[(4, 1)]), lc(&[(2, 1)])),
+            (lc(&[(5, 1)]), lc(&[(6, 1)]), lc(&[(2, 1)])),
+        ];
+        let layout = WireLayout::new(1, 1, 0, (0..7).collect());
+        let (optimized, layout, stats) = optimize(constraints.clone(), layout);
+        assert_eq!(optimized, constraints);
+        assert_eq!(layout.var_wires(), [3, 4, 5, 6]);
+        assert_eq!(stats.before, stats.after);
+
+        // once `r` is only used twice, substituting it adds as many entries as
+        // the linear constraint has
+        let (optimized, _, stats) = optimize(
+            constraints[..6].to_vec(),
+            WireLayout::new(1, 1, 0, (0..7).collect()),
+        );
+        assert_eq!(optimized.len(), 5);
+        assert!(stats.after.num_non_zero <= stats.before.num_non_zero);
+    }
+
+    #[test]
+    fn check_test_circuit() {
+        // `b[0] * b[1] = b_prod` and `a * b_prod = c` are not linear
+        let (circuit, stats) =
+            load_optimized_circuit(Path::new("../spartan_wasm/test_circuit/test_circuit.r1cs"))
+                .unwrap();
+        assert_eq!(stats.before, stats.after);
+        assert_eq!(circuit.layout.var_wires(), [5]);
+
+        let witness = load_witness_from_bin_file::<Fr>(Path::new(
+            "../spartan_wasm/test_circuit/witness.wtns",
+        ))
+        .unwrap();
+        let (vars, inputs) = to_spartan_assignments(&circuit.layout, &witness).unwrap();
+        assert!(circuit.instance.is_sat(&vars, &inputs).unwrap());
+    }
+}
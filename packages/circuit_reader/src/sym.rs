@@ -0,0 +1,105 @@
+//! Reading circom `.sym` files, which name the signal behind each label.
+//!
+//! Each line of a `.sym` file is `label,wire,component,name`, with the wire
+//! `-1` for the signals the compiler optimized away. Several labels can share
+//! a wire, so that the name of a wire is the one of the label the `.r1cs` file
+//! maps it to.
+use crate::layout::WireLayout;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+use thiserror::Error;
+
+/// Errors returned when reading a circom `.sym` file
+#[derive(Error, Debug)]
+pub enum SymFileError {
+    /// the file could not be read
+    #[error("failed to read the symbols file: {0}")]
+    Io(#[from] std::io::Error),
+    /// a line is not `label,wire,component,name`
+    #[error("invalid symbol on line {0}")]
+    InvalidLine(usize),
+}
+
+/// The names of the signals of a circuit, by wire
+#[derive(Clone, Debug, Default)]
+pub struct SignalNames {
+    names: HashMap<usize, String>,
+}
+
+impl SignalNames {
+    /// Loads the names of the wires of the circuit with `layout` from the
+    /// `.sym` file the compiler wrote next to its `.r1cs` file
+    pub fn load(sym_file: &Path, layout: &WireLayout) -> Result<Self, SymFileError> {
+        let reader = File::open(sym_file)?;
+        Self::from_reader(BufReader::new(reader), layout)
+    }
+
+    /// Reads the names of the wires of the circuit with `layout` from a `.sym` file
+    pub fn from_reader<R: BufRead>(reader: R, layout: &WireLayout) -> Result<Self, SymFileError> {
+        // label -> wire, for the labels the wires are mapped to
+        let labels: HashMap<usize, usize> = (0..layout.num_wires())
+            .map(|wire| (layout.label(wire), wire))
+            .collect();
+
+        let mut names = HashMap::new();
+        for (i, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let mut fields = line.splitn(4, ',');
+            let label = fields.next().and_then(|label| label.parse::<usize>().ok());
+            let name = fields.nth(2);
+            match (label, name) {
+                (Some(label), Some(name)) => {
+                    if let Some(wire) = labels.get(&label) {
+                        names.insert(*wire, name.to_string());
+                    }
+                }
+                _ => return Err(SymFileError::InvalidLine(i + 1)),
+            }
+        }
+        Ok(Self { names })
+    }
+
+    /// Returns the name of the signal on `wire`, if the file names it
+    pub fn wire_name(&self, wire: usize) -> Option<&str> {
+        self.names.get(&wire).map(String::as_str)
+    }
+
+    /// Returns the name of the signal on `wire`, or `wire <wire>` if the file
+    /// does not name it
+    pub fn describe(&self, wire: usize) -> String {
+        match self.wire_name(wire) {
+            Some(name) => name.to_string(),
+            None => format!("wire {}", wire),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_names() {
+        // `c <== a * b[0] * b[1]`, where the label of `out` is an alias of `c`
+        let sym = "1,1,0,main.c\n2,2,0,main.a\n3,3,0,main.b[0]\n4,4,0,main.b[1]\n\
+                   5,5,0,main.b_prod\n6,1,1,main.mul.out\n7,-1,1,main.unused\n";
+        let layout = WireLayout::new(1, 3, 0, vec![0, 1, 2, 3, 4, 5]);
+
+        let names = SignalNames::from_reader(sym.as_bytes(), &layout).unwrap();
+        assert_eq!(names.wire_name(1), Some("main.c"));
+        assert_eq!(names.wire_name(3), Some("main.b[0]"));
+        assert_eq!(names.wire_name(0), None);
+        assert_eq!(names.describe(0), "wire 0");
+        assert_eq!(names.describe(5), "main.b_prod");
+
+        assert!(matches!(
+            SignalNames::from_reader("1,1,0,main.c\n2,2\n".as_bytes(), &layout),
+            Err(SymFileError::InvalidLine(2))
+        ));
+    }
+}
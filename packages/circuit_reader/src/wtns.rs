@@ -0,0 +1,332 @@
+//! Reading and writing circom `.wtns` witness files.
+//!
+//! A witness file holds a header section with the field prime and the number
+//! of values, then a section with the values, one little-endian field element
+//! per wire in circom's wire order.
+use crate::circom_reader::{is_modulus, read_field, to_le_bytes};
+use crate::layout::WireLayout;
+use byteorder::{LittleEndian, ReadBytesExt, WriteBytesExt};
+use ff::PrimeField;
+use libspartan::{Assignment, InputsAssignment, VarsAssignment};
+use secq256k1::FieldBytes;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Errors returned when reading a circom `.wtns` file or splitting a witness
+#[derive(Error, Debug)]
+pub enum WitnessFileError {
+    /// the file could not be read or written, or ended early
+    #[error("failed to read the witness file: {0}")]
+    Io(#[from] std::io::Error),
+    /// the file does not start with "wtns"
+    #[error("invalid magic number {0:?}, not a witness file")]
+    InvalidMagic([u8; 4]),
+    /// the format version is not 1 or 2
+    #[error("unsupported witness format version: {0}")]
+    UnsupportedVersion(u32),
+    /// the file does not hold exactly a header and a values section
+    #[error("expected 2 witness sections, found {0}")]
+    SectionCount(u32),
+    /// a section is not the one expected at its position
+    #[error("expected a witness section of type {expected}, found {actual}")]
+    UnexpectedSection { expected: u32, actual: u32 },
+    /// the size of a section does not match its contents
+    #[error("witness section of type {section} has {actual} bytes, expected {expected}")]
+    SectionSize {
+        section: u32,
+        expected: u64,
+        actual: u64,
+    },
+    /// the witness was computed over another field
+    #[error("the witness is over the prime 0x{0}, not the scalar field of secq256k1")]
+    WrongPrime(String),
+    /// a value is not the encoding of a field element
+    #[error("invalid value for wire {0}")]
+    InvalidValue(usize),
+    /// the witness does not have one value per wire of the circuit
+    #[error("the witness has {0} values, which do not fit the circuit")]
+    WrongLength(usize),
+    /// the value of wire 0, the constant 1, is not 1
+    #[error("wire 0 should always be 1")]
+    InvalidConstant,
+    /// the number of values does not fit the size of a section
+    #[error("the witness declares too many values: {0}")]
+    TooManyValues(u32),
+}
+
+type Result<T> = std::result::Result<T, WitnessFileError>;
+
+const HEADER_TYPE: u32 = 1;
+const VALUES_TYPE: u32 = 2;
+
+/// Loads the `.wtns` file at `filename`
+pub fn load_witness_from_bin_file<Fr: PrimeField>(filename: &Path) -> Result<Vec<Fr>> {
+    let reader = File::open(filename)?;
+    load_witness_from_bin(BufReader::new(reader))
+}
+
+/// Reads a `.wtns` file over the field `Fr`
+pub fn load_witness_from_bin<Fr: PrimeField, R: Read>(mut reader: R) -> Result<Vec<Fr>> {
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if magic != *b"wtns" {
+        return Err(WitnessFileError::InvalidMagic(magic));
+    }
+
+    let version = reader.read_u32::<LittleEndian>()?;
+    if version == 0 || version > 2 {
+        return Err(WitnessFileError::UnsupportedVersion(version));
+    }
+
+    let num_sections = reader.read_u32::<LittleEndian>()?;
+    if num_sections != 2 {
+        return Err(WitnessFileError::SectionCount(num_sections));
+    }
+
+    let size = read_section_header(&mut reader, HEADER_TYPE)?;
+    let field_size = reader.read_u32::<LittleEndian>()?;
+    // the prime is checked against the field before being read, to bound it
+    let repr_size = Fr::Repr::default().as_ref().len();
+    if size != 8 + repr_size as u64 || field_size as usize != repr_size {
+        return Err(WitnessFileError::SectionSize {
+            section: HEADER_TYPE,
+            expected: 8 + repr_size as u64,
+            actual: size,
+        });
+    }
+    let mut prime = vec![0u8; repr_size];
+    reader.read_exact(&mut prime)?;
+    if !is_modulus::<Fr>(&prime) {
+        let prime = prime.iter().rev().map(|byte| format!("{:02x}", byte));
+        return Err(WitnessFileError::WrongPrime(prime.collect()));
+    }
+    let num_values = reader.read_u32::<LittleEndian>()?;
+
+    let size = read_section_header(&mut reader, VALUES_TYPE)?;
+    let expected = (num_values as u64)
+        .checked_mul(repr_size as u64)
+        .ok_or(WitnessFileError::TooManyValues(num_values))?;
+    if size != expected {
+        return Err(WitnessFileError::SectionSize {
+            section: VALUES_TYPE,
+            expected,
+            actual: size,
+        });
+    }
+    // the sizes only come from the header, so the values are not allocated
+    // before they are read
+    let mut values = Vec::new();
+    for i in 0..num_values as usize {
+        values.push(read_field(&mut reader)?.ok_or(WitnessFileError::InvalidValue(i))?);
+    }
+    Ok(values)
+}
+
+/// Writes `witness` as a version 2 `.wtns` file, as the witness generators of
+/// circom do
+pub fn write_witness_to_bin<Fr: PrimeField, W: Write>(witness: &[Fr], mut writer: W) -> Result<()> {
+    let repr_size = Fr::Repr::default().as_ref().len();
+
+    writer.write_all(b"wtns")?;
+    writer.write_u32::<LittleEndian>(2)?;
+    writer.write_u32::<LittleEndian>(2)?;
+
+    writer.write_u32::<LittleEndian>(HEADER_TYPE)?;
+    writer.write_u64::<LittleEndian>(8 + repr_size as u64)?;
+    writer.write_u32::<LittleEndian>(repr_size as u32)?;
+    writer.write_all(modulus_le_bytes::<Fr>().as_ref())?;
+    writer.write_u32::<LittleEndian>(witness.len() as u32)?;
+
+    writer.write_u32::<LittleEndian>(VALUES_TYPE)?;
+    writer.write_u64::<LittleEndian>((witness.len() * repr_size) as u64)?;
+    for value in witness {
+        writer.write_all(to_le_bytes(value).as_ref())?;
+    }
+    Ok(writer.flush()?)
+}
+
+/// Writes `witness` to a `.wtns` file at `filename`
+pub fn write_witness_to_bin_file<Fr: PrimeField>(witness: &[Fr], filename: &Path) -> Result<()> {
+    let writer = File::create(filename)?;
+    write_witness_to_bin(witness, BufWriter::new(writer))
+}
+
+/// Splits a witness in circom's wire order into the variables and inputs of
+/// the circuit with `layout`, as `WireLayout::split_witness` does
+pub fn to_spartan_assignments<Fr: PrimeField<Repr = FieldBytes>>(
+    layout: &WireLayout,
+    witness: &[Fr],
+) -> Result<(VarsAssignment, InputsAssignment)> {
+    if witness.len() != layout.num_wires() {
+        return Err(WitnessFileError::WrongLength(witness.len()));
+    }
+    if witness[0] != Fr::one() {
+        return Err(WitnessFileError::InvalidConstant);
+    }
+
+    let encode = |values: Vec<&Fr>| {
+        let bytes = Zeroizing::new(
+            values
+                .iter()
+                .map(|value| to_le_bytes(*value).into())
+                .collect::<Vec<[u8; 32]>>(),
+        );
+        // the values are field elements, so that their encodings are canonical
+        Assignment::new(&bytes).unwrap()
+    };
+    // split by reference, so that the only copies are the wiped encodings
+    let (vars, inputs) = layout.split_witness(&witness.iter().collect::<Vec<_>>());
+    Ok((encode(vars), encode(inputs)))
+}
+
+// reads the type and size of the next section, which must be `section_type`
+fn read_section_header<R: Read>(mut reader: R, section_type: u32) -> Result<u64> {
+    let actual = reader.read_u32::<LittleEndian>()?;
+    if actual != section_type {
+        return Err(WitnessFileError::UnexpectedSection {
+            expected: section_type,
+            actual,
+        });
+    }
+    Ok(reader.read_u64::<LittleEndian>()?)
+}
+
+// the modulus is not a field element, so it is encoded as `-1`, plus 1
+fn modulus_le_bytes<Fr: PrimeField>() -> Fr::Repr {
+    let mut bytes = to_le_bytes(&-Fr::one());
+    for byte in bytes.as_mut() {
+        let (sum, carry) = byte.overflowing_add(1);
+        *byte = sum;
+        if !carry {
+            break;
+        }
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::load_circuit;
+    use ff::Field;
+    use secq256k1::AffinePoint;
+    use std::io::Cursor;
+
+    type Fr = <AffinePoint as group::Group>::Scalar;
+
+    const WITNESS_FILE: &str = "../spartan_wasm/test_circuit/witness.wtns";
+
+    fn load(file: Vec<u8>) -> Result<Vec<Fr>> {
+        load_witness_from_bin(Cursor::new(file))
+    }
+
+    #[test]
+    fn check_round_trip() {
+        // `[1, c, a, b[0], b[1], b[0] * b[1]]` of `c <== a * b[0] * b[1]`
+        let witness = load_witness_from_bin_file::<Fr>(Path::new(WITNESS_FILE)).unwrap();
+        assert_eq!(witness, [1u64, 2, 1, 1, 2, 2].map(Fr::from));
+
+        // the generator's output, byte for byte
+        let mut file = Vec::new();
+        write_witness_to_bin(&witness, &mut file).unwrap();
+        assert_eq!(file, std::fs::read(WITNESS_FILE).unwrap());
+
+        let witness = vec![Fr::one(), -Fr::one(), Fr::from(1u64 << 40)];
+        let mut file = Vec::new();
+        write_witness_to_bin(&witness, &mut file).unwrap();
+        assert_eq!(load(file).unwrap(), witness);
+    }
+
+    #[test]
+    fn check_errors() {
+        let valid = std::fs::read(WITNESS_FILE).unwrap();
+
+        let mut bad_magic = valid.clone();
+        bad_magic[..4].copy_from_slice(b"r1cs");
+        assert!(matches!(
+            load(bad_magic),
+            Err(WitnessFileError::InvalidMagic(_))
+        ));
+
+        let mut version_3 = valid.clone();
+        version_3[4] = 3;
+        assert!(matches!(
+            load(version_3),
+            Err(WitnessFileError::UnsupportedVersion(3))
+        ));
+
+        let mut swapped = valid.clone();
+        swapped[12] = 2;
+        assert!(matches!(
+            load(swapped),
+            Err(WitnessFileError::UnexpectedSection {
+                expected: 1,
+                actual: 2
+            })
+        ));
+
+        // the header section starts at byte 12, the prime at byte 28
+        let mut wrong_prime = valid.clone();
+        wrong_prime[28] ^= 1;
+        assert!(matches!(
+            load(wrong_prime),
+            Err(WitnessFileError::WrongPrime(_))
+        ));
+
+        // the modulus itself is not a field element
+        let mut out_of_range = valid.clone();
+        let values = valid.len() - 6 * 32;
+        out_of_range[values..values + 32].copy_from_slice(&valid[28..60]);
+        assert!(matches!(
+            load(out_of_range),
+            Err(WitnessFileError::InvalidValue(0))
+        ));
+
+        assert!(matches!(
+            load(valid[..valid.len() - 1].to_vec()),
+            Err(WitnessFileError::Io(_))
+        ));
+
+        // a count and a values section size as large as the header allows,
+        // followed by a single value
+        let mut oversized = valid[..valid.len() - 5 * 32].to_vec();
+        oversized[60..64].copy_from_slice(&u32::MAX.to_le_bytes());
+        oversized[68..76].copy_from_slice(&(u32::MAX as u64 * 32).to_le_bytes());
+        assert!(matches!(load(oversized), Err(WitnessFileError::Io(_))));
+    }
+
+    #[test]
+    fn check_assignments() {
+        let circuit =
+            load_circuit(Path::new("../spartan_wasm/test_circuit/test_circuit.r1cs")).unwrap();
+        let witness = load_witness_from_bin_file::<Fr>(Path::new(WITNESS_FILE)).unwrap();
+
+        let (vars, inputs) = to_spartan_assignments(&circuit.layout, &witness).unwrap();
+        assert!(circuit.instance.is_sat(&vars, &inputs).unwrap());
+
+        let mut wrong_output = witness.clone();
+        wrong_output[1] = Fr::from(3u64);
+        let (vars, inputs) = to_spartan_assignments(&circuit.layout, &wrong_output).unwrap();
+        assert!(!circuit.instance.is_sat(&vars, &inputs).unwrap());
+
+        let mut no_constant = witness.clone();
+        no_constant[0] = Fr::zero();
+        assert!(matches!(
+            to_spartan_assignments(&circuit.layout, &no_constant),
+            Err(WitnessFileError::InvalidConstant)
+        ));
+        // the instance is padded to 2 variables, but the circuit has 6 wires
+        assert!(matches!(
+            to_spartan_assignments(&circuit.layout, &witness[..5]),
+            Err(WitnessFileError::WrongLength(5))
+        ));
+        let extra_wire = [witness.as_slice(), &[Fr::zero()]].concat();
+        assert!(matches!(
+            to_spartan_assignments(&circuit.layout, &extra_wire),
+            Err(WitnessFileError::WrongLength(7))
+        ));
+    }
+}
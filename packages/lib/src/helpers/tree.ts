@@ -1,22 +1,23 @@
-import { IncrementalMerkleTree } from "@zk-kit/incremental-merkle-tree";
+import { wasm } from "@src/wasm";
 import { Poseidon } from "./poseidon";
 import { MerkleProof } from "../types";
+import { bigIntToLeBytes, bytesLeToBigInt } from "./utils";
 
 export class Tree {
   depth: number;
   poseidon: Poseidon;
-  private treeInner!: IncrementalMerkleTree;
+  private treeInner!: wasm.PoseidonTree;
 
+  // The tree is hashed in wasm, which `poseidon.initWasm()` loads
   constructor(depth: number, poseidon: Poseidon) {
     this.depth = depth;
 
     this.poseidon = poseidon;
-    const hash = poseidon.hash.bind(poseidon);
-    this.treeInner = new IncrementalMerkleTree(hash, this.depth, BigInt(0));
+    this.treeInner = new wasm.PoseidonTree(this.depth);
   }
 
   insert(leaf: bigint) {
-    this.treeInner.insert(leaf);
+    this.treeInner.insert(bigIntToLeBytes(leaf, 32));
   }
 
   delete(index: number) {
@@ -24,27 +25,55 @@ export class Tree {
   }
 
   leaves(): bigint[] {
-    return this.treeInner.leaves;
+    const bytes = this.treeInner.leaves();
+    const leaves: bigint[] = [];
+    for (let i = 0; i < bytes.length; i += 32) {
+      leaves.push(bytesLeToBigInt(bytes.slice(i, i + 32)));
+    }
+    return leaves;
   }
 
   root(): bigint {
-    return this.treeInner.root;
+    return bytesLeToBigInt(this.treeInner.root());
   }
 
   indexOf(leaf: bigint): number {
-    return this.treeInner.indexOf(leaf);
+    const index = this.treeInner.index_of(bigIntToLeBytes(leaf, 32));
+    return index === undefined ? -1 : index;
   }
 
+  // Proofs are encoded by the wasm tree as
+  // `root || leaf || siblings || path indices`, with one byte per path index
   createProof(index: number): MerkleProof {
-    const proof = this.treeInner.createProof(index);
+    const bytes = this.treeInner.create_proof(index);
+    const siblingsEnd = 64 + 32 * this.depth;
+
+    const siblings: [bigint][] = [];
+    for (let i = 64; i < siblingsEnd; i += 32) {
+      siblings.push([bytesLeToBigInt(bytes.slice(i, i + 32))]);
+    }
     return {
-      siblings: proof.siblings,
-      pathIndices: proof.pathIndices,
-      root: proof.root
+      siblings,
+      pathIndices: Array.from(bytes.slice(siblingsEnd)),
+      root: bytesLeToBigInt(bytes.slice(0, 32))
     };
   }
 
   verifyProof(proof: MerkleProof, leaf: bigint): boolean {
-    return this.treeInner.verifyProof({ ...proof, leaf });
+    if (
+      proof.siblings.length !== this.depth ||
+      proof.pathIndices.length !== this.depth
+    ) {
+      return false;
+    }
+
+    const bytes = new Uint8Array(64 + 33 * this.depth);
+    bytes.set(bigIntToLeBytes(proof.root, 32), 0);
+    bytes.set(bigIntToLeBytes(leaf, 32), 32);
+    proof.siblings.forEach(([sibling], i) => {
+      bytes.set(bigIntToLeBytes(sibling, 32), 64 + 32 * i);
+    });
+    bytes.set(proof.pathIndices, 64 + 32 * this.depth);
+    return this.treeInner.verify_proof(bytes);
   }
 }
@@ -23,6 +23,67 @@ export function verify(circuit: Uint8Array, proof: Uint8Array, public_input: Uin
 */
 export function poseidon(input_bytes: Uint8Array): Uint8Array;
 
+/**
+* Poseidon Merkle tree of the membership circuits, in place of zk-kit's
+* `IncrementalMerkleTree`. Leaves and roots are 32-byte field elements,
+* encoded as for `poseidon`.
+*/
+export class PoseidonTree {
+  free(): void;
+/**
+* @param {number} depth
+*/
+  constructor(depth: number);
+/**
+* Builds a tree from the concatenation of its leaves
+* @param {number} depth
+* @param {Uint8Array} leaves
+* @returns {PoseidonTree}
+*/
+  static from_leaves(depth: number, leaves: Uint8Array): PoseidonTree;
+/**
+* @param {Uint8Array} leaf
+* @returns {number}
+*/
+  insert(leaf: Uint8Array): number;
+/**
+* @param {number} index
+* @param {Uint8Array} leaf
+*/
+  update(index: number, leaf: Uint8Array): void;
+/**
+* @param {number} index
+*/
+  delete(index: number): void;
+/**
+* @returns {Uint8Array}
+*/
+  root(): Uint8Array;
+/**
+* Returns the concatenation of the leaves, deleted ones being zero
+* @returns {Uint8Array}
+*/
+  leaves(): Uint8Array;
+/**
+* @param {Uint8Array} leaf
+* @returns {number | undefined}
+*/
+  index_of(leaf: Uint8Array): number | undefined;
+/**
+* Returns `root || leaf || siblings || path indices`, with one byte per
+* path index
+* @param {number} index
+* @returns {Uint8Array}
+*/
+  create_proof(index: number): Uint8Array;
+/**
+* Checks a proof encoded by `create_proof` against the current root
+* @param {Uint8Array} proof
+* @returns {boolean}
+*/
+  verify_proof(proof: Uint8Array): boolean;
+}
+
 export type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
 
 export interface InitOutput {
@@ -31,6 +92,17 @@ export interface InitOutput {
   readonly verify: (a: number, b: number, c: number, d: number, e: number, f: number, g: number) => void;
   readonly poseidon: (a: number, b: number, c: number) => void;
   readonly init_panic_hook: () => void;
+  readonly __wbg_poseidontree_free: (a: number) => void;
+  readonly poseidontree_new: (a: number, b: number) => void;
+  readonly poseidontree_from_leaves: (a: number, b: number, c: number, d: number) => void;
+  readonly poseidontree_insert: (a: number, b: number, c: number, d: number) => void;
+  readonly poseidontree_update: (a: number, b: number, c: number, d: number, e: number) => void;
+  readonly poseidontree_delete: (a: number, b: number, c: number) => void;
+  readonly poseidontree_root: (a: number, b: number) => void;
+  readonly poseidontree_leaves: (a: number, b: number) => void;
+  readonly poseidontree_index_of: (a: number, b: number, c: number, d: number) => void;
+  readonly poseidontree_create_proof: (a: number, b: number, c: number) => void;
+  readonly poseidontree_verify_proof: (a: number, b: number, c: number, d: number) => void;
   readonly __wbindgen_add_to_stack_pointer: (a: number) => number;
   readonly __wbindgen_malloc: (a: number) => number;
   readonly __wbindgen_free: (a: number, b: number) => void;
@@ -1 +1 @@
-sage ./sage/generate_params_poseidon.sage 1 0 256 3 5 128 0xfffffffffffffffffffffffffffffffffffffffffffffffffffffffefffffc2f
\ No newline at end of file
+cargo run --release --bin gen_poseidon_consts -- ./src ../circuits/poseidon
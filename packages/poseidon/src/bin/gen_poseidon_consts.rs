@@ -0,0 +1,50 @@
+use poseidon::params::{
+    generate, generate_poseidon2, to_circom, to_rust_module, to_rust_poseidon2_table, to_rust_table,
+};
+use poseidon::poseidon2_k256::WIDTHS;
+use poseidon::poseidon_k256::{FieldElement, MAX_WIDTH, MIN_WIDTH};
+use poseidon::PoseidonConstants;
+use std::env::{args, current_dir};
+use std::fs;
+
+const ALPHA: u64 = 5;
+const SECURITY_LEVEL: usize = 128;
+
+fn main() {
+    let rust_dir = args().nth(1).unwrap();
+    let circom_dir = args().nth(2).unwrap();
+
+    let root = current_dir().unwrap();
+    let constants: Vec<PoseidonConstants<FieldElement>> = (MIN_WIDTH..=MAX_WIDTH)
+        .map(|width| generate(width, ALPHA, SECURITY_LEVEL))
+        .collect();
+    // the width of the `Poseidon` circuit and `poseidon_k256::hash`
+    let width_3 = &constants[3 - MIN_WIDTH];
+    let poseidon2_constants: Vec<_> = WIDTHS
+        .iter()
+        .map(|width| generate_poseidon2::<FieldElement>(*width, ALPHA, SECURITY_LEVEL))
+        .collect();
+
+    let files = [
+        (
+            root.join(&rust_dir).join("k256_consts.rs"),
+            to_rust_module(width_3, "secq256k1::field::field_secp::FieldElement"),
+        ),
+        (
+            root.join(&rust_dir).join("k256_sponge_consts.rs"),
+            to_rust_table(&constants),
+        ),
+        (
+            root.join(&rust_dir).join("k256_poseidon2_consts.rs"),
+            to_rust_poseidon2_table(&poseidon2_constants),
+        ),
+        (
+            root.join(&circom_dir).join("poseidon_constants.circom"),
+            to_circom(width_3),
+        ),
+    ];
+    for (path, contents) in files {
+        fs::write(&path, contents).unwrap();
+        println!("Written Poseidon constants to {}", path.display());
+    }
+}
@@ -0,0 +1,255 @@
+//! The scalar field of BN254, only to run the generic Poseidon2 code on the
+//! test vectors published for that field. Elements are kept as reduced
+//! little-endian limbs and multiplied by doubling and adding, which is slow
+//! but enough for a few permutations.
+use ff::{Field, PrimeField};
+use rand_core::RngCore;
+use std::ops::{Add, AddAssign, Mul, MulAssign, Neg, Sub, SubAssign};
+use subtle::{Choice, ConditionallySelectable, ConstantTimeEq, CtOption};
+
+const MODULUS: [u64; 4] = [
+    0x43e1f593f0000001,
+    0x2833e84879b97091,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+];
+
+// `p - 1 = 2^S * T` with `T` odd
+const S: u32 = 28;
+const T: [u64; 4] = [
+    0x9b9709143e1f593f,
+    0x181585d2833e8487,
+    0x131a029b85045b68,
+    0x000000030644e72e,
+];
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fr([u64; 4]);
+
+// `a + b`, and whether it overflows
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut sum = [0u64; 4];
+    let mut carry = false;
+    for i in 0..4 {
+        let (s, c1) = a[i].overflowing_add(b[i]);
+        let (s, c2) = s.overflowing_add(carry as u64);
+        sum[i] = s;
+        carry = c1 || c2;
+    }
+    (sum, carry)
+}
+
+// `a - b`, and whether it underflows
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], bool) {
+    let mut difference = [0u64; 4];
+    let mut borrow = false;
+    for i in 0..4 {
+        let (d, b1) = a[i].overflowing_sub(b[i]);
+        let (d, b2) = d.overflowing_sub(borrow as u64);
+        difference[i] = d;
+        borrow = b1 || b2;
+    }
+    (difference, borrow)
+}
+
+impl Fr {
+    // the modulus is below `2^254`, so that sums of reduced limbs never overflow
+    fn reduce(limbs: [u64; 4]) -> Self {
+        match sub_limbs(&limbs, &MODULUS) {
+            (reduced, false) => Fr(reduced),
+            _ => Fr(limbs),
+        }
+    }
+}
+
+impl From<u64> for Fr {
+    fn from(x: u64) -> Self {
+        Fr([x, 0, 0, 0])
+    }
+}
+
+impl Add<&Fr> for Fr {
+    type Output = Fr;
+
+    fn add(self, rhs: &Fr) -> Fr {
+        Fr::reduce(add_limbs(&self.0, &rhs.0).0)
+    }
+}
+
+impl Sub<&Fr> for Fr {
+    type Output = Fr;
+
+    fn sub(self, rhs: &Fr) -> Fr {
+        match sub_limbs(&self.0, &rhs.0) {
+            (difference, true) => Fr(add_limbs(&difference, &MODULUS).0),
+            (difference, false) => Fr(difference),
+        }
+    }
+}
+
+impl Mul<&Fr> for Fr {
+    type Output = Fr;
+
+    fn mul(self, rhs: &Fr) -> Fr {
+        let mut product = Fr::zero();
+        for i in (0..256).rev() {
+            product = product.double();
+            if (rhs.0[i / 64] >> (i % 64)) & 1 == 1 {
+                product += self;
+            }
+        }
+        product
+    }
+}
+
+impl Neg for Fr {
+    type Output = Fr;
+
+    fn neg(self) -> Fr {
+        Fr::zero() - self
+    }
+}
+
+macro_rules! impl_binop {
+    ($trait:ident, $method:ident, $assign_trait:ident, $assign_method:ident) => {
+        impl $trait for Fr {
+            type Output = Fr;
+
+            fn $method(self, rhs: Fr) -> Fr {
+                $trait::$method(self, &rhs)
+            }
+        }
+
+        impl $assign_trait for Fr {
+            fn $assign_method(&mut self, rhs: Fr) {
+                *self = $trait::$method(*self, &rhs);
+            }
+        }
+
+        impl $assign_trait<&Fr> for Fr {
+            fn $assign_method(&mut self, rhs: &Fr) {
+                *self = $trait::$method(*self, rhs);
+            }
+        }
+    };
+}
+
+impl_binop!(Add, add, AddAssign, add_assign);
+impl_binop!(Sub, sub, SubAssign, sub_assign);
+impl_binop!(Mul, mul, MulAssign, mul_assign);
+
+impl ConditionallySelectable for Fr {
+    fn conditional_select(a: &Self, b: &Self, choice: Choice) -> Self {
+        Fr([0, 1, 2, 3].map(|i| u64::conditional_select(&a.0[i], &b.0[i], choice)))
+    }
+}
+
+impl ConstantTimeEq for Fr {
+    fn ct_eq(&self, other: &Self) -> Choice {
+        self.0.ct_eq(&other.0)
+    }
+}
+
+impl Field for Fr {
+    fn random(mut rng: impl RngCore) -> Self {
+        let mut repr = [0u8; 32];
+        loop {
+            rng.fill_bytes(&mut repr);
+            repr[31] &= 0x3f;
+            if let Some(x) = Option::from(Fr::from_repr(repr)) {
+                return x;
+            }
+        }
+    }
+
+    fn zero() -> Self {
+        Fr([0; 4])
+    }
+
+    fn one() -> Self {
+        Fr([1, 0, 0, 0])
+    }
+
+    fn square(&self) -> Self {
+        *self * self
+    }
+
+    fn double(&self) -> Self {
+        *self + self
+    }
+
+    fn invert(&self) -> CtOption<Self> {
+        let p_minus_2 = sub_limbs(&MODULUS, &[2, 0, 0, 0]).0;
+        CtOption::new(self.pow_vartime(p_minus_2), !self.is_zero())
+    }
+
+    // Tonelli-Shanks
+    fn sqrt(&self) -> CtOption<Self> {
+        let t_minus_1_over_2 = [
+            (T[0] >> 1) | (T[1] << 63),
+            (T[1] >> 1) | (T[2] << 63),
+            (T[2] >> 1) | (T[3] << 63),
+            T[3] >> 1,
+        ];
+        let w = self.pow_vartime(t_minus_1_over_2);
+        let mut x = *self * w;
+        let mut b = x * w;
+        let mut z = Fr::root_of_unity();
+        let mut v = S;
+        while b != Fr::one() {
+            let mut k = 0;
+            let mut b_pow = b;
+            while b_pow != Fr::one() {
+                b_pow = b_pow.square();
+                k += 1;
+            }
+            if k == v {
+                return CtOption::new(Fr::zero(), Choice::from(0));
+            }
+            let mut w = z;
+            for _ in k + 1..v {
+                w = w.square();
+            }
+            z = w.square();
+            b *= z;
+            x *= w;
+            v = k;
+        }
+        CtOption::new(x, Choice::from(1))
+    }
+}
+
+impl PrimeField for Fr {
+    type Repr = [u8; 32];
+
+    fn from_repr(repr: [u8; 32]) -> CtOption<Self> {
+        let limbs =
+            [0, 1, 2, 3].map(|i| u64::from_le_bytes(repr[8 * i..8 * i + 8].try_into().unwrap()));
+        let is_reduced = sub_limbs(&limbs, &MODULUS).1;
+        CtOption::new(Fr(limbs), Choice::from(is_reduced as u8))
+    }
+
+    fn to_repr(&self) -> [u8; 32] {
+        let mut repr = [0u8; 32];
+        for (bytes, limb) in repr.chunks_exact_mut(8).zip(self.0) {
+            bytes.copy_from_slice(&limb.to_le_bytes());
+        }
+        repr
+    }
+
+    fn is_odd(&self) -> Choice {
+        Choice::from((self.0[0] & 1) as u8)
+    }
+
+    const NUM_BITS: u32 = 254;
+    const CAPACITY: u32 = 253;
+    const S: u32 = S;
+
+    fn multiplicative_generator() -> Self {
+        Fr::from(5)
+    }
+
+    fn root_of_unity() -> Self {
+        Fr::multiplicative_generator().pow_vartime(T)
+    }
+}
@@ -0,0 +1,899 @@
+//! Round constants and internal matrix diagonals of the Poseidon2 permutations of width
+//! 2, 3, 4, 8, 12 and 16, generated by `gen_poseidon_consts`. Entry `i` of each table is for
+//! width `poseidon2_k256::WIDTHS[i]`.
+
+pub(crate) const EXTERNAL_ROUND_CONSTANTS: [&[&[&str]]; 6] = [
+    // t = 2
+    &[
+        &[
+            "32677819000466876332022634540832042135307528954666730661567805254602133113557",
+            "10406578814985098385531091876190378066103532684835653600714566408200124223743",
+        ],
+        &[
+            "39039322530790802828973957702620875595092180320105256188300623516137993339833",
+            "28587649088044840805547558630361251257433059631920611528655091417295140342256",
+        ],
+        &[
+            "66461309108900487575819957277185002803475839901864856140274930773123002665072",
+            "41451843488764278144622183064656504754897745314688836853321798133739756088605",
+        ],
+        &[
+            "36169781760580081984527698394550186575580531060117080170382745558772621108310",
+            "11318882118569156297568377797694530363749241798300543735399899796847682354496",
+        ],
+        &[
+            "98015379518161501435931283659346605094750643523679216352626750728022530313124",
+            "74487867693801227534965405055451053036896873892907183925287019630759328719454",
+        ],
+        &[
+            "69046026971779400267312410247839795130737084233793321454778149325901120425116",
+            "40971464162080916165358095627824178914066393056997622518540069800230181063530",
+        ],
+        &[
+            "94558860076286947879327245788461243540632608495165254843064034832888590552196",
+            "14811711514757053865584479301926754026811579745030149747679610248017487401510",
+        ],
+        &[
+            "90978158629617245721193247993348799918992881331236187290258475012964333481843",
+            "15172653645315192240136077166668318679083249666228161447223010732142738785019",
+        ],
+    ],
+    // t = 3
+    &[
+        &[
+            "15180568604901803243989155929934437997245952775071395385994322939386074967328",
+            "98155933184944822056372510812105826951789406432246960633912199752807271851218",
+            "32585497418154084368870158853355239726261349829448673320273043226636389078017",
+        ],
+        &[
+            "66713968576806622579829258440960693099797917756640662361943757758980796487698",
+            "61296025743283504825054745787375839406507895949474930140819919915792438454216",
+            "64548089412749542282115556935384382035671782881737715696939837764375912217104",
+        ],
+        &[
+            "108421562972909537718478936575770973463273651828765393113349044862621092658552",
+            "93957623861448681916560847065407918286434708744548934125771289238599801659600",
+            "31886767595881910145119755249133120645312710313371225820300496900248094187131",
+        ],
+        &[
+            "36511615103248888903406040506250394762206798360602726106046630438239169384653",
+            "21193239787133737740669439860809806837993750509086389566475677877580362491125",
+            "15159189447883181997488877417695825734356570617827322308691834229181804753656",
+        ],
+        &[
+            "16959835233095757670013367728627149851239789174357906293937455553277911805495",
+            "15116421110200928832147360650392633091242147433006813656250997138988179879750",
+            "107878787525302837370688492081178689950008165750500003692400517211520334656293",
+        ],
+        &[
+            "44210105558575948369921579518078229089923760124167628288943900602376706136436",
+            "90305995748749060889452130219544332384396626628663475498252761213618628372367",
+            "104941997925797907872686462815914481945432760720471803254797908465921520138024",
+        ],
+        &[
+            "100036855232527386145662094141100441220151775745916101660987264242446845728894",
+            "103285582836474146806606752170525767341430483568396209591447274936228630298052",
+            "82197692939371228160449741709034077803239992888716859217989995857278406253737",
+        ],
+        &[
+            "10040764964044995095453717286623030376397745892179877153575434454090155545240",
+            "27304226040425863042893623786832369758179176309230053449707879364285977952630",
+            "42627232144930751842910170221862679057276668485045156742021958050665662768084",
+        ],
+    ],
+    // t = 4
+    &[
+        &[
+            "25620113361824764432764248623618926336138819866011303644033075650279528528481",
+            "26592814964782198106946695176665239659256485463739615495933645272134768648369",
+            "13452230154314950031889857475187628653268280284448307461227780164282558079425",
+            "44341864127786504566009332108112021453575984963397198903434483527066446921961",
+        ],
+        &[
+            "54582822227591426995881696327293113192869267367169845254153294059556718838928",
+            "13268384460536678264232199807308829866858289313876420027266961067762414080496",
+            "46889347739412323906951867547183894569478741685268359322653895413391431912386",
+            "1751910260781624294471650764550024184670279039409442224369609579409832857800",
+        ],
+        &[
+            "59220806224003705354668779480068097763829693575632891264769417780660551342716",
+            "13033148305259746557704398371874699774181197308086573149897844010033396859898",
+            "37074074941939457037672331677900575949220089088882699187653324630309289382868",
+            "26049038240826393452709357124862350270973700887634859806226164540182685668104",
+        ],
+        &[
+            "75100760751686725388141111768971093670932349255156064690710154757133945828796",
+            "114492516214779763330631248468166558142157618631562938259227613652661291617958",
+            "77256539306519077571901844883549412711682708305631947012651429100516576782085",
+            "78924361506678725469553842885174674813984256480388229813700199278209589056018",
+        ],
+        &[
+            "92207944752467301677541009923535744656519576612918991023923422774992737825957",
+            "41507731132442277454973224612449704102997239898477961579447292224391982555603",
+            "40748714521675201177661645328690926157991617250370031782117164234520458443134",
+            "23533379910338363600136405354478264979487575746563460944777573025978040259263",
+        ],
+        &[
+            "68027627007347897826692885867137127750867543498973190321108476273414417139144",
+            "99766510751546402155286959528561828257557020216091130661181526886953986215621",
+            "3490351881958509763981467656300653739010520040262762535404808637968673867246",
+            "96573476396543206593265544183940391496275989000210439539882960149710550824416",
+        ],
+        &[
+            "20183107795746455194371507830117391885877372505062237133047403670494875944722",
+            "99750579212020933305930278273891149067144661347996302925769352263362736985124",
+            "102225305383618210471113335055321054835466465757822689652396866441082059376462",
+            "57263636393434470268834321420305030046705499789820758610084350490372465262824",
+        ],
+        &[
+            "49799218007529476722721813424853412678433292674030377690385556801315106352995",
+            "88984047446309889891046090903619523516587819876117080243537160820663688436011",
+            "9721788489585597096126914498129250795434068428800119316861846420998893882279",
+            "43225862411270595933931085239344218193997413622713896697964003166140317395227",
+        ],
+    ],
+    // t = 8
+    &[
+        &[
+            "6323300860635514819803818646582908963468461560498304230611248761881715378210",
+            "31246920707714912388345736319350696273981664032006722100257270645359185053723",
+            "2588861792496101861145710038210920444213708398738314005409229665814852972870",
+            "61943492197753596457176725858923733329171743551814494869876356328734215619508",
+            "42887478101939468026907716051634835194525291792067851343506259442736714288953",
+            "89066600138566000907103782562763173315431931931722205255470585209516145400651",
+            "7577336723865441349707403463329689025541371558272420437103319316751110332475",
+            "52718847856873611141512012007863117086634268396047890564922961775562108559050",
+        ],
+        &[
+            "75117315325014174603372654696860438802334254335283814740897665319343491905192",
+            "109262921730206344994655793194726848561078317816930661562770989172063859977340",
+            "10336466440530510766521712790691560983536716428980063871895966644754779069757",
+            "27283332521761646520842326044478989022157316803242702576563450864610581650651",
+            "51449431985172950133769550517999799420171286318472270607980668132545586328792",
+            "20167609720524240931525465042608788887262197322273350322922501009685163433687",
+            "68687432780020136288826766471786040264608148927249090110614155489198654492279",
+            "65090140952649559167216352049163300735451451086287995937843356980588535192024",
+        ],
+        &[
+            "22399369171446918954741861081531737379113961304584487989905456572166000415246",
+            "94750679013839336613060416024227079711305548748207995304123060859115713033268",
+            "3010180720753007836339597773526852502873124558864731653628769117365720548536",
+            "91127294661384005846645643562743420048775772260774457540688234410886795373155",
+            "46884056789875806155258662954262709150077071634723426631771450274288320785441",
+            "69522206924121243740753672836092168327368279076421575058715433601269545489815",
+            "15352464932793224382520206903487067236337549601158613526015995862302843380335",
+            "32930580332610305425916924538345602463384513136097978598277522511369335952282",
+        ],
+        &[
+            "32653925143138033445545569191413105948698002510147856138915014426884788454684",
+            "66895073562177829642017010936702911551250764212153962236236828918516524577755",
+            "97538434482964504528026781002402822980307638669170490466838408691393050399505",
+            "9212179044915655903618438874649875419582776731677664287100305646347522021294",
+            "29815120885047576905061967868559236455440226858632891813626264404809132782595",
+            "69708983694409945938754787272794991882807863607793054710469148103023725556006",
+            "71502876993786515762717867155550776850873115140920991986262156760706722431560",
+            "63593111861450375207791008749888686751992084442782334698408071912445985724303",
+        ],
+        &[
+            "89930151170518293781002961324173172958850196385169721882559959478930655414162",
+            "107506715407051564403836641113982858907853141834073092397846300665330005371023",
+            "112277737523465377634600473447094396007281996106644977890031109862361811625648",
+            "74310523288500323347920068655698952982786021176923140743039623547130551499817",
+            "20780197280837192787633478549390077748654221832391988397844409628354734837936",
+            "39291007646677406349448961826229937728351601741451479257177626647255380274926",
+            "108360881957168527234744054469549980918322522775658773417706555805371875690791",
+            "61868315746136297749084633004601763451460849791437179467154106057339065535519",
+        ],
+        &[
+            "103960811612582219508140964118198743017189033346182377632079832361767156057787",
+            "51157747651388794230739116981545631982138496809999293681497498717829689860107",
+            "63935701427197375668752330641873665596798737402118206182820163571256222829340",
+            "115762892254649946863969537999244284946185766426358660270058013235263481043656",
+            "1562794901269840551098534214271198258003465286973188838328891133663591698578",
+            "43286554059739314551722573514617304406922541783574516197216249503112333638141",
+            "80411494673390450487559702610784822367339544459238209306209405441690186248251",
+            "41517032879648529379043177280901498846552782318762730993873544765607687976800",
+        ],
+        &[
+            "74046669642234640083586250567927244493072281010697627051338746612527786281639",
+            "79600500788982066701057915480253265644217833980785674727194508319606116077987",
+            "41727843156957045194990528369648141839315811212917919511100358558985377131303",
+            "60350376924892391065365108105023924801016344522703338955488798231634525033843",
+            "17860854961645425409959323281877349129878039842005241904433299362356870810569",
+            "94572908261700486079678343025022772452065379487929276241510645464331436052482",
+            "113551686626620528531633414991178122988465123300550605889044149311425323591585",
+            "65775290743419460822050799484359991235561086893114975272516147497898595321063",
+        ],
+        &[
+            "13441477305324182431502037865763210450522841805487834765738743408263801248420",
+            "6196617126786975491280286421603742820424488521162007583144669920739093178105",
+            "31735565573393293340356915406667707204681318192561426936584061695190179833126",
+            "61194764710244741399792399186378404474044689328169357027341845196747432199867",
+            "26865673954714476916598420044426866340965909517184367444353711265100474552003",
+            "37452798415031476988979278225963491281521521010917005293017279209838921854564",
+            "15490193793157858993285922471993374733080707143702061102010429175100930740573",
+            "64435138560119138674362105246141709941337708649637211880982795555296407066332",
+        ],
+    ],
+    // t = 12
+    &[
+        &[
+            "48591972787766725286100025966815070283671405712953449277785039093479199152132",
+            "7875596267152740957802343701800603833582955209862684531081421467884017490757",
+            "12687599593053596458196044172874493589428542013058478859825209666678678964070",
+            "40721782303748252319704465243391332610641386247934232712771201843817312350679",
+            "13802686915396681491057314722172736766133472808612970059940809096984641706581",
+            "91240702766343624697720773398203645126433873629143728910857636417521065142597",
+            "42877840629445237889338728346881809083079077280889664095540670566725679387694",
+            "20610835149814726354442545642079710726614964856038121768829949068436722890516",
+            "114647323862480661394794911496588991186616422463930509482329987107693918477524",
+            "87405400628483633568224958924614240595599444300806189948819424369144097206727",
+            "30291387470291422906598282573500428699334068642069093810902575954788879850022",
+            "24567363143516341899830121228166617889932497313304142838667856078983370423788",
+        ],
+        &[
+            "32646975765377301076777578215045692349828935647994803985911242101397499638435",
+            "77920137490928928163025336128834399373808868284177448146961433213716653888094",
+            "18205901335372590702047537910460198441448581098137645338115082366204740758675",
+            "13330875070217727681683049156360313302208120459180202911029025434731149183058",
+            "26934466263060281568222147808777225580384427202387705889529448343204920519515",
+            "1885084885091214972714391435175028753661584331765122083437564421092389618429",
+            "64076582126280117258265868765802274949082457352197993800457892527449088833945",
+            "7692351419111476607733339194731900762884365815328980835674610027941544618098",
+            "11938326292863905534139351963594011886000920990335257308645435694013912555579",
+            "9702332179934561461205298849669307928135822612801753793978724518147437228172",
+            "40644205184801244840121150653002533191573363814852995038428369138377032946560",
+            "103945700011653745831124646938306951965166307803434554770937986841495042358671",
+        ],
+        &[
+            "66266050108923414283001069707323508283880382973875673555638575908950759039798",
+            "55751570359648876161283405208621431560813578880786834595883289759674260299352",
+            "113008965257588215860762802944905640800173518491634584185163728450768971389815",
+            "96629454496863753904185123854050010821572277694540966774178375933737684228213",
+            "31847106982156106950012036708954128954065644450729098360408796832126123623767",
+            "70089741553856219710596940791978568457734275790639053141243874125277346415957",
+            "62449288860691304563209765501625451878370663392659634890075436941811630641165",
+            "97529224482894394731515799356593533174914328756822810496892178380542163736381",
+            "60844453614817091994642691517306256235668634774638020859764908861535073797835",
+            "113378872124977494626788469775820845195503703409414186305987976177915956706143",
+            "28350564336641910563753764616860200036439312868750472634282711577012948511509",
+            "60874642620550817302697666896032098512798288803476410235916790475643257838470",
+        ],
+        &[
+            "56913565025938036791094494033778288818139910365057389193070319990767899451598",
+            "90715720554370826179332228796237783005951205201180875373650436021133038704767",
+            "53654307962892364772247026307191205127438018241426917426127112825805222443840",
+            "73447346917118599899950979187164364457200691857515727186174038720539711220196",
+            "69742293783582900427388528535154586897388447203565396207092665683821949591945",
+            "103348196905094335757756699732755129284368154099981704369964787418005377848275",
+            "70989233825525522395663645248981890015436132639459471385356674789848293054416",
+            "58011568182603562588730840680969721001986768879155242845219689112150331859771",
+            "27119450614088278490604069315129016549633640016307351674737963880650929141334",
+            "37562704668734361539794536188749332460916338821448557746053454857190171180645",
+            "49953094556874997025383556960124950927462006865786007821870995869180427625930",
+            "10726023459081965910096355347769414583683943974378671362760225323523678892021",
+        ],
+        &[
+            "72191955123957303172019750475278740161077229620487436655443604578043150613411",
+            "38560969247260888646365628191995015201996169736549350660385606145403825102678",
+            "93139607630744595848880261260626639852829059261543507565802433950002295962023",
+            "23055120422718176601065328898232769659332967802581170336062135073724472577572",
+            "10977027374647709268520770411397619205791205216025888864306195133544588149949",
+            "76968228027613244638076597680626287840882225928158297064018646445489412247782",
+            "92789169489647543536809796417928424098890466047994324653121416032391345649396",
+            "77729493860085856695280256433345583163413624560359889959392611712299052425574",
+            "23405036891273506371324456440262345894723772539939831925057549779661544748610",
+            "43244793620377785197899957610269869172039973799125320354530345155962816207950",
+            "60594886235463053668625413679627837310508994100034713320032996558252128000560",
+            "26731460913900766251424678468986035486053919213593687624828589918258263804260",
+        ],
+        &[
+            "5638434933972677475882990396959689147849310047187094196491401275136307232076",
+            "28023453036855027654877744782125631296138274719424919775993676790109181096734",
+            "60288776655656611385537277391474435793783993229121071327661383130165514226390",
+            "64674277254284726506273464603790777884937875750522833415965715342558515420642",
+            "6862632761409826516299562332127267801519924556430322227962556146064879955864",
+            "40001383119050762093988027406137912991775522288616856313973141299336420044417",
+            "31308111555203217936978048877120806094243402107422061383515908012587589945547",
+            "79427656482482427915795586551485278063250634703949942975499177625381129564312",
+            "41109926102742003641746637719348785643913837627356393911829813205348527748972",
+            "38782684871478827763768686752393944523136275285319029363990003044496237804325",
+            "23453724321948413234926320255193901791427938156211396524005605874938937930328",
+            "33582760377817518961552021334943210028232108209247750558309662766683662668988",
+        ],
+        &[
+            "86774610290018224452854430083104182128724036583695711464256803211134296963474",
+            "74153702699136152481342337005668292260549477504948068230176278453184608814247",
+            "109491704916768479115827679373531934346239573349946624019139401202203295116218",
+            "2456659118036918326919247567210152251694784600453459647724721873199559702258",
+            "109352858837024290411370604445507397651376838578070706418802294863893578806153",
+            "103152776692233504428939774276455443410199640516158967562794852598555280664436",
+            "83096626772071832268658216609165458923891958338561829569718807394411052674958",
+            "41262657244853256372005712265461950742823719447917077098086089325066625506779",
+            "115693689186435946137898869168964233405935677964042174325827166653611699965723",
+            "54424461456635387442974934302901970170794770598033050947264916754000032910388",
+            "36322205776716365736282906110612806932980830034218755173122925447164121037785",
+            "101304093798822747382651469670851807373630257847988381492171620199231148310538",
+        ],
+        &[
+            "49044592958869806535655910141157074705608278398582191807785096825547707999525",
+            "75041596661121680485043096740216868836353320732304646251848572928822188147849",
+            "62971099534205535476620426988186473135693472969762961554129180293779387411165",
+            "50926755167294233658379514368667364616503729309328628882731118306382387440359",
+            "21892570584894195164919872856657562056793885903823217031135076007582384536449",
+            "108322863682386238403756214012050472212371200410410252468725703091327315020538",
+            "26274263396272300000835296530672430846726778248482907948283135925597413616352",
+            "28560993965494795525755373229432547731829245944753796757962240452063561848204",
+            "31258322001621503360796356409809358074785431505392583664034292728173233147750",
+            "16361342858556613951692531856382537883363159362740793313297058308783229727808",
+            "64049206832530583095631627470531715560456317873142567288793746099954470344233",
+            "108891555342678209420433151282338512649864851636897421375087371208133498731752",
+        ],
+    ],
+    // t = 16
+    &[
+        &[
+            "109662619147001507322239915630310015801948472574391109728969011375923078109805",
+            "19895849119383218286103523795394534778809622380460199348150338009679461753575",
+            "47833614704005780492072212464347102151837215562440084358932119413823253457298",
+            "95057539525918980181549684193885605818637220596699093293137093955419138880357",
+            "40726744288134814949532957657782670902429277048704827157658298943531371932294",
+            "79764602583849169612772456470542125556703503031358565645693131802182071176749",
+            "67647235667888480398764882147016949219213543507210390962182324542018332943230",
+            "90854416639506749004310432951947193078581325409676629119145254462525255914969",
+            "1071202846310936263143151323514945629433368804447780838435161744302536110543",
+            "34233808780092661021928921877320056575158458925878603451282080302409555934598",
+            "14341917541252937570667472143969012640935275188171844522824436258146077970067",
+            "109371220619003195549960234018358405278959232618609299345129154566476501249544",
+            "38512296136635783396212878597623070156705393052810465138606262378788119120805",
+            "44915336408340214858501048932350103961942784412614610564324928045478229122361",
+            "108095895445953330904696789542078829085254255088785706256786418396115041163305",
+            "91343807843353894298261690095775723820308441098515842631227618133946806821900",
+        ],
+        &[
+            "40698100041862556824866000362205654350991608970019324083509262602707246071470",
+            "15677641496016939875901575877947117265709300792711409877202683633172302853625",
+            "46096597488172986724110948100114370690194915751524474282508630147255333909322",
+            "88837754535058918014431930353032565990437630498024107550095190512058872542804",
+            "96558467022225175349561342785035534103489215268300928884393543451484174569466",
+            "54997459042626884287484409171992181777109503489154204724837646680732866902314",
+            "32997363287045833619864724204988450877963572672358410815323834868919839160203",
+            "52991373584819575233822472574798700842118786044235542208705113150141046754365",
+            "77940442259278996070065576740215175761789701581574888652014599310785181009555",
+            "36347202291046034774233671121185959945067878636227452739096030516395027413050",
+            "95410972682238077590668956154268669253806419663569317209423165288881028630496",
+            "99917717388960371679888869074870047493739493283136585449756130529886653246777",
+            "89981534328910485508893546784925447092742347929278034700205181346425522970821",
+            "29496794665176555358528030880789559505471842252427254086548916547600590495566",
+            "34884832692395187008404653188032885562305111648955400185235810576609076711261",
+            "3042682259838856274454096584599513471125912035106776757446950689437090184880",
+        ],
+        &[
+            "23119749002011752853187535931820210887294283222590681581356082035243382866600",
+            "12562771651482171393125984369743536070240079298346545272036235697221939013076",
+            "59780231880123360381977768935272047594163338959047253697704216358407270263706",
+            "46349627338419382463872533942016867665788814867216508890967146677014275673870",
+            "67643059979205411094973059531850171601422869630598570514343511022912241785760",
+            "75953802120938550478699925402614841778040190519111133042627962451815797989593",
+            "29232142233340473293911099030859306697716194884348708275895765164643625756554",
+            "46807516652049502811330773508464817937400740687042940423345607008857658987029",
+            "32894989777095300049702812814742173362909860902479992063860924487437045473661",
+            "35817266813185252273553370551776947404506102368526656749422563450267880129727",
+            "54679293154772693622972805666371153667716432871360628219321503185795498111083",
+            "48133940775577994131730517930839172104378533328855426824941729590361935865537",
+            "94966067318997232980554082073289069685433866791540480704687163839540330271293",
+            "74316420621092568420148920324568925772510813195381850144030517599370868905257",
+            "45507586354051974175072354621886200483018622119581480149984829600113360040782",
+            "6925206172163479401229698105979149762717365035098502469605126287416826234103",
+        ],
+        &[
+            "92653911756041875932652219856762812258475648959097178433053850741946154208132",
+            "31951899585860327570703608021499258689902865465072734215661901334965225691998",
+            "73101144627187723751780076520133241580844772003103844471140377576323567914302",
+            "41170491623737308414807265129887716649355154477551473633069335347069180418816",
+            "73174057731558408805908731796820465490994578858057173544115110418492624714691",
+            "51204216540534618478364837439507467863368163232989755851866248024193147356157",
+            "24400804454314446517400161927949378030269719772754838958377725252328377856737",
+            "32265333884507854164356033168432896993900238048882608832158918098826237318229",
+            "13522925445779287255511399545222394934028318536949314406936957883660151914626",
+            "22558282327087684875176111768087108218398531160203460552751261568784086135314",
+            "37905465118144590883164973889000250192752069980858627910041046874725632178510",
+            "55049087343691326497686979185141248436828614131801772344592691902161835000635",
+            "108130667632971233870408124628280667659493467270720908842272181181649088761326",
+            "92437467953725791046490476856772350434749079935150250827208535562964065788463",
+            "90535725922507830901658547675860580385331541986758692754008533530017131915783",
+            "28538452440458724245277923889993469006636092495907324565678881392576797894964",
+        ],
+        &[
+            "44228631309895911949772041679768867954204777455478762811690659271317739888432",
+            "66072404743190915323285452449579300878064534755279601303575736446855613535822",
+            "8128183306567779528115045877348275266847943319927198748256036205845740651653",
+            "53306892795139379045377243117585592297930647084135657984399239532755612223665",
+            "15078043540064762676666150997739934315880802245946564480871440272955581234277",
+            "2123962174545911195834568120495122533647239922530492355479446665856193274505",
+            "8068574199407446742173970490317912169667434319675022294873980249646941609061",
+            "65367921800853121057211997132332896855425893923479274496056680423854948060486",
+            "22460349491137488963319617872897482458698903424628024367422130641245469889808",
+            "40780119868228776791119442883619275696015086174699589744073576637840639917393",
+            "6576158562206264290230374419303009604318549196896320236973824585528115123350",
+            "61766606715473454989083262222262857323963455383815001891219778552001333928031",
+            "112780850690179351527535935227507272149437927662843572231345503004459909304520",
+            "34622549988712342536071354205722773200081214241892943298954178213619736054391",
+            "88295317694595818979416507605530547612060149932487694180329747915649205004615",
+            "15488746612931327347417138318669875121361786470450758297293815315158787807878",
+        ],
+        &[
+            "88420636740654575473890677370271205804866348886016135027574905388420905711979",
+            "59163754662506180655768273993609862842181462250176308170301943404279566642771",
+            "105569953825670730346056814755179331298560255182921859332052503017368940691576",
+            "57389808793826508229727860647429218791564929982425465150609281804764741459556",
+            "36341324019190611903056092552326353562170608026645719450311108957165269681379",
+            "65476108386029169716305382206112578299677337647335484675329494858943636249426",
+            "87733613806960027780799468777102011345469519716621300206135960719110885560150",
+            "39091554897359126396758586775481782991872054690762482227486172197207350841059",
+            "97925942197361858265510648415537851078913030902229603271846132331268009109598",
+            "52834636654878433791231180555350523359185782717962227943639706058409990904806",
+            "38695704437347836782007522241011617192049334056084882471402941189233382513597",
+            "28547250874410033050138494203432544498018334361265262634163729849392995815479",
+            "84038991919367898055751133060081363492574682311895110238108942044446539884686",
+            "71922146136956091693479269815857113437336111455075352462145774070706595326423",
+            "10166648774306199551571926437854744711920725549989442502122909047332772142045",
+            "77327372951768431639522940664319379084969623315676667905586982303026154868444",
+        ],
+        &[
+            "14337038358691426495781013388727153737019232535640048382014513439288422228758",
+            "59082394046584136101052451747073742796561815080760807993023684398766922342151",
+            "16709876365104053833624013488848075756106889902166014158334345912336384687854",
+            "73630348659155174673903367398244913118879626933376162251137459084948285443119",
+            "87928082857190866232444067446845629874453694288282417383020995887184497041804",
+            "69960090457605506385449722979425680011358394554639487810613297728658537732777",
+            "106398492348614012598775004189871538979576470409529516955767625666636244693480",
+            "30075776384519781995418464270703214936487374801167577485866283946255522560065",
+            "101461525213182872776111044632044841279704883737635985068552911150331669343356",
+            "61072411741045518319005255538808808029719170833747727621318005305722342788951",
+            "4906670283637190354114753984039222402461577588293570352821193409225009328878",
+            "99509672712759881186940703732419275204472959560813665725205289618946898672116",
+            "105241267408094629841597451600969832682711021487995854377799029202296529602936",
+            "86734086212057789498041031159493170910509226895005613193158261941724211865369",
+            "58947394537757159963601074383358513841423358542748889716439769268081344676425",
+            "99527003678196130950966027551143185424309184504808419400405361122871960780619",
+        ],
+        &[
+            "81727254071377137852334912564427503585538576301005309201887300933118126787528",
+            "106272038152593462588019698296975583305889178329676940735004683530737747665426",
+            "39713207515624897913426702930629876184644991966141117683543656714698213421663",
+            "112268684271152300363519279087114617975372010791935133209561806825655836288516",
+            "58494668877967347399499102780923327644005344828920782782260056685289926637966",
+            "109369118928597924770619340972735067224285954872546311542973958681234402983882",
+            "69451251021087755661694940643406626168244928137650474749190461639668321173423",
+            "65307017166587700574992826740996743309297965624848653112140892741485013831824",
+            "17210019865662001816389792905662945380241348964854826210573088089949770710440",
+            "20952686809890901601161672770068020483676781920423365351104613674064242466911",
+            "61096574369427554699956851606354891781172485972948142386176479722904792965138",
+            "25116576491571664686231558367751336390386374656858288396460997806425586273624",
+            "53796371076323107083396227370305316550386014814582440631676852566737003827612",
+            "2753422498566031575734202789254115926602110664212015659019617649137927237148",
+            "15950057826089515679370496343583735408909027302737144556659298786761541756235",
+            "111496490349760266533796283602661986489465056730622137452761784824493766623931",
+        ],
+    ],
+];
+
+pub(crate) const INTERNAL_ROUND_CONSTANTS: [&[&str]; 6] = [
+    // t = 2
+    &[
+        "35507605999345861629588730522568443843841370642107938182037559229421483937825",
+        "88219101078381818250210849387289312058785434645735340073481629550894315651068",
+        "94643202567856538147971638841956554818115421773650247812571555896676777705962",
+        "18962080224240624977756939297022160166632869858116431593582682552400664060017",
+        "16716683249855088793984658980908893557377683870514831781673601073518391073130",
+        "79243148759292738872636320192100641604413377010873410972485440822884401433370",
+        "24936361277755321241691068820403069493469581332391229975694328833850199045853",
+        "75577085942403179351268425314141947879618773574549061946240406515358897774109",
+        "95104499274159206217032585381526506751428031083764486766259396203966053863285",
+        "38104659463235705257819612844213577905650865120663020261526434301354223754823",
+        "62087896754623743117692225772634093920297902142803146511349223358624177760004",
+        "52448266489841476866695774141170809461216605229087784160506383992075976610848",
+        "89820499938776571620082016568915055750036050918051221099876972864675926393260",
+        "26872137440298862656858292937005902082679327736685065334604755626897131184526",
+        "75728349047867230352456319204999985286736383286387828973461376744646949197797",
+        "38353531233615558762080179399194224875187776143151915098851567100668297415614",
+        "28202740604968868225104573968714465105926050225688598454420767250165144572748",
+        "47095816693942075583737937384317849252936106980885797761635311614004639960718",
+        "37335138102311240367312744679934938413272899074914508053701107463057246269222",
+        "90315117696835568913719619324023684381527663337240349210739942051792259842771",
+        "7634192396074695197555238249071603382201432589471559705121913083974317355256",
+        "59523547146928458350597435923115391597504677487810713330129401260447888685204",
+        "49223132869255334650266480079078735493232108314325998997897453620557456049257",
+        "45654733309929120567968602016258261699563501709150061453880098891162993902593",
+        "22702059600240699683191712104298031128451304113391835071882488462912577171436",
+        "102554249502802102056833366369621888879636358078532819246554808860768423366152",
+        "67977038313498445469183486521823294016853691926700470204381737668112990241024",
+        "1677854808237151129623291770237231572028883297373335843560903996176977053425",
+        "43895739365730441857402351457780537163738648977518961971632965596155206451437",
+        "88030272801491366439987614533398432722870397669777578261333668189792586996629",
+        "4424413113540669934209914738196480797751127673735853440494696699640464882701",
+        "1265157622831773881843364302195570768697883869437088389511764714637756420767",
+        "44714061245523436117945344528954446162738935202165939408341684205200124270797",
+        "48015591323256552819513429238255557840666053518502099654171598339767901545254",
+        "53750465211442131617390532758965408371452053116462462989086943356281021495337",
+        "97220474459334199655186567682748522567286511251420127302502659642183488790965",
+        "1863705796254138010932534698329990924843995395154587249209220134723699516345",
+        "114641608263500945501025874491396486105780052891701321375196114755875799790288",
+        "30716852414508293758433387375857717358026679301249182272662989241539163046647",
+        "24883138378061449513048462259692886704835030670914523895423703762623572112132",
+        "97539036169012865388254304384546444247873075707679199343009008384115299093273",
+        "34732576533982714922891714309427629815437236012779165158510486286987733435468",
+        "52404151095294070516998666618800000316733421365815129494507054742256303845550",
+        "76081137185282839545614414248337113742131314445735662564568507819410746279683",
+        "23518818722736557801041036213044099347324318254249516240460821627466749212834",
+        "93527153229138269448608372545344979975813378245392984854465818783358540770650",
+        "107910754339428808915933224593322185134101257996404800463537945458113076749136",
+        "18063318610832225533994903501790107612597441965161153996827446543510024047743",
+        "96003198250572392567623610228298711102577314954522261028199276198962716729529",
+        "80115343615233236224950047705101689147105095915815843384838389975899487257715",
+        "43218427843750114768294964057016033903546055253950201320978798006281269836250",
+        "91384075884780056278181690734414636943250396506353229204970010454841324134852",
+        "57830278809685080520293585689935325499810077898045711551279669112036060336596",
+        "97543229961106992982291681806061266640429668214993652113348347923222431219406",
+        "13836574913995785508250102536032571661434641830112026429139620580143910115754",
+        "47500844155831293479781753909563360698998461540920697143773812513253138344618",
+    ],
+    // t = 3
+    &[
+        "19272373877630561389686073945290625876718814210798194797601715657476609730306",
+        "23132197996397121955527964729507651432518694856862854469217474256539272053037",
+        "9869753235007825662020275771343858285582964429845049469800863115040150206544",
+        "36536341316285671890133896506951910369952562161551585116256678375995315827743",
+        "62582239167707347777855528698896708360409296899261565735324151945083720570858",
+        "96597358901965097853721114962031771931271685249979807653919643952343419105640",
+        "99475971754252188104003224702005940217163363685728394033034788135108600073953",
+        "52080483875928847502018688921126796935417602445765802481027972679966274137987",
+        "101922748752417217354391348649359865075718358385248454632698502400961567227929",
+        "26980595292132221181330746499613907829041623688147011560382352796984836870749",
+        "7059991836806083192408106370472821784612460308866802565871813230060135266390",
+        "19329812920723038526370491239817117039289784665617181727933894076969997926129",
+        "65570620823578601926240439251563587376966657231502120214692324496443514623818",
+        "58403733332589349613112270854204921427257113546270812628317365115158685715742",
+        "45021021211732634759643776743541935700591354899980928498981462362035961745443",
+        "313468157086800401026946312285365733155132234906935411743639256319782592571",
+        "101316949793045093761117346380310841944294663456931203380573537653884068660109",
+        "23683935571424619534194393788101669168630123784066421490798386323411538828592",
+        "45470730427236677197026094498490008082250264942279323465121581539984407294442",
+        "48141067373531800337373447278127981363951468257064369512416205750641258258193",
+        "42554919225040466028330117313396362347164995917041931400909482795914116747618",
+        "11551941832988244108260444347046942236051939264069344013774353630451796870907",
+        "60185799182545404739011626517355854847787627814101363386450657535504094743765",
+        "81823160578900678880708744457872721685515019032370491632046212317701226128393",
+        "7165646831054215773988859638722974820791178194871546344315162343128362695647",
+        "75289707601640398424243937567716657896680380639974371761136292031415717685949",
+        "7150842764562742184396161198129263121409208675362553300851082062734889620953",
+        "24380904705269761063866540342138412601132455197711667167747524315310027386226",
+        "9728986075621437350131504894128984146939551938810073671231633620616345344412",
+        "10579382052089733216628873394134968879891026686695240299956972154694558493896",
+        "8171994519466002143995890536756742287314780571933910736618431096190430536601",
+        "30420144259409274775063072923609924427757612539094840146996944760708902708570",
+        "63962155989812703023698320394024694856871261481871757094333286947755599007133",
+        "25280070391177856032024336895094721131222985610587247589336316615596140400436",
+        "15305872319988027006162258914083163651002306183917888172691618513722838997098",
+        "51545603291342006705870081001071419395633279951502747769141857387796043104608",
+        "91109680756552587805002537489407348773333405839144382221272597323798859182191",
+        "72175452855185658158184807496160149169667221240389196996344579971523681433202",
+        "30361989157454953234766224747536334157139256334148153290771332849307087761025",
+        "38169634499980959088614671703639492517637815232220682121652135514105493936992",
+        "49591153263237620796156788742811547511792615129981565620486914545749079774827",
+        "47403873018260745456113868791119169163627014766514972598212646481717066065016",
+        "93989849689047144228924801010853106857960399638657695410345207191739048300111",
+        "10590240512802509131776989274411792739339398409955259174829387591089799115255",
+        "29183703335869638067547208413224742887766212046438654772943025958628178245227",
+        "4131650227136944095885036960767735080970262672750406866066212532739784907379",
+        "43395510588213653537697670365796375057855260611965666448183946252832290017444",
+        "95246795133940226900907730059125298420936467652619708443128629427116119621152",
+        "6012209003558496814495903476753006089125143165365334812097313083703216071080",
+        "26183233284429251459198269925441295879550203824094631575778521083706115817955",
+        "26058994700533582730528567480051558438548299522338811756875396252016497202713",
+        "107240485663145290290374164860301805857261278222480421976433215167444496066511",
+        "84412820763898503096477800002865877536719992495674955119188074297975154406587",
+        "52386303852182662900790700046090769869460994629239741773176060026198900130384",
+        "95746062835936512160025091603469309809932540674474329021370075533568318932379",
+        "22711334660013961010382652754865456251782349529764119853461446587583972054666",
+    ],
+    // t = 4
+    &[
+        "52809075309556943106986141452949717940629148273802787423423289362399196209034",
+        "60890046715664146619001639031441537160046835768724933226280241184492224363164",
+        "23504117963617971456037139029683229034435200714834222030049030075576535185840",
+        "84785861006672570580184220137497900432874830589095014372656702765686986514839",
+        "48262541868214438391434694322190751245954411559025755375302464983013008233007",
+        "62662117491859372838718936087523324597083031817967190482362583657692417908852",
+        "47770615331410120719518400793795922138565278497062361476915904726554644523833",
+        "65697009294914346106289794495065122374861558437980199975669118389011818969137",
+        "51338048476734654819843665191644594938059575055578689546561894318992739335808",
+        "31133690456147317185728873208579095670381990615260449930815788944507181453075",
+        "55763274281054639030268021029060482421351269199900106351259119197798010259788",
+        "22891567093403816254251107249600270859899275673714022046505848338473197230070",
+        "52077302256471174791686894608615120411834081959643679441452133717735689281303",
+        "78747225156283800285769115220044228751331503586133795898693375618140910254930",
+        "5032009813944426328987940301787864160486881916123015815157895493390279538209",
+        "3296253720813581629649930186206488990291518340459246921246801990409173411227",
+        "78734567004808691954517611734527050502286856282296318771381118793532013317800",
+        "17113679436237357429117846540497160147511195762263507745797462195967121291341",
+        "47180227053058405146289969756756410172047249399235140618128808559719748751290",
+        "27024584858612405133058081860423764819822852994745263956536099330230554508315",
+        "27458758994982227619447384716744030905277205242109018806141496944306304083755",
+        "46337565371969989669039222639017129055896131731110447453067974493576740487416",
+        "72817143232212749054161057518007856845261815730688826966294109841789506580078",
+        "56321494994453401534060639060211393214707318338430183169556862970465703931267",
+        "47641066954874010332005668243012415666424932011782345721868145476950612989806",
+        "2173056577234477985166568984006937441351015925471271372352948948323631751926",
+        "38008238713973671686609878927922532552420003245697390397131098922059208396613",
+        "18741494449733507943028908511356647806056489575806686276240585710724316245122",
+        "53480515848443478349127664667082107520415843219859891784936043894483736370782",
+        "111628534688062499113480305150504911869824753556637412033729042512640809318951",
+        "22162998876949376172698095054421177356402699879414052933924827031157759377099",
+        "32233872982998511535262761892559272474691705344112425999960121770613519186517",
+        "23991618352903980719304373251590088844007918691571650283783952627380543475467",
+        "37881003061581410458963102603716521584769423351447088689587392769250090219779",
+        "81158214558444585606676377853246189455611030387732381278220763711495170911187",
+        "28753310744689954691634232600679013665070472836146831942653432134584014444123",
+        "10724770276737893700403938392298071654510143271356861637127074603751733158454",
+        "58120459702438585737101202332620227457378431460301877641776317253034792985304",
+        "78951698789451510913286632797939684428524912296754565082238286399575687362870",
+        "61091630408695599460278928327185238911501152529434654010705219465573542933339",
+        "8958683536661991523279567261226202991916911936486910372492921366521289901950",
+        "65875025589294107245190447572916821037389270248167808061603976411091969165836",
+        "61049170349193798283637841253534645696425828577719436637671204029518598277737",
+        "113903982285924141749959236477242572405287393167417988683130929535207535742399",
+        "1081205435472372334561760291537417729788515829448028367160900025649898973131",
+        "48071768686499350286800657707057381586487581734493940259910364855170863734291",
+        "37754795669707915408876786398387172079287468364493750519229803982685848577168",
+        "109998767128076691601477155142321845142104686737263583881157826989425734170893",
+        "80366339480377020354951295065608115667237213239917138037794479587749543987906",
+        "16385846337182324400240578042110320687263116317161838139562368621709615286529",
+        "56108550547210517852500348871186179691293213958952962142791556645233541539671",
+        "25691588493032044329330146256636141331572443178931731975131706773389348909069",
+        "70620434333274708110537298861977586431076717758291049248569778235804417082889",
+        "112889260949454670825451793594649181991605107319979225196780963336260206621596",
+        "31356483669230584812384585173518633846694626514292795837622203808929423489818",
+        "72535203520064717156407277079735909514529742182000787682238040666504492728468",
+    ],
+    // t = 8
+    &[
+        "86749598179938374687520438185609065841411387406401055870034665771734160114990",
+        "57571113568061149319472522275765486178512636094348121712222068120540569365366",
+        "23934780689745390894132653114120016781681763755250288383940413009293634095998",
+        "99096503964749214824683712845781066122199533360528639130095343013298329653411",
+        "65597079247623718076180272310820145944706325118809785738370944262342903044706",
+        "95465444681043657612052303594586073232679188671104208032191237038205651214501",
+        "54742294682466017724541884390062089087588586326825022764348360797493965417856",
+        "106504963145005179323741800811230196462311406390285400901971464177673515767785",
+        "70492092382086318366253433712875895788093564944739572923157778834562465251402",
+        "111090609978696848818164547844426544348933587675674707901714264792630946363771",
+        "47839829272069215973527406785241704849318292913020558009641534636500714471942",
+        "15103091358899422298494661571991313090863363244674263312828836460377580082382",
+        "114511556125560461047805633921057004880173753911258850069098199162887188764610",
+        "84079203192697961199751526508599624607530484308380162392499255100814705855674",
+        "87529320091051510287764346355640772594401143181948717896877076297632715456384",
+        "108831141508953488658567162889224051875583051896175954278771789742194594281967",
+        "18755499195817268888041703466593023751243044582814921743559761292126340961194",
+        "92201388034224049411415798285438657783262612293995588015010440052792855047516",
+        "52150716695191561756383995797982527904236187480159954814514286742967408508597",
+        "97492814583042893165502493361383739077183527734451312881814604989233094543041",
+        "1936935999066095233223298060953267129013983749017900896558963206891295560768",
+        "37067153604228734615411270234414732646790935484392697280455753822620977496663",
+        "1596295863982432180097190292799085992860621920236057602505162097433543936206",
+        "76183780449914208813173279737564303641877587101268754788790197836549411046299",
+        "109285022796135848863305509453030920768714514007960624032070918143180152058207",
+        "54683950165663881348722280149187464335144491766934887702371749784585259524586",
+        "24595449057468055211636504248219233270169796442522071701964786642268517686469",
+        "109987480520471005499729153387228674129047629012410367196227969171421927958644",
+        "42868629176122608365840929119181559887094336242290914265340416288028973382189",
+        "25384617337388444314117490967876401451670224599805115548428610245642924402478",
+        "14552173312946783745450340218002974843783248165515666511618612520233316709576",
+        "85405596459235459049159532475806886229230425030739501366673780095579588518039",
+        "88514512495319645529279050866942772610748290459327477504956250819837843457268",
+        "108771972663242688983962306340942283538244408745985411129313490514775731369653",
+        "85216824840060156827897021497316124532708514823161785011623966463889783172146",
+        "33281295406823153051114050081531879416792061188673705642836709542085981522987",
+        "34342671452435056895179512317025921633214180372195129160473066066712184217128",
+        "20246587920097980680943736869050151045204771778418757567129053563916325017178",
+        "88960864223596495929261810212324763319538374002526130242788810267397666303677",
+        "2514638029180918548591363191597707162470677315924347283491165169662958766257",
+        "115588784137235467958614392309850779514859610438357339287264254398799848866082",
+        "42340173605390078226383382814771440034077419332561450731795517728357191826348",
+        "63933487481629787447228312102295997077018713909034156027915699436219385788046",
+        "99407205832436417479827406092274613274353889349191182601279431101820153415020",
+        "67915627345856510344058847473322683273304917635447242050508292877347461583545",
+        "101475244356013516465065365230533925347425182086286973265782636443962275618806",
+        "80122022192604709375212733356479198355232390910223123618809260858779059841936",
+        "108957246251239712642329579151864336122266990741538413125695180399529386934745",
+        "97372189818239396480019351135903955684431679436748328135814473008307166157388",
+        "71405694813953008438549851300242261456358826921769467863885611006124072129187",
+        "13697717924405946598682749702516351663343438112081108443198096807582764641946",
+        "53457842033900688850062907090470507485079786493869295858816161587429848151542",
+        "15479553038055591787966329683860955236361141206421891583986229384772244566796",
+        "21390753956580658442224178543201829034500897483214223564158538402430798860358",
+        "23665242495750115297051969619523033203687838429861978741817911452175294348648",
+        "35235792101132822635475380655602992884922184245445398880877865807786442036860",
+        "73169649122555237546078629948470296405648902752710661819851722142454119445515",
+    ],
+    // t = 12
+    &[
+        "76001837089163580023803805319846356482495242803940411489363810008360721687823",
+        "70107899346039288088336886482724137113650173016345078331443285823604256689491",
+        "73664234878789619158277780550328853026435520432960451012658693328532189668696",
+        "46842673561577166673152615357762117858060445943402044835031273060413011445827",
+        "102793955888978009766978369935110144372631223822874174603614838543852217432397",
+        "28354179754326739086081287972443802016156440967572474697372953462374944223316",
+        "114964826304789977515073035283779605119720770905724095663845693737072419598083",
+        "49633154201976727028449373243602255696198377092255564218276985520013729496071",
+        "63995541275090592040054295200757932696934948220704380355305713190050143570378",
+        "7702550310958097776325075811146058105211053343626403598822553167420893574758",
+        "23571633548625900957842653892565741979840619722379390950526600543008808146244",
+        "100786232148310991867305475724366014487365441829760000697114223334175059403709",
+        "103817350831430416108085805573959769016669605911493078403189505274843643133785",
+        "33649488828660646405685214098909323884345207437450120663272969992996014239047",
+        "65249202346715414960536640266000542699454448404800482738841848238080567027293",
+        "84475670253227751136461386093090610186795423584132587209548364880415641188871",
+        "59740716382936189875571279438000217391650587495139096293217076435760682716027",
+        "26659130460831229842872642071965907163632467323368475929509304402842736908684",
+        "88945338553587736267939269396073060832963370202341561889797522000836198597101",
+        "103281196655732531188815701312672461350042601239273744389556170149396221786220",
+        "39025932732842180354621508100997962418804826221724285305942611856257831846305",
+        "6311777103451644691530422107471920972759848152035433581894992623392817667070",
+        "94097257597906941923376690080661300492295400104403240645172663591763377536679",
+        "47095068521066055338702411067116431839795794045471647484776216749126648330682",
+        "87158818270711333684991844488268287722270159259044074061770957773070994599209",
+        "8612744677573019953387882214155967815384981740209939001751387468898107800562",
+        "114639532921623449914674502972594330041468492162847748738446432413792748648925",
+        "78645052448361495563584184304820802733514939794801767517778679006757840408882",
+        "13445752259302660835376325211256481898433238359252232907378713066370552519485",
+        "92241022655345919003853822209415948472146180669550089778356725796757783433585",
+        "81014872895416380098869455499926572623787882937876711475875819614096153512807",
+        "28535388563777409583597426613771300341750900140871124859305420684175712712629",
+        "36931833210800476310310189759833901968168765364674476347531569067202428419966",
+        "61963499710842578443230218509430541312538881633091852998392648082696630071433",
+        "107040326177666950094315410240146202554461819079224086295656143311947005393651",
+        "72502097660549397177943757521490167296022179625102568998737103758066663472967",
+        "53235742251191776369816094582068095037440565461147189968072286942268765649735",
+        "58874707680015070034342921305784706729150886216701658380690218986790933409358",
+        "36040083059639532728459452664357736343170506834707751731583006456517190522533",
+        "18331226484393203129266477009021816144316847665709325251730600597503341593778",
+        "85508488229561589121379762592507087406567011616108333422029252673127636217253",
+        "100833481157185214917481084419314965018284580809227446067986411206430702359255",
+        "27023997173166027937025991142117241540071633605317906990542356501065772074442",
+        "38384084912786915677217509648041747958107336515580578509535231993612371893166",
+        "55202203182797579730933333765570975659248501572555219366116011976637733623835",
+        "92960317348811275861326112436834016191650444473365820574633011808730554843688",
+        "44714529112488612535168702300543735433327314457921389933095684477949847891406",
+        "30566300017266059845635074183414663868162962993364981178740124942722487767154",
+        "48141239276161169155025525915392637718472640443121448234711820697092626075020",
+        "41915744424551989460168298523294058807856153875976010416365255493972167493776",
+        "8642217486091477816653154505353766326296892654473211220622208550378175595210",
+        "937334773474603113865979501360941904959360589590275038724946918475025744074",
+        "15854787931545615182759532069347312798548724116392464595396750930576000641133",
+        "79963427660063111245294393445915807776370692214754722008032771013965659018019",
+        "105870590534709967991062331946427260986162424879446517510952246153659044650574",
+        "33382167651569915291865756731545182363096793467364597517337445952982312071174",
+        "54086717657743146879702179679352583824762812684308523895639289276408173354658",
+    ],
+    // t = 16
+    &[
+        "114956456581892462444966978409365059090888260879303021778571815619181423999872",
+        "17297699179211446901337025752811837409696929483851410223880356564664768620092",
+        "42007212928491965684869674435400182805758963538687709887108905611711657278484",
+        "91832386358629157668393213267566521837716334591932114934422677704515598730546",
+        "5493466363019933489084905082042236857264126882651825183973737430326207577940",
+        "71086926217135606265744648281406557722989081134010817925289428643277392764295",
+        "104606883355836496312997065764196778809419774635523324974472641666294102121255",
+        "95587763952121570739888734809208608388112771285133106797412299219336662966248",
+        "75781162902730092785864352496382589083763660152799172975705722105972707275129",
+        "77094221623282595990658120801918555285550193921515733107385454246748620224756",
+        "27167725460220463312776131984279027696346861308384249181443254965981792108789",
+        "75997594119994099888854457008790231375797295850356642581691033478283843572905",
+        "85338718326991819569744065130752213228765051473656170254918058815214856905705",
+        "49649249977979631978416782350677293920919280291009245644306925957639326586253",
+        "62245364945351230570779091850990645884621648348791841693639023675936191000777",
+        "95858546582616583046715665897606342952827765313421981367340136112932152720898",
+        "49167165114760783549036029963494778495199546189704931755978403414785283594921",
+        "110152718795592771718106178175684669707009818450572129319202512467666396628120",
+        "38684218207586157736230675444134890334204355100840392556067239956297566810387",
+        "82757715936676229111981630551398605443961778448071432294262843648563251437558",
+        "10863641584561144612451129790097558886511095796007913949995114212016041423437",
+        "74657780767626135429981057720618316442842094855372440051869954646601614814061",
+        "112255566774473835943893200045268559488659641412940385155801672929651847997833",
+        "77459223484727522905121112432518532795680408770732306876922158263443020529901",
+        "52689846226243973944119732399105121034272407206819577645127954545967081855303",
+        "90900521243406272777138320660796361182668993745418419339128672722989873301642",
+        "52583553716696737874688700207781602109523436546956125688302123480150128774814",
+        "112845242565673655946062113874730667861811354746570373785151122393118978543219",
+        "93973202370242844681273394577921867858548637758713790017017657187751757994190",
+        "83730814014012540366893686812423873427035355434263979696856158299673706276267",
+        "113866370791413723977991095401513303815035971773669999378505412903954997422628",
+        "68785240227252514319168202694880740050397986046478393849477474173779341318490",
+        "70165183399854574185746118237164494610999665614252220817864509643810819379809",
+        "88479975072591483281724726738467517739753194619718823351897463593864564790755",
+        "34347448343454720485495071016150274420034305621644406942434570897865679210881",
+        "24816050080270482048281075468593070999481548955703635983451130815014941346498",
+        "33831616497792919307037849675558339960184377595630835657236678101232708433193",
+        "114898030127568976434576002700751920353546741936201711071873524467105419597488",
+        "89490681139265016659794218180242654721156702078402465795996541594029066721214",
+        "33340557249424973511326472883402271529171024988215313511102479684032005121340",
+        "57634440389101185394715554255651938002902339606824872810699142116625859269708",
+        "1046155763223193776954875508938336591359959300777037928270881523189331651001",
+        "23037841165352643616365162040802451178553922661370703278629130867219838702291",
+        "66378379656855550450400786188844829723297816894280996072670296329214643647739",
+        "80258523268803294371073475629530209385585959271820865345285179088717481769939",
+        "23113920381469637230829064514775504655842613847133036302823477940136230084969",
+        "100594769254797937185529274818530274172322406417980375628756258623361181902378",
+        "102524348812948727277590754319070989183249404974815544109711979739302802341991",
+        "44834285148459128634754475164548916659590877084528548209959215076495106227699",
+        "23084969428648336146549844701221145073408557283485804849411407952854408954661",
+        "38480354022609184935882608001348192770944916418647013908085370952626526724418",
+        "100293282899777795892180589692510112491442913530469783516798896432832435655493",
+        "62684190685599810826701105732227913020546875206100495733759381922984421740576",
+        "30701360440500849233908623245348966232542364954434662914445059331979857643242",
+        "103892478051464114272886878648234210454596826426416961033090078775871514106228",
+        "23726256671413086889281140154098911493199621188608664735742560962985655295954",
+        "75138412896179337098527751981553093050801850460508997269443725943663992794248",
+    ],
+];
+
+pub(crate) const INTERNAL_DIAGONALS: [&[&str]; 6] = [
+    // t = 2
+    &["1", "2"],
+    // t = 3
+    &["1", "1", "2"],
+    // t = 4
+    &[
+        "5023966523249720356732541980656676261465423179426355965585174785213540465424",
+        "10324012398642155103551450049263606946888905037257280451764367730749085416306",
+        "96969922067225143145051760692400990017514844912735843888127363904116921172845",
+        "4720213742152762019269707520479578993362061998054777312058308512956594343268",
+    ],
+    // t = 8
+    &[
+        "68962229205423440639076262171297900740500403940537094098519323608165859965878",
+        "114107432901269100787001406737069444286903028167098464281339236642706111386315",
+        "115252758325428937816679658084659310563873364361853879508560493600988628148701",
+        "34168833932457801670049277592744224216389577485385930898386284300815621379467",
+        "42243551631230563903346277819681282969430508652827070142005227060916353177598",
+        "31478064872207126820945409850838406237885493883992467428865609465806796634557",
+        "71034716761358116247198356974706680906937591061846333832919191877099111154442",
+        "17250942983197359844247026288081145711434006875007634529273083554971100996390",
+    ],
+    // t = 12
+    &[
+        "43083287526211854660488966291812991231405797353293953819575768333042724734803",
+        "48264708804106572079614398401207077734155233895020949070117603038325786747364",
+        "92059242768146235244942714774986051908796105833754609116962383026517021887481",
+        "82660863967511165368707386219367647395529483621721386095886256578451130614463",
+        "94922156681607928243719976144707391442454746537919433356472734593824671789137",
+        "41405106029074057995371404185604091440854053987416873456010162727671957138321",
+        "91945404149874923380743060859424135342173463991408680257312178545906572244041",
+        "22130764428617754176381666810949966741643685951196092615398861658614089389313",
+        "98577548793418628930038973701138052829582947619402707999646448183117940034839",
+        "36111097971944971233004378731814795994329343496570805259930344157367580510811",
+        "31519912348183084930786675246965205564504676371424729609841091058861669848000",
+        "46692704854121003321474882260708762483827026485415751146455502060443235384047",
+    ],
+    // t = 16
+    &[
+        "106402005216564710557489454835164869403306627615685843393021980182142018272199",
+        "49385632887004447254575563313201172211819659386627584279780547120334532289347",
+        "44598301531329514127924327371596700702766333651120363214707174409791214717212",
+        "75782480754046813391797541352394494116196655307388457140564758000703867170041",
+        "61148506885330158822754344066215797480573640527254854179637799097248324590495",
+        "20119377545967409184709635845366325308779650181177140481410664877096197125428",
+        "87960587884142840290839573165428545895529028346596067819303716013586608210705",
+        "72385946948083542719334337185023337118054983161549603105867584311818280488906",
+        "28312193553012511655116621876122251404265208731144903944016501672780461742660",
+        "46999516885108683834686692737617601423946988144688435519323473373806471727746",
+        "2360149332052167972335855274945244759309542337469305715178959024671645153095",
+        "83044040733380224330630139373230932226273270113682982039185775628559472419915",
+        "107547276282891443770936979230971801989291402253390266730754984367292402827556",
+        "54909646749471158478216975800690352789627633664348009586160259367647677369141",
+        "60148152098129794733737315223299081696201541666137869301935373261978465174097",
+        "17984285183313465368986026247215894814150812859373452346924927429962983028905",
+    ],
+];
@@ -1,5 +1,17 @@
+#[cfg(test)]
+mod bn254;
 mod k256_consts;
+mod k256_poseidon2_consts;
+mod k256_sponge_consts;
+mod linalg;
+pub mod merkle;
+pub mod merkle_store;
+pub mod optimized;
+pub mod params;
+pub mod poseidon2;
+pub mod poseidon2_k256;
 pub mod poseidon_k256;
+pub mod sponge;
 
 use ff::PrimeField;
 
@@ -24,6 +36,11 @@ impl<F: PrimeField> PoseidonConstants<F> {
             round_keys: round_constants,
         }
     }
+
+    /// Returns the width of the state the constants are for
+    pub fn width(&self) -> usize {
+        self.mds_matrix.len()
+    }
 }
 
 pub struct Poseidon<F: PrimeField> {
@@ -48,6 +65,7 @@ impl<F: PrimeField> Poseidon<F> {
         let input = [domain_tag, input[0], input[1]];
 
         self.state = input;
+        self.pos = 0;
 
         let full_rounds_half = self.constants.num_full_rounds / 2;
 
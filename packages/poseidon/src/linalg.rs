@@ -0,0 +1,377 @@
+//! Dense matrices and polynomials over a prime field, as needed to generate
+//! and check Poseidon parameters. Matrices are lists of rows and polynomials
+//! lists of coefficients, lowest degree first.
+use ff::PrimeField;
+
+pub(crate) type Matrix<F> = Vec<Vec<F>>;
+
+/// Returns the `F::NUM_BITS` bits of the integer `x`, most significant first
+pub(crate) fn to_bits<F: PrimeField>(x: &F) -> Vec<bool> {
+    let half = F::from(2).invert().unwrap();
+    let mut x = *x;
+    let mut bits = Vec::with_capacity(F::NUM_BITS as usize);
+    for _ in 0..F::NUM_BITS {
+        let bit = bool::from(x.is_odd());
+        if bit {
+            x -= F::one();
+        }
+        x *= half;
+        bits.push(bit);
+    }
+    bits.reverse();
+    bits
+}
+
+pub(crate) fn identity<F: PrimeField>(n: usize) -> Matrix<F> {
+    (0..n)
+        .map(|i| {
+            (0..n)
+                .map(|j| if i == j { F::one() } else { F::zero() })
+                .collect()
+        })
+        .collect()
+}
+
+pub(crate) fn mul_vec<F: PrimeField>(a: &[Vec<F>], v: &[F]) -> Vec<F> {
+    a.iter()
+        .map(|row| {
+            row.iter()
+                .zip(v)
+                .fold(F::zero(), |sum, (x, y)| sum + *x * y)
+        })
+        .collect()
+}
+
+pub(crate) fn mul<F: PrimeField>(a: &[Vec<F>], b: &[Vec<F>]) -> Matrix<F> {
+    let b_cols = transpose(b);
+    a.iter().map(|row| mul_vec(&b_cols, row)).collect()
+}
+
+pub(crate) fn transpose<F: PrimeField>(a: &[Vec<F>]) -> Matrix<F> {
+    let cols = a.first().map_or(0, |row| row.len());
+    (0..cols)
+        .map(|j| a.iter().map(|row| row[j]).collect())
+        .collect()
+}
+
+/// Returns `[I, a, a^2, ..., a^n]`
+pub(crate) fn powers<F: PrimeField>(a: &[Vec<F>], n: usize) -> Vec<Matrix<F>> {
+    let mut powers = vec![identity(a.len())];
+    for k in 0..n {
+        powers.push(mul(&powers[k], a));
+    }
+    powers
+}
+
+/// Returns the reduced row echelon form of `rows` without its zero rows, a
+/// canonical basis of the space they span
+pub(crate) fn row_reduce<F: PrimeField>(mut rows: Matrix<F>) -> Matrix<F> {
+    let cols = rows.first().map_or(0, |row| row.len());
+    let mut rank = 0;
+    for col in 0..cols {
+        let pivot = match (rank..rows.len()).find(|&i| !bool::from(rows[i][col].is_zero())) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        rows.swap(rank, pivot);
+
+        let inv = rows[rank][col].invert().unwrap();
+        for x in rows[rank].iter_mut() {
+            *x *= inv;
+        }
+        let pivot_row = rows[rank].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            let factor = row[col];
+            if i != rank && !bool::from(factor.is_zero()) {
+                for (x, p) in row.iter_mut().zip(&pivot_row) {
+                    *x -= factor * p;
+                }
+            }
+        }
+        rank += 1;
+    }
+    rows.truncate(rank);
+    rows
+}
+
+/// Returns the inverse of the square matrix `a`, if it is invertible
+pub(crate) fn invert<F: PrimeField>(a: &[Vec<F>]) -> Option<Matrix<F>> {
+    let n = a.len();
+    let augmented = a
+        .iter()
+        .zip(identity::<F>(n))
+        .map(|(row, unit)| [row.clone(), unit].concat())
+        .collect();
+    let reduced = row_reduce(augmented);
+
+    // `[a | I]` reduces to `[I | a^-1]` if `a` is invertible
+    let (left, right): (Matrix<F>, Matrix<F>) = reduced
+        .into_iter()
+        .map(|row| (row[..n].to_vec(), row[n..].to_vec()))
+        .unzip();
+    if left == identity(n) {
+        Some(right)
+    } else {
+        None
+    }
+}
+
+/// Returns the dimension of the space spanned by `rows`
+pub(crate) fn rank<F: PrimeField>(rows: Matrix<F>) -> usize {
+    row_reduce(rows).len()
+}
+
+/// Returns a basis of `{ v : a * v = 0 }` for a matrix with `cols` columns
+pub(crate) fn kernel<F: PrimeField>(a: Matrix<F>, cols: usize) -> Matrix<F> {
+    let reduced = row_reduce(a);
+    let pivots: Vec<usize> = reduced
+        .iter()
+        .map(|row| row.iter().position(|x| !bool::from(x.is_zero())).unwrap())
+        .collect();
+
+    (0..cols)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut v = vec![F::zero(); cols];
+            v[free] = F::one();
+            for (row, pivot) in reduced.iter().zip(&pivots) {
+                v[*pivot] = -row[free];
+            }
+            v
+        })
+        .collect()
+}
+
+/// Returns the characteristic polynomial `det(x * I - a)`, by the
+/// Faddeev-LeVerrier algorithm
+pub(crate) fn charpoly<F: PrimeField>(a: &[Vec<F>]) -> Vec<F> {
+    let n = a.len();
+    let mut coeffs = vec![F::zero(); n + 1];
+    coeffs[n] = F::one();
+
+    let mut m = vec![vec![F::zero(); n]; n];
+    for k in 1..=n {
+        for (i, row) in m.iter_mut().enumerate() {
+            row[i] += coeffs[n - k + 1];
+        }
+        m = mul(a, &m);
+        let trace = (0..n).fold(F::zero(), |sum, i| sum + m[i][i]);
+        coeffs[n - k] = -trace * F::from(k as u64).invert().unwrap();
+    }
+    coeffs
+}
+
+fn trim<F: PrimeField>(mut p: Vec<F>) -> Vec<F> {
+    while matches!(p.last(), Some(c) if bool::from(c.is_zero())) {
+        p.pop();
+    }
+    p
+}
+
+/// Returns the quotient and remainder of `a / b` for a non-zero `b`
+fn div_rem<F: PrimeField>(a: &[F], b: &[F]) -> (Vec<F>, Vec<F>) {
+    let b = trim(b.to_vec());
+    let mut rem = trim(a.to_vec());
+    if rem.len() < b.len() {
+        return (Vec::new(), rem);
+    }
+
+    let lead_inv = b.last().unwrap().invert().unwrap();
+    let mut quot = vec![F::zero(); rem.len() - b.len() + 1];
+    for i in (0..quot.len()).rev() {
+        let factor = rem[i + b.len() - 1] * lead_inv;
+        quot[i] = factor;
+        for (j, c) in b.iter().enumerate() {
+            rem[i + j] -= factor * c;
+        }
+    }
+    (trim(quot), trim(rem))
+}
+
+fn mul_mod<F: PrimeField>(a: &[F], b: &[F], m: &[F]) -> Vec<F> {
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut product = vec![F::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += *x * y;
+        }
+    }
+    div_rem(&product, m).1
+}
+
+/// Returns `base^e mod m` for the exponent `e` given by its bits, most
+/// significant first
+fn pow_mod<F: PrimeField>(base: &[F], e: &[bool], m: &[F]) -> Vec<F> {
+    let mut result = vec![F::one()];
+    for bit in e {
+        result = mul_mod(&result, &result, m);
+        if *bit {
+            result = mul_mod(&result, base, m);
+        }
+    }
+    result
+}
+
+/// Returns the monic greatest common divisor of `a` and `b`
+fn gcd<F: PrimeField>(a: &[F], b: &[F]) -> Vec<F> {
+    let (mut a, mut b) = (trim(a.to_vec()), trim(b.to_vec()));
+    while !b.is_empty() {
+        let rem = div_rem(&a, &b).1;
+        a = b;
+        b = rem;
+    }
+    if let Some(lead) = a.last() {
+        let inv = lead.invert().unwrap();
+        a.iter_mut().for_each(|c| *c *= inv);
+    }
+    a
+}
+
+fn sub<F: PrimeField>(a: &[F], b: &[F]) -> Vec<F> {
+    let mut diff = vec![F::zero(); a.len().max(b.len())];
+    for (i, c) in a.iter().enumerate() {
+        diff[i] += c;
+    }
+    for (i, c) in b.iter().enumerate() {
+        diff[i] -= c;
+    }
+    trim(diff)
+}
+
+/// Returns the distinct roots of `f` in the field, whose modulus minus one has
+/// the bits `modulus_minus_one`, most significant first
+pub(crate) fn roots<F: PrimeField>(f: &[F], modulus_minus_one: &[bool]) -> Vec<F> {
+    let x = [F::zero(), F::one()];
+    // the product of `x - r` over the roots `r` of `f` is `gcd(f, x^p - x)`
+    let x_pow_p = mul_mod(&pow_mod(&x, modulus_minus_one, f), &x, f);
+    let linear_factors = gcd(f, &sub(&x_pow_p, &x));
+
+    let mut roots = Vec::new();
+    // `(p - 1) / 2`, `p - 1` being even
+    let half = &modulus_minus_one[..modulus_minus_one.len() - 1];
+    split_linear_factors(linear_factors, half, &mut roots);
+    roots
+}
+
+/// Checks that the monic polynomial `f` of degree `n >= 1` is irreducible by
+/// Rabin's test: `x^(p^n) = x mod f`, and `gcd(f, x^(p^(n/q)) - x) = 1` for
+/// each prime `q` dividing `n`
+pub(crate) fn is_irreducible<F: PrimeField>(f: &[F], modulus_minus_one: &[bool]) -> bool {
+    let n = f.len() - 1;
+    let x = div_rem(&[F::zero(), F::one()], f).1;
+    let x_pow_p = mul_mod(&pow_mod(&x, modulus_minus_one, f), &x, f);
+
+    // `x^(p^k) mod f` for `k = 0..=n`, as `g(x)^p = g(x^p)` for any `g`
+    let mut frobenius = vec![x.clone()];
+    for k in 0..n {
+        // Horner's rule
+        let next = frobenius[k].iter().rev().fold(Vec::new(), |acc, c| {
+            sub(&mul_mod(&acc, &x_pow_p, f), &[-*c])
+        });
+        frobenius.push(next);
+    }
+
+    frobenius[n] == x
+        && (2..=n)
+            .filter(|q| n % q == 0 && (2..*q).all(|d| q % d != 0))
+            .all(|q| gcd(f, &sub(&frobenius[n / q], &x)).len() == 1)
+}
+
+// splits a product of distinct linear factors by `gcd(g, (x + d)^((p - 1) / 2) - 1)`,
+// which keeps the roots `r` for which `r + d` is a non-zero square
+fn split_linear_factors<F: PrimeField>(g: Vec<F>, half: &[bool], roots: &mut Vec<F>) {
+    match g.len() {
+        0 | 1 => return,
+        2 => {
+            roots.push(-g[0]);
+            return;
+        }
+        _ => {}
+    }
+
+    for d in 0u64.. {
+        let shifted = pow_mod(&[F::from(d), F::one()], half, &g);
+        let factor = gcd(&g, &sub(&shifted, &[F::one()]));
+        if factor.len() > 1 && factor.len() < g.len() {
+            let cofactor = div_rem(&g, &factor).0;
+            split_linear_factors(factor, half, roots);
+            split_linear_factors(cofactor, half, roots);
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_k256::FieldElement;
+    use ff::Field;
+
+    fn matrix(rows: &[&[u64]]) -> Matrix<FieldElement> {
+        rows.iter()
+            .map(|row| row.iter().map(|x| FieldElement::from(*x)).collect())
+            .collect()
+    }
+
+    #[test]
+    fn check_kernel_and_rank() {
+        let a = matrix(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]);
+        assert_eq!(rank(a.clone()), 2);
+
+        let kernel = kernel(a.clone(), 3);
+        assert_eq!(kernel.len(), 1);
+        assert!(mul_vec(&a, &kernel[0])
+            .iter()
+            .all(|x| bool::from(x.is_zero())));
+
+        assert_eq!(row_reduce(identity::<FieldElement>(3)), identity(3));
+    }
+
+    #[test]
+    fn check_charpoly_roots() {
+        // eigenvalues 2 and 5, and the roots of x^2 + 1, which are not in the
+        // field as p = 3 mod 4
+        let mut a = matrix(&[&[2, 0, 0, 0], &[1, 5, 0, 0], &[0, 0, 0, 1], &[0, 0, 0, 0]]);
+        a[3][2] = -FieldElement::one();
+
+        let f = charpoly(&a);
+        assert_eq!(f.len(), 5);
+        let mut roots = roots(&f, &to_bits(&-FieldElement::one()));
+        roots.sort_by_key(to_bits);
+        assert_eq!(
+            roots,
+            vec![FieldElement::from(2u64), FieldElement::from(5u64)]
+        );
+    }
+
+    #[test]
+    fn check_is_irreducible() {
+        let p_minus_one = to_bits(&-FieldElement::one());
+        let poly = |coeffs: &[u64]| {
+            coeffs
+                .iter()
+                .map(|c| FieldElement::from(*c))
+                .collect::<Vec<_>>()
+        };
+
+        // -1 is not a square as p = 3 mod 4, but 2 is as p = 7 mod 8
+        assert!(is_irreducible(&poly(&[1, 0, 1]), &p_minus_one));
+        assert!(!is_irreducible(
+            &[-FieldElement::from(2), 0.into(), 1.into()],
+            &p_minus_one
+        ));
+        // (x^2 + 1)^2 has no root but is not irreducible
+        assert!(!is_irreducible(&poly(&[1, 0, 2, 0, 1]), &p_minus_one));
+        assert!(is_irreducible(&poly(&[7, 1]), &p_minus_one));
+    }
+
+    #[test]
+    fn check_to_bits() {
+        let bits = to_bits(&FieldElement::from(6u64));
+        assert_eq!(bits.len(), 256);
+        assert_eq!(bits[253..], [true, true, false]);
+        assert!(bits[..253].iter().all(|b| !b));
+    }
+}
@@ -0,0 +1,335 @@
+//! Incremental binary Merkle tree over the secp256k1 Poseidon hash.
+//!
+//! The tree matches `MerkleTreeInclusionProof` of the membership circuits and
+//! zk-kit's `IncrementalMerkleTree` used by the JS library: empty leaves are
+//! zero, an inner node is `hash([left, right])`, and a path index is 0 when
+//! the node on the path is the left child.
+use crate::poseidon_k256::{hash, hash_pairs, FieldElement};
+use thiserror::Error;
+
+/// Depth of the trees of the membership circuits
+pub const MEMBERSHIP_TREE_DEPTH: usize = 20;
+
+/// Errors returned by Merkle tree operations
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum MerkleError {
+    /// the depth is zero or too large to address the leaves
+    #[error("invalid tree depth: {0}")]
+    InvalidDepth(usize),
+    /// all `2^depth` leaves are in use
+    #[error("the tree is full")]
+    TreeFull,
+    /// the index is not the one of an inserted leaf
+    #[error("leaf index {0} is out of range")]
+    IndexOutOfRange(usize),
+}
+
+/// An inclusion proof of `leaf` in the tree with the given `root`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    /// root of the tree the proof was made for
+    pub root: FieldElement,
+    /// the leaf whose inclusion is proven
+    pub leaf: FieldElement,
+    /// the sibling of the node on the path at each level, from the leaves up
+    pub siblings: Vec<FieldElement>,
+    /// 0 if the node on the path is a left child and 1 otherwise, from the leaves up
+    pub path_indices: Vec<u8>,
+}
+
+impl MerkleProof {
+    /// Checks that the siblings and path indices lead from `leaf` to `root`
+    pub fn verify(&self) -> bool {
+        if self.siblings.len() != self.path_indices.len() {
+            return false;
+        }
+
+        let mut node = self.leaf;
+        for (sibling, index) in self.siblings.iter().zip(self.path_indices.iter()) {
+            node = match index {
+                0 => hash(&[node, *sibling]),
+                1 => hash(&[*sibling, node]),
+                _ => return false,
+            };
+        }
+        node == self.root
+    }
+}
+
+/// Returns the roots of the empty subtrees of height `0..=depth`
+pub(crate) fn zeroes(depth: usize) -> Vec<FieldElement> {
+    let mut zeroes = Vec::with_capacity(depth + 1);
+    zeroes.push(FieldElement::zero());
+    for level in 0..depth {
+        zeroes.push(hash(&[zeroes[level], zeroes[level]]));
+    }
+    zeroes
+}
+
+/// A Merkle tree of fixed depth whose leaves are appended from the left
+#[derive(Clone, Debug)]
+pub struct IncrementalMerkleTree {
+    depth: usize,
+    /// `zeroes[level]` is the root of an empty subtree of height `level`
+    zeroes: Vec<FieldElement>,
+    /// the non-empty prefix of each level, the leaves first
+    nodes: Vec<Vec<FieldElement>>,
+    root: FieldElement,
+}
+
+impl IncrementalMerkleTree {
+    /// Creates an empty tree with `2^depth` leaves
+    pub fn new(depth: usize) -> Result<Self, MerkleError> {
+        if depth == 0 || depth >= usize::BITS as usize {
+            return Err(MerkleError::InvalidDepth(depth));
+        }
+
+        let zeroes = zeroes(depth);
+        Ok(IncrementalMerkleTree {
+            depth,
+            root: zeroes[depth],
+            zeroes,
+            nodes: vec![Vec::new(); depth],
+        })
+    }
+
+    /// Creates a tree holding `leaves`, hashing each inner node only once
+    pub fn from_leaves(depth: usize, leaves: &[FieldElement]) -> Result<Self, MerkleError> {
+        let mut tree = IncrementalMerkleTree::new(depth)?;
+        if leaves.len() > tree.capacity() {
+            return Err(MerkleError::TreeFull);
+        }
+
+        tree.nodes[0] = leaves.to_vec();
+        let mut level_nodes = leaves.to_vec();
+        for level in 0..depth {
+            let pairs: Vec<[FieldElement; 2]> = level_nodes
+                .chunks(2)
+                .map(|pair| [pair[0], *pair.get(1).unwrap_or(&tree.zeroes[level])])
+                .collect();
+            level_nodes = hash_pairs(&pairs);
+            if level + 1 < depth {
+                tree.nodes[level + 1] = level_nodes.clone();
+            }
+        }
+        tree.root = level_nodes.first().copied().unwrap_or(tree.zeroes[depth]);
+
+        Ok(tree)
+    }
+
+    /// Returns the depth of the tree
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves the tree can hold
+    pub fn capacity(&self) -> usize {
+        1 << self.depth
+    }
+
+    /// Returns the current root
+    pub fn root(&self) -> FieldElement {
+        self.root
+    }
+
+    /// Returns the leaves inserted so far, deleted ones being zero
+    pub fn leaves(&self) -> &[FieldElement] {
+        &self.nodes[0]
+    }
+
+    /// Returns the index of the first occurrence of `leaf`
+    pub fn index_of(&self, leaf: &FieldElement) -> Option<usize> {
+        self.leaves().iter().position(|l| l == leaf)
+    }
+
+    /// Appends `leaf` and returns its index
+    pub fn insert(&mut self, leaf: FieldElement) -> Result<usize, MerkleError> {
+        let index = self.leaves().len();
+        if index == self.capacity() {
+            return Err(MerkleError::TreeFull);
+        }
+
+        self.nodes[0].push(leaf);
+        self.update_path(index);
+        Ok(index)
+    }
+
+    /// Replaces the leaf at `index`
+    pub fn update(&mut self, index: usize, leaf: FieldElement) -> Result<(), MerkleError> {
+        if index >= self.leaves().len() {
+            return Err(MerkleError::IndexOutOfRange(index));
+        }
+
+        self.nodes[0][index] = leaf;
+        self.update_path(index);
+        Ok(())
+    }
+
+    /// Sets the leaf at `index` back to zero; the indices of the other leaves
+    /// do not change
+    pub fn delete(&mut self, index: usize) -> Result<(), MerkleError> {
+        self.update(index, FieldElement::zero())
+    }
+
+    /// Creates an inclusion proof of the leaf at `index`
+    pub fn create_proof(&self, index: usize) -> Result<MerkleProof, MerkleError> {
+        if index >= self.leaves().len() {
+            return Err(MerkleError::IndexOutOfRange(index));
+        }
+
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut path_indices = Vec::with_capacity(self.depth);
+        let mut node_index = index;
+        for level in 0..self.depth {
+            siblings.push(self.node(level, node_index ^ 1));
+            path_indices.push((node_index & 1) as u8);
+            node_index >>= 1;
+        }
+
+        Ok(MerkleProof {
+            root: self.root,
+            leaf: self.nodes[0][index],
+            siblings,
+            path_indices,
+        })
+    }
+
+    /// Checks that `proof` is a valid inclusion proof for the current root
+    pub fn verify_proof(&self, proof: &MerkleProof) -> bool {
+        proof.root == self.root && proof.siblings.len() == self.depth && proof.verify()
+    }
+
+    fn node(&self, level: usize, index: usize) -> FieldElement {
+        self.nodes[level]
+            .get(index)
+            .copied()
+            .unwrap_or(self.zeroes[level])
+    }
+
+    // rehashes the nodes from the leaf at `index` up to the root
+    fn update_path(&mut self, index: usize) {
+        let mut node_index = index;
+        for level in 0..self.depth {
+            let left = node_index & !1;
+            let node = hash(&[self.node(level, left), self.node(level, left + 1)]);
+            node_index >>= 1;
+
+            if level + 1 == self.depth {
+                self.root = node;
+            } else {
+                let parents = &mut self.nodes[level + 1];
+                if node_index == parents.len() {
+                    parents.push(node);
+                } else {
+                    parents[node_index] = node;
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leaves(n: u64) -> Vec<FieldElement> {
+        (1..=n).map(FieldElement::from).collect()
+    }
+
+    #[test]
+    fn check_empty_tree() {
+        let tree = IncrementalMerkleTree::new(3).unwrap();
+        let mut zero = FieldElement::zero();
+        for _ in 0..3 {
+            zero = hash(&[zero, zero]);
+        }
+        assert_eq!(tree.root(), zero);
+        assert_eq!(
+            IncrementalMerkleTree::new(0).unwrap_err(),
+            MerkleError::InvalidDepth(0)
+        );
+    }
+
+    #[test]
+    fn check_insert() {
+        let mut tree = IncrementalMerkleTree::new(2).unwrap();
+        let [a, b, c] = [1u64, 2, 3].map(FieldElement::from);
+        tree.insert(a).unwrap();
+        tree.insert(b).unwrap();
+        assert_eq!(tree.insert(c), Ok(2));
+
+        let zero = FieldElement::zero();
+        let expected = hash(&[hash(&[a, b]), hash(&[c, zero])]);
+        assert_eq!(tree.root(), expected);
+
+        tree.insert(a).unwrap();
+        assert_eq!(tree.insert(a), Err(MerkleError::TreeFull));
+        assert_eq!(tree.index_of(&c), Some(2));
+    }
+
+    #[test]
+    fn check_from_leaves() {
+        for n in [0, 1, 2, 5, 8, 13] {
+            let mut tree = IncrementalMerkleTree::new(4).unwrap();
+            for leaf in leaves(n) {
+                tree.insert(leaf).unwrap();
+            }
+
+            let bulk = IncrementalMerkleTree::from_leaves(4, &leaves(n)).unwrap();
+            assert_eq!(bulk.root(), tree.root());
+            assert_eq!(bulk.nodes, tree.nodes);
+        }
+        assert_eq!(
+            IncrementalMerkleTree::from_leaves(2, &leaves(5)).unwrap_err(),
+            MerkleError::TreeFull
+        );
+    }
+
+    #[test]
+    fn check_update_and_delete() {
+        let mut tree = IncrementalMerkleTree::from_leaves(4, &leaves(6)).unwrap();
+
+        let mut updated = leaves(6);
+        updated[3] = FieldElement::from(42u64);
+        tree.update(3, updated[3]).unwrap();
+        assert_eq!(
+            tree.root(),
+            IncrementalMerkleTree::from_leaves(4, &updated)
+                .unwrap()
+                .root()
+        );
+
+        updated[5] = FieldElement::zero();
+        tree.delete(5).unwrap();
+        assert_eq!(
+            tree.root(),
+            IncrementalMerkleTree::from_leaves(4, &updated)
+                .unwrap()
+                .root()
+        );
+        assert_eq!(tree.leaves().len(), 6);
+
+        assert_eq!(tree.delete(6), Err(MerkleError::IndexOutOfRange(6)));
+    }
+
+    #[test]
+    fn check_proofs() {
+        let tree = IncrementalMerkleTree::from_leaves(MEMBERSHIP_TREE_DEPTH, &leaves(5)).unwrap();
+
+        for index in 0..5 {
+            let proof = tree.create_proof(index).unwrap();
+            assert_eq!(proof.leaf, leaves(5)[index]);
+            assert_eq!(proof.siblings.len(), MEMBERSHIP_TREE_DEPTH);
+            assert!(tree.verify_proof(&proof));
+
+            let mut tampered = proof.clone();
+            tampered.leaf = FieldElement::from(99u64);
+            assert!(!tree.verify_proof(&tampered));
+
+            let mut tampered = proof.clone();
+            tampered.path_indices[0] ^= 1;
+            assert!(!tree.verify_proof(&tampered));
+        }
+        assert!(tree.create_proof(5).is_err());
+    }
+}
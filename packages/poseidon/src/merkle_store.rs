@@ -0,0 +1,730 @@
+//! Disk-backed, append-only version of `merkle::IncrementalMerkleTree` for
+//! groups too large to rebuild on every start.
+//!
+//! A store is a directory with two files:
+//!
+//! * `nodes`: every node of the tree, written once when its subtree is
+//!   complete. Appending leaf `k` writes the leaf followed by the parents it
+//!   completes, so the position of any complete node follows from its level
+//!   and index, and no index has to be kept.
+//! * `roots`: the root history, one `(number of leaves, root)` entry per
+//!   append. An append is committed once its entry is written; nodes of an
+//!   interrupted append are discarded when the store is opened.
+//!
+//! Nodes that are not complete yet, and the roots of earlier snapshots, are
+//! recomputed from the complete nodes with `O(depth)` hashes.
+use crate::merkle::{zeroes, MerkleError, MerkleProof};
+use crate::poseidon_k256::{hash, FieldElement};
+use secq256k1::field::BaseField;
+use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::Path;
+use thiserror::Error;
+
+const NODES_MAGIC: &[u8; 4] = b"PMTN";
+const ROOTS_MAGIC: &[u8; 4] = b"PMTR";
+const VERSION: u8 = 1;
+const HEADER_LEN: u64 = 8;
+const NODE_LEN: u64 = 32;
+const ROOT_ENTRY_LEN: u64 = 8 + 32;
+
+/// Errors returned by `MerkleStore`
+#[derive(Error, Debug)]
+pub enum StoreError {
+    /// reading or writing the store failed
+    #[error("I/O error: {0}")]
+    Io(#[from] io::Error),
+    /// the files are not a valid store, or do not agree with each other
+    #[error("corrupt Merkle store: {0}")]
+    Corrupt(&'static str),
+    /// the operation is not valid for the tree
+    #[error(transparent)]
+    Tree(#[from] MerkleError),
+    /// the leaf was never appended
+    #[error("leaf not found")]
+    LeafNotFound,
+}
+
+/// A Merkle tree whose nodes and root history are kept on disk
+pub struct MerkleStore {
+    depth: usize,
+    zeroes: Vec<FieldElement>,
+    nodes: File,
+    roots: File,
+    len: usize,
+    /// the last complete node at an even index of each level; the left
+    /// sibling of the next node completed on that level
+    filled: Vec<FieldElement>,
+    root: FieldElement,
+    history: Vec<(usize, FieldElement)>,
+    leaf_index: HashMap<[u8; 32], usize>,
+}
+
+impl MerkleStore {
+    /// Creates an empty store of the given depth in the directory `dir`,
+    /// which is created if needed. Fails if a store already exists there
+    pub fn create(dir: &Path, depth: usize) -> Result<Self, StoreError> {
+        if depth == 0 || depth >= 64 || depth >= usize::BITS as usize {
+            return Err(MerkleError::InvalidDepth(depth).into());
+        }
+
+        std::fs::create_dir_all(dir)?;
+        let header = |magic: &[u8; 4]| {
+            [
+                magic[0],
+                magic[1],
+                magic[2],
+                magic[3],
+                VERSION,
+                depth as u8,
+                0,
+                0,
+            ]
+        };
+        let mut nodes = open_file(&dir.join("nodes"), true)?;
+        nodes.write_all(&header(NODES_MAGIC))?;
+        nodes.sync_data()?;
+        let mut roots = open_file(&dir.join("roots"), true)?;
+        roots.write_all(&header(ROOTS_MAGIC))?;
+        roots.sync_data()?;
+
+        let zeroes = zeroes(depth);
+        Ok(MerkleStore {
+            depth,
+            root: zeroes[depth],
+            filled: zeroes[..depth].to_vec(),
+            zeroes,
+            nodes,
+            roots,
+            len: 0,
+            history: Vec::new(),
+            leaf_index: HashMap::new(),
+        })
+    }
+
+    /// Opens the store in `dir`. The headers, the sizes of the files and the
+    /// order of the root history are checked, and the latest root is
+    /// recomputed from the stored nodes and compared with the recorded one.
+    /// Use `verify_integrity` to also check every stored node
+    pub fn open(dir: &Path) -> Result<Self, StoreError> {
+        let nodes = open_file(&dir.join("nodes"), false)?;
+        let roots = open_file(&dir.join("roots"), false)?;
+        let depth = read_header(&nodes, NODES_MAGIC)?;
+        if read_header(&roots, ROOTS_MAGIC)? != depth {
+            return Err(StoreError::Corrupt("nodes and roots have different depths"));
+        }
+        let zeroes = zeroes(depth);
+
+        // a torn entry at the end of the history is an uncommitted append
+        let roots_len = roots.metadata()?.len();
+        let num_entries = (roots_len - HEADER_LEN) / ROOT_ENTRY_LEN;
+        roots.set_len(HEADER_LEN + num_entries * ROOT_ENTRY_LEN)?;
+
+        let mut history = Vec::with_capacity(num_entries as usize);
+        let mut reader = BufReader::new(&roots);
+        reader.seek(SeekFrom::Start(HEADER_LEN))?;
+        for _ in 0..num_entries {
+            let mut entry = [0u8; ROOT_ENTRY_LEN as usize];
+            reader.read_exact(&mut entry)?;
+            let len = u64::from_le_bytes(entry[..8].try_into().unwrap()) as usize;
+            let root = decode_node(entry[8..].try_into().unwrap())?;
+            if history.last().map_or(len == 0, |(last, _)| len <= *last) || len > 1 << depth {
+                return Err(StoreError::Corrupt("root history is out of order"));
+            }
+            history.push((len, root));
+        }
+        let (len, root) = history.last().copied().unwrap_or((0, zeroes[depth]));
+
+        // so are nodes past the last committed append
+        let nodes_len = HEADER_LEN + NODE_LEN * num_records(len);
+        if nodes.metadata()?.len() < nodes_len {
+            return Err(StoreError::Corrupt("nodes are missing"));
+        }
+        nodes.set_len(nodes_len)?;
+
+        let mut store = MerkleStore {
+            depth,
+            filled: zeroes[..depth].to_vec(),
+            zeroes,
+            nodes,
+            roots,
+            len: 0,
+            root,
+            history,
+            leaf_index: HashMap::new(),
+        };
+        store.load(len)?;
+        if store.root_at(len)? != root {
+            return Err(StoreError::Corrupt(
+                "recorded root does not match the nodes",
+            ));
+        }
+
+        Ok(store)
+    }
+
+    /// Returns the depth of the tree
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns true if no leaf was appended yet
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the current root
+    pub fn root(&self) -> FieldElement {
+        self.root
+    }
+
+    /// Returns the root history as `(number of leaves, root)` pairs, oldest first
+    pub fn history(&self) -> &[(usize, FieldElement)] {
+        &self.history
+    }
+
+    /// Returns true if `root` is the root of some snapshot in the history
+    pub fn is_known_root(&self, root: &FieldElement) -> bool {
+        self.history.iter().any(|(_, r)| r == root)
+    }
+
+    /// Returns the index of the first occurrence of `leaf`
+    pub fn index_of(&self, leaf: &FieldElement) -> Option<usize> {
+        self.leaf_index.get(&leaf.to_bytes()).copied()
+    }
+
+    /// Returns the leaf at `index`
+    pub fn leaf(&self, index: usize) -> Result<FieldElement, StoreError> {
+        if index >= self.len {
+            return Err(MerkleError::IndexOutOfRange(index).into());
+        }
+        self.read_node(0, index)
+    }
+
+    /// Appends `leaf`, records the new root and returns the leaf's index
+    pub fn append(&mut self, leaf: FieldElement) -> Result<usize, StoreError> {
+        let index = self.len;
+        self.extend(&[leaf])?;
+        Ok(index)
+    }
+
+    /// Appends `leaves` and records a single root for all of them. If writing
+    /// fails, the store is left as it was before the call
+    pub fn extend(&mut self, leaves: &[FieldElement]) -> Result<(), StoreError> {
+        if leaves.len() > (1 << self.depth) - self.len {
+            return Err(MerkleError::TreeFull.into());
+        }
+        if leaves.is_empty() {
+            return Ok(());
+        }
+
+        // also drops what a failed append could not truncate
+        self.truncate()?;
+        let (filled, root) = match self.write_append(leaves) {
+            Ok(staged) => staged,
+            Err(err) => {
+                let _ = self.truncate();
+                return Err(err);
+            }
+        };
+
+        for (k, leaf) in leaves.iter().enumerate() {
+            self.leaf_index
+                .entry(leaf.to_bytes())
+                .or_insert(self.len + k);
+        }
+        self.len += leaves.len();
+        self.filled = filled;
+        self.root = root;
+        self.history.push((self.len, self.root));
+        Ok(())
+    }
+
+    /// Creates an inclusion proof of the leaf at `index` for the current root
+    pub fn create_proof(&self, index: usize) -> Result<MerkleProof, StoreError> {
+        self.create_proof_at(index, self.len)
+    }
+
+    /// Creates an inclusion proof of the first occurrence of `leaf`
+    pub fn create_proof_for_leaf(&self, leaf: &FieldElement) -> Result<MerkleProof, StoreError> {
+        match self.index_of(leaf) {
+            Some(index) => self.create_proof(index),
+            None => Err(StoreError::LeafNotFound),
+        }
+    }
+
+    /// Returns the root the tree had when it held its first `len` leaves
+    pub fn root_at(&self, len: usize) -> Result<FieldElement, StoreError> {
+        if len > self.len {
+            return Err(MerkleError::IndexOutOfRange(len).into());
+        }
+        Ok(self.frontier(len)?[self.depth])
+    }
+
+    /// Creates an inclusion proof of the leaf at `index` for the root the tree
+    /// had when it held its first `len` leaves
+    pub fn create_proof_at(&self, index: usize, len: usize) -> Result<MerkleProof, StoreError> {
+        if index >= len || len > self.len {
+            return Err(MerkleError::IndexOutOfRange(index).into());
+        }
+
+        let frontier = self.frontier(len)?;
+        let mut siblings = Vec::with_capacity(self.depth);
+        let mut path_indices = Vec::with_capacity(self.depth);
+        let levels = frontier.iter().zip(&self.zeroes).take(self.depth);
+        for (level, (last_node, zero)) in levels.enumerate() {
+            let sibling = (index >> level) ^ 1;
+            let last = (len - 1) >> level;
+            siblings.push(match sibling {
+                s if s < last => self.read_node(level, s)?,
+                s if s == last => *last_node,
+                _ => *zero,
+            });
+            path_indices.push(((index >> level) & 1) as u8);
+        }
+
+        Ok(MerkleProof {
+            root: frontier[self.depth],
+            leaf: self.read_node(0, index)?,
+            siblings,
+            path_indices,
+        })
+    }
+
+    /// Rehashes every stored node and recomputes every root of the history
+    pub fn verify_integrity(&self) -> Result<(), StoreError> {
+        for level in 1..=self.depth {
+            for index in 0..self.len >> level {
+                let children = [
+                    self.read_node(level - 1, 2 * index)?,
+                    self.read_node(level - 1, 2 * index + 1)?,
+                ];
+                if hash(&children) != self.read_node(level, index)? {
+                    return Err(StoreError::Corrupt("node does not match its children"));
+                }
+            }
+        }
+
+        for (len, root) in &self.history {
+            if self.root_at(*len)? != *root {
+                return Err(StoreError::Corrupt(
+                    "recorded root does not match the nodes",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // writes the nodes and the root entry of an append, and returns the
+    // `filled` nodes and the root after it. The append is committed once its
+    // root entry is synced, and the in-memory state is only updated after that
+    fn write_append(
+        &self,
+        leaves: &[FieldElement],
+    ) -> Result<(Vec<FieldElement>, FieldElement), StoreError> {
+        let mut filled = self.filled.clone();
+        let mut records = Vec::new();
+        for (k, leaf) in leaves.iter().enumerate() {
+            Self::push_leaf(&mut filled, self.len + k, *leaf, &mut records);
+            if records.len() >= 1 << 20 {
+                (&self.nodes).write_all(&records)?;
+                records.clear();
+            }
+        }
+        (&self.nodes).write_all(&records)?;
+        self.nodes.sync_data()?;
+
+        let len = self.len + leaves.len();
+        let root = root_of(&filled, &self.zeroes, len, leaves[leaves.len() - 1]);
+        let mut entry = Vec::with_capacity(ROOT_ENTRY_LEN as usize);
+        entry.extend_from_slice(&(len as u64).to_le_bytes());
+        entry.extend_from_slice(&root.to_bytes());
+        (&self.roots).write_all(&entry)?;
+        self.roots.sync_data()?;
+        Ok((filled, root))
+    }
+
+    // cuts the files back to the committed appends
+    fn truncate(&self) -> Result<(), StoreError> {
+        let nodes_len = HEADER_LEN + NODE_LEN * num_records(self.len);
+        let roots_len = HEADER_LEN + ROOT_ENTRY_LEN * self.history.len() as u64;
+        for (file, len) in [(&self.nodes, nodes_len), (&self.roots, roots_len)] {
+            if file.metadata()?.len() != len {
+                file.set_len(len)?;
+            }
+        }
+        Ok(())
+    }
+
+    // appends leaf `index` and the parents it completes to `records`
+    fn push_leaf(
+        filled: &mut [FieldElement],
+        mut index: usize,
+        leaf: FieldElement,
+        records: &mut Vec<u8>,
+    ) {
+        let mut node = leaf;
+        records.extend_from_slice(&node.to_bytes());
+        for left in filled.iter_mut() {
+            if index & 1 == 0 {
+                *left = node;
+                break;
+            }
+            node = hash(&[*left, node]);
+            index >>= 1;
+            records.extend_from_slice(&node.to_bytes());
+        }
+    }
+
+    // rebuilds the in-memory state from the first `len` leaves on disk
+    fn load(&mut self, len: usize) -> Result<(), StoreError> {
+        let mut reader = BufReader::new(&self.nodes);
+        reader.seek(SeekFrom::Start(HEADER_LEN))?;
+
+        let mut read = || -> Result<FieldElement, StoreError> {
+            let mut bytes = [0u8; NODE_LEN as usize];
+            reader.read_exact(&mut bytes)?;
+            decode_node(&bytes)
+        };
+        for k in 0..len {
+            let leaf = read()?;
+            self.leaf_index.entry(leaf.to_bytes()).or_insert(k);
+
+            let mut node = leaf;
+            let mut index = k;
+            for level in 0..self.depth {
+                if index & 1 == 0 {
+                    self.filled[level] = node;
+                    break;
+                }
+                node = read()?;
+                index >>= 1;
+            }
+        }
+        self.len = len;
+        Ok(())
+    }
+
+    // the last non-empty node of each level of the tree with `len` leaves
+    fn frontier(&self, len: usize) -> Result<Vec<FieldElement>, StoreError> {
+        if len == 0 {
+            return Ok(self.zeroes.clone());
+        }
+
+        let mut frontier = Vec::with_capacity(self.depth + 1);
+        frontier.push(self.read_node(0, len - 1)?);
+        for level in 1..=self.depth {
+            let child = (len - 1) >> (level - 1);
+            frontier.push(if child & 1 == 1 {
+                hash(&[self.read_node(level - 1, child - 1)?, frontier[level - 1]])
+            } else {
+                hash(&[frontier[level - 1], self.zeroes[level - 1]])
+            });
+        }
+        Ok(frontier)
+    }
+
+    // reads the complete node at `index` on `level`
+    fn read_node(&self, level: usize, index: usize) -> Result<FieldElement, StoreError> {
+        // the node is completed by the last leaf below it, as the `level`-th
+        // record written for that leaf
+        let k = (((index as u64) + 1) << level) - 1;
+        let offset = HEADER_LEN + NODE_LEN * (num_records_before(k) + level as u64);
+
+        let mut bytes = [0u8; NODE_LEN as usize];
+        let mut nodes = &self.nodes;
+        nodes.seek(SeekFrom::Start(offset))?;
+        nodes.read_exact(&mut bytes)?;
+        decode_node(&bytes)
+    }
+}
+
+// the root of a non-empty tree with `len` leaves, given its `filled` nodes and
+// its last leaf
+fn root_of(
+    filled: &[FieldElement],
+    zeroes: &[FieldElement],
+    len: usize,
+    last_leaf: FieldElement,
+) -> FieldElement {
+    let mut node = last_leaf;
+    let mut index = len - 1;
+    for (filled, zero) in filled.iter().zip(zeroes) {
+        node = if index & 1 == 1 {
+            hash(&[*filled, node])
+        } else {
+            hash(&[node, *zero])
+        };
+        index >>= 1;
+    }
+    node
+}
+
+// number of nodes written for the leaves before leaf `k`
+fn num_records_before(k: u64) -> u64 {
+    2 * k - k.count_ones() as u64
+}
+
+// number of nodes written for a tree with `len` leaves
+fn num_records(len: usize) -> u64 {
+    num_records_before(len as u64)
+}
+
+fn open_file(path: &Path, create: bool) -> Result<File, StoreError> {
+    Ok(OpenOptions::new()
+        .read(true)
+        .append(true)
+        .create_new(create)
+        .open(path)?)
+}
+
+fn read_header(mut file: &File, magic: &[u8; 4]) -> Result<usize, StoreError> {
+    let mut header = [0u8; HEADER_LEN as usize];
+    file.seek(SeekFrom::Start(0))?;
+    file.read_exact(&mut header)
+        .map_err(|_| StoreError::Corrupt("truncated header"))?;
+
+    if &header[..4] != magic {
+        return Err(StoreError::Corrupt("bad magic"));
+    }
+    if header[4] != VERSION {
+        return Err(StoreError::Corrupt("unsupported version"));
+    }
+    let depth = header[5] as usize;
+    if depth == 0 || depth >= 64 || depth >= usize::BITS as usize {
+        return Err(StoreError::Corrupt("invalid depth"));
+    }
+    Ok(depth)
+}
+
+fn decode_node(bytes: &[u8; 32]) -> Result<FieldElement, StoreError> {
+    Option::from(FieldElement::from_bytes(bytes))
+        .ok_or(StoreError::Corrupt("node is not a field element"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::merkle::{IncrementalMerkleTree, MEMBERSHIP_TREE_DEPTH};
+    use std::path::PathBuf;
+
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!(
+                "poseidon-merkle-store-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&dir);
+            TempDir(dir)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn leaves(n: u64) -> Vec<FieldElement> {
+        (1..=n).map(FieldElement::from).collect()
+    }
+
+    #[test]
+    fn check_matches_in_memory_tree() {
+        let dir = TempDir::new("matches");
+        let mut store = MerkleStore::create(&dir.0, 4).unwrap();
+        let mut tree = IncrementalMerkleTree::new(4).unwrap();
+        assert_eq!(store.root(), tree.root());
+
+        for leaf in leaves(11) {
+            assert_eq!(store.append(leaf).unwrap(), tree.insert(leaf).unwrap());
+            assert_eq!(store.root(), tree.root());
+        }
+        store.extend(&leaves(16)[11..]).unwrap();
+        for leaf in &leaves(16)[11..] {
+            tree.insert(*leaf).unwrap();
+        }
+        assert_eq!(store.root(), tree.root());
+        assert!(matches!(
+            store.append(FieldElement::from(1u64)),
+            Err(StoreError::Tree(MerkleError::TreeFull))
+        ));
+
+        for index in 0..16 {
+            let proof = store.create_proof(index).unwrap();
+            assert_eq!(proof, tree.create_proof(index).unwrap());
+        }
+        let leaf = FieldElement::from(7u64);
+        assert_eq!(store.index_of(&leaf), Some(6));
+        assert_eq!(store.create_proof_for_leaf(&leaf).unwrap().leaf, leaf);
+        assert!(matches!(
+            store.create_proof_for_leaf(&FieldElement::from(17u64)),
+            Err(StoreError::LeafNotFound)
+        ));
+        store.verify_integrity().unwrap();
+    }
+
+    #[test]
+    fn check_snapshots() {
+        let dir = TempDir::new("snapshots");
+        let mut store = MerkleStore::create(&dir.0, 5).unwrap();
+        store.extend(&leaves(3)).unwrap();
+        store.append(FieldElement::from(4u64)).unwrap();
+        store.extend(&leaves(13)[4..]).unwrap();
+
+        let lens = store
+            .history()
+            .iter()
+            .map(|(len, _)| *len)
+            .collect::<Vec<_>>();
+        assert_eq!(lens, [3, 4, 13]);
+        for (len, root) in store.history() {
+            let tree = IncrementalMerkleTree::from_leaves(5, &leaves(*len as u64)).unwrap();
+            assert_eq!(*root, tree.root());
+            assert_eq!(store.root_at(*len).unwrap(), tree.root());
+            assert!(store.is_known_root(root));
+
+            // proofs against an old root remain valid for it
+            for index in 0..*len {
+                let proof = store.create_proof_at(index, *len).unwrap();
+                assert_eq!(proof, tree.create_proof(index).unwrap());
+            }
+        }
+        assert!(store.create_proof_at(3, 3).is_err());
+    }
+
+    #[test]
+    fn check_reopen() {
+        let dir = TempDir::new("reopen");
+        let mut store = MerkleStore::create(&dir.0, MEMBERSHIP_TREE_DEPTH).unwrap();
+        store.extend(&leaves(37)).unwrap();
+        let root = store.root();
+        drop(store);
+        assert!(MerkleStore::create(&dir.0, MEMBERSHIP_TREE_DEPTH).is_err());
+
+        let mut store = MerkleStore::open(&dir.0).unwrap();
+        assert_eq!(store.len(), 37);
+        assert_eq!(store.root(), root);
+        assert_eq!(store.index_of(&FieldElement::from(37u64)), Some(36));
+
+        // appending after reopening continues the same tree
+        store.append(FieldElement::from(38u64)).unwrap();
+        let tree = IncrementalMerkleTree::from_leaves(MEMBERSHIP_TREE_DEPTH, &leaves(38)).unwrap();
+        assert_eq!(store.root(), tree.root());
+        assert_eq!(
+            store.create_proof(20).unwrap(),
+            tree.create_proof(20).unwrap()
+        );
+    }
+
+    #[test]
+    fn check_interrupted_append() {
+        let dir = TempDir::new("interrupted");
+        let mut store = MerkleStore::create(&dir.0, 4).unwrap();
+        store.extend(&leaves(5)).unwrap();
+        let root = store.root();
+        drop(store);
+
+        // nodes written without a root entry, and a torn root entry
+        let mut nodes = OpenOptions::new()
+            .append(true)
+            .open(dir.0.join("nodes"))
+            .unwrap();
+        nodes.write_all(&[1u8; 64]).unwrap();
+        let mut roots = OpenOptions::new()
+            .append(true)
+            .open(dir.0.join("roots"))
+            .unwrap();
+        roots.write_all(&[6u8; 10]).unwrap();
+
+        let mut store = MerkleStore::open(&dir.0).unwrap();
+        assert_eq!(store.root(), root);
+        store.append(FieldElement::from(6u64)).unwrap();
+        store.verify_integrity().unwrap();
+        drop(store);
+        assert_eq!(MerkleStore::open(&dir.0).unwrap().len(), 6);
+    }
+
+    #[test]
+    fn check_failed_append() {
+        let dir = TempDir::new("failed");
+        let mut store = MerkleStore::create(&dir.0, 4).unwrap();
+        store.extend(&leaves(5)).unwrap();
+        let root = store.root();
+        let nodes_len = store.nodes.metadata().unwrap().len();
+
+        // the root entry cannot be written through a read-only handle
+        let roots = std::mem::replace(&mut store.roots, File::open(dir.0.join("roots")).unwrap());
+        assert!(matches!(
+            store.extend(&leaves(8)[5..]),
+            Err(StoreError::Io(_))
+        ));
+        assert_eq!((store.len(), store.root()), (5, root));
+        assert_eq!(store.history().len(), 1);
+        assert_eq!(store.index_of(&FieldElement::from(6u64)), None);
+        assert_eq!(store.nodes.metadata().unwrap().len(), nodes_len);
+
+        store.roots = roots;
+        store.extend(&leaves(8)[5..]).unwrap();
+        let tree = IncrementalMerkleTree::from_leaves(4, &leaves(8)).unwrap();
+        assert_eq!(store.root(), tree.root());
+        assert_eq!(store.index_of(&FieldElement::from(6u64)), Some(5));
+        store.verify_integrity().unwrap();
+        drop(store);
+        assert_eq!(MerkleStore::open(&dir.0).unwrap().root(), tree.root());
+    }
+
+    #[test]
+    fn check_corruption() {
+        let dir = TempDir::new("corrupt");
+        let mut store = MerkleStore::create(&dir.0, 4).unwrap();
+        store.extend(&leaves(6)).unwrap();
+        drop(store);
+
+        let path = dir.0.join("nodes");
+        let original = std::fs::read(&path).unwrap();
+
+        // a changed last leaf changes the root
+        let mut bytes = original.clone();
+        bytes[(HEADER_LEN + NODE_LEN * num_records_before(5)) as usize] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            MerkleStore::open(&dir.0),
+            Err(StoreError::Corrupt(_))
+        ));
+
+        // any other changed node is caught by the full check
+        let mut bytes = original.clone();
+        bytes[HEADER_LEN as usize] ^= 1;
+        std::fs::write(&path, &bytes).unwrap();
+        let store = MerkleStore::open(&dir.0).unwrap();
+        assert!(matches!(
+            store.verify_integrity(),
+            Err(StoreError::Corrupt(_))
+        ));
+        drop(store);
+
+        std::fs::write(&path, &original[..original.len() - 32]).unwrap();
+        assert!(matches!(
+            MerkleStore::open(&dir.0),
+            Err(StoreError::Corrupt(_))
+        ));
+
+        let mut bytes = original;
+        bytes[0] = b'X';
+        std::fs::write(&path, &bytes).unwrap();
+        assert!(matches!(
+            MerkleStore::open(&dir.0),
+            Err(StoreError::Corrupt(_))
+        ));
+    }
+}
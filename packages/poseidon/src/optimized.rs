@@ -0,0 +1,211 @@
+//! The Poseidon permutation with cheap partial rounds, as in appendix B of the
+//! Poseidon paper.
+//!
+//! A partial round only applies the S-box to `state[0]`, so most of its work
+//! is the MDS multiplication. Two rewrites bring it down to `O(t)`:
+//!
+//! * the round constants of a partial round are moved through the MDS matrix
+//!   into the previous round: the part that lands on `state[1..]` commutes
+//!   with the S-box and joins the previous constants, and only a scalar for
+//!   `state[0]` remains, added after the S-box;
+//! * the MDS matrix `M` is factored into `M'' * M'` where `M' = diag(1, M^)`
+//!   commutes with the S-box and is merged into the matrix of the previous
+//!   round, and `M''` has non-zero elements only in its first row, first
+//!   column and diagonal.
+//!
+//! Applied from the last partial round backwards, only the first partial round
+//! keeps a constant vector, and the matrices merged out of the partial rounds
+//! end up in the last full round before them.
+use crate::linalg::{self, Matrix};
+use crate::PoseidonConstants;
+use ff::PrimeField;
+
+/// `M'' = [[m_00, row], [column, I]]`
+#[derive(Clone, Debug)]
+struct SparseMatrix<F: PrimeField> {
+    m_00: F,
+    row: Vec<F>,
+    column: Vec<F>,
+}
+
+impl<F: PrimeField> SparseMatrix<F> {
+    fn mul_assign(&self, state: &mut [F]) {
+        let first = state[0];
+        state[0] = self.m_00 * first
+            + self
+                .row
+                .iter()
+                .zip(&state[1..])
+                .fold(F::zero(), |sum, (m, s)| sum + *m * s);
+        for (element, m) in state[1..].iter_mut().zip(&self.column) {
+            *element += *m * first;
+        }
+    }
+}
+
+/// Constants of the Poseidon permutation rearranged for cheap partial rounds.
+/// The permutation is the one of the original constants, which are kept.
+pub struct OptimizedConstants<F: PrimeField> {
+    constants: PoseidonConstants<F>,
+    /// the matrix of the last full round before the partial rounds, `M'_0 * M`
+    pre_partial_matrix: Matrix<F>,
+    /// the constants added before the S-box of the first partial round
+    first_partial_keys: Vec<F>,
+    /// the constants added after the S-box of each partial round but the last
+    partial_keys: Vec<F>,
+    /// the sparse matrix of each partial round
+    sparse_matrices: Vec<SparseMatrix<F>>,
+}
+
+impl<F: PrimeField> OptimizedConstants<F> {
+    /// Precomputes the partial rounds of `constants`.
+    /// Panics if the round numbers are not positive or the matrix is not MDS.
+    pub fn new(constants: PoseidonConstants<F>) -> Self {
+        let t = constants.width();
+        let half = constants.num_full_rounds / 2;
+        let num_partial_rounds = constants.num_partial_rounds;
+        assert!(
+            half > 0 && num_partial_rounds > 0,
+            "expected both full and partial rounds"
+        );
+
+        let mds = &constants.mds_matrix;
+        let mds_inv = linalg::invert(mds).expect("the MDS matrix is not invertible");
+
+        // move the constants of partial rounds backwards through `M`
+        let mut keys: Matrix<F> = constants
+            .round_keys
+            .chunks_exact(t)
+            .skip(half)
+            .take(num_partial_rounds)
+            .map(|keys| keys.to_vec())
+            .collect();
+        let mut partial_keys = vec![F::zero(); num_partial_rounds - 1];
+        for round in (1..num_partial_rounds).rev() {
+            let moved = linalg::mul_vec(&mds_inv, &keys[round]);
+            partial_keys[round - 1] = moved[0];
+            for (key, m) in keys[round - 1][1..].iter_mut().zip(&moved[1..]) {
+                *key += m;
+            }
+        }
+
+        // factor the matrix of each partial round, merged with the `M'` of
+        // the next one, into `M'' * M'`
+        let mut sparse_matrices = Vec::with_capacity(num_partial_rounds);
+        let mut matrix = mds.clone();
+        let mut m_prime = Vec::new();
+        for _ in 0..num_partial_rounds {
+            let m_hat: Matrix<F> = matrix[1..].iter().map(|row| row[1..].to_vec()).collect();
+            let m_hat_inv = linalg::invert(&m_hat).expect("the MDS matrix is not MDS");
+            sparse_matrices.push(SparseMatrix {
+                m_00: matrix[0][0],
+                // `row * M^-1`
+                row: linalg::mul_vec(&linalg::transpose(&m_hat_inv), &matrix[0][1..]),
+                column: matrix[1..].iter().map(|row| row[0]).collect(),
+            });
+
+            m_prime = expand(&m_hat);
+            matrix = linalg::mul(&m_prime, mds);
+        }
+        sparse_matrices.reverse();
+
+        OptimizedConstants {
+            first_partial_keys: linalg::mul_vec(&m_prime, &keys[0]),
+            pre_partial_matrix: matrix,
+            partial_keys,
+            sparse_matrices,
+            constants,
+        }
+    }
+
+    /// Returns the constants the permutation is defined by
+    pub fn constants(&self) -> &PoseidonConstants<F> {
+        &self.constants
+    }
+
+    /// Returns the width of the permutation
+    pub fn width(&self) -> usize {
+        self.constants.width()
+    }
+
+    /// Applies the permutation to `state`, whose length must be the width
+    pub fn permute(&self, state: &mut [F]) {
+        let t = self.width();
+        assert_eq!(state.len(), t, "state and constants widths differ");
+
+        let half = self.constants.num_full_rounds / 2;
+        let mut full_round_keys = self.constants.round_keys.chunks_exact(t);
+        let mut mixed = vec![F::zero(); t];
+
+        for round in 0..half {
+            let matrix = if round + 1 == half {
+                &self.pre_partial_matrix
+            } else {
+                &self.constants.mds_matrix
+            };
+            full_round(state, full_round_keys.next().unwrap(), matrix, &mut mixed);
+        }
+
+        for (element, key) in state.iter_mut().zip(&self.first_partial_keys) {
+            *element += key;
+        }
+        for (round, sparse) in self.sparse_matrices.iter().enumerate() {
+            state[0] = sbox(state[0]);
+            if let Some(key) = self.partial_keys.get(round) {
+                state[0] += key;
+            }
+            sparse.mul_assign(state);
+        }
+
+        for keys in full_round_keys.skip(self.constants.num_partial_rounds) {
+            full_round(state, keys, &self.constants.mds_matrix, &mut mixed);
+        }
+    }
+}
+
+// `diag(1, m_hat)`
+fn expand<F: PrimeField>(m_hat: &[Vec<F>]) -> Matrix<F> {
+    let mut matrix = linalg::identity(m_hat.len() + 1);
+    for (row, m_hat_row) in matrix[1..].iter_mut().zip(m_hat) {
+        row[1..].copy_from_slice(m_hat_row);
+    }
+    matrix
+}
+
+fn sbox<F: PrimeField>(x: F) -> F {
+    x.square().square() * x
+}
+
+fn full_round<F: PrimeField>(state: &mut [F], keys: &[F], matrix: &[Vec<F>], mixed: &mut [F]) {
+    for (element, key) in state.iter_mut().zip(keys) {
+        *element = sbox(*element + key);
+    }
+    for (out, row) in mixed.iter_mut().zip(matrix) {
+        *out = row
+            .iter()
+            .zip(state.iter())
+            .fold(F::zero(), |sum, (m, s)| sum + *m * s);
+    }
+    state.copy_from_slice(mixed);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::poseidon_k256::{optimized_constants, FieldElement, MAX_WIDTH, MIN_WIDTH};
+    use crate::sponge::permute;
+
+    #[test]
+    fn check_same_permutation() {
+        for width in MIN_WIDTH..=MAX_WIDTH {
+            let optimized = optimized_constants(width).unwrap();
+
+            let mut state: Vec<FieldElement> = (0..width as u64)
+                .map(|i| FieldElement::from(i * 1000 + 7))
+                .collect();
+            let mut expected = state.clone();
+            permute(optimized.constants(), &mut expected);
+            optimized.permute(&mut state);
+            assert_eq!(state, expected);
+        }
+    }
+}
@@ -0,0 +1,840 @@
+//! Generation of Poseidon parameters for `x^alpha` S-boxes over any prime
+//! field, following the reference `sage/generate_params_poseidon.sage` of the
+//! Poseidon authors: the round numbers are the cheapest ones satisfying the
+//! security inequalities plus a margin, and the round constants and the MDS
+//! matrix are sampled from a Grain LFSR seeded with the parameters, the matrix
+//! being resampled until it has no invariant subspace trails.
+//!
+//! Poseidon2 parameters are generated the same way, with the round numbers
+//! and internal matrices of the Poseidon2 paper.
+//!
+//! The module also renders the constants as the `k256_consts.rs`,
+//! `k256_sponge_consts.rs`, `k256_poseidon2_consts.rs` and
+//! `poseidon_constants.circom` files, see the `gen_poseidon_consts` binary.
+use crate::linalg::{self, Matrix};
+use crate::poseidon2::Poseidon2Constants;
+use crate::PoseidonConstants;
+use ff::PrimeField;
+use std::collections::VecDeque;
+use std::f64::consts::LN_2;
+use std::fmt::Write;
+
+/// Returns the number of full and partial rounds of the permutation of width
+/// `width` with the S-box `x^alpha` and `security_level` bits of security
+pub fn round_numbers<F: PrimeField>(
+    width: usize,
+    alpha: u64,
+    security_level: usize,
+) -> (usize, usize) {
+    let log2_p = log2_modulus::<F>();
+    find_round_numbers(width, |r_f, r_p| {
+        is_secure::<F>(width, r_f, r_p, alpha, security_level, log2_p)
+    })
+}
+
+/// Returns the number of full and partial rounds of the Poseidon2
+/// permutation of width `width` with the S-box `x^alpha` and `security_level`
+/// bits of security
+pub fn poseidon2_round_numbers<F: PrimeField>(
+    width: usize,
+    alpha: u64,
+    security_level: usize,
+) -> (usize, usize) {
+    let log2_p = log2_modulus::<F>();
+    find_round_numbers(width, |r_f, r_p| {
+        is_secure::<F>(width, r_f, r_p, alpha, security_level, log2_p)
+            && is_secure_poseidon2(width, r_f, r_p, alpha, security_level, log2_p)
+    })
+}
+
+// the cheapest round numbers secure without the margin, with the margin
+fn find_round_numbers(width: usize, is_secure: impl Fn(usize, usize) -> bool) -> (usize, usize) {
+    let mut num_full_rounds = 0;
+    let mut num_partial_rounds = 0;
+    let mut min_cost = usize::MAX;
+
+    // brute force, as in `find_FD_round_numbers`, including the way the
+    // margin added to a candidate carries over to the next ones
+    for r_p in 1..500 {
+        let mut r_p = r_p;
+        for r_f in (4..100).step_by(2) {
+            if !is_secure(r_f, r_p) {
+                continue;
+            }
+            let r_f = r_f + 2;
+            r_p = (r_p as f64 * 1.075).ceil() as usize;
+
+            let cost = width * r_f + r_p;
+            if cost < min_cost || (cost == min_cost && r_f < num_full_rounds) {
+                num_full_rounds = r_f;
+                num_partial_rounds = r_p;
+                min_cost = cost;
+            }
+        }
+    }
+    (num_full_rounds, num_partial_rounds)
+}
+
+// the security inequalities of `sat_inequiv_alpha`: statistical, interpolation
+// and two Groebner basis attacks
+fn is_secure<F: PrimeField>(
+    t: usize,
+    r_f: usize,
+    r_p: usize,
+    alpha: u64,
+    m: usize,
+    log2_p: f64,
+) -> bool {
+    let n = F::NUM_BITS as usize;
+    let (t_f, r_p_f, m_f) = (t as f64, r_p as f64, m as f64);
+    let log_alpha = |x: f64| x.ln() / (alpha as f64).ln();
+
+    let r_f_1 = if m_f <= (log2_p - (alpha - 1) as f64 / 2.0).floor() * (t_f + 1.0) {
+        6.0
+    } else {
+        10.0
+    };
+    let r_f_2 = 1.0 + (log_alpha(2.0) * m.min(n) as f64).ceil() + log_alpha(t_f).ceil() - r_p_f;
+    let r_f_3 = 1.0 + log_alpha(2.0) * (m_f / 3.0).min(log2_p / 2.0) - r_p_f;
+    let r_f_4 =
+        t_f - 1.0 + (log_alpha(2.0) * m_f / (t_f + 1.0)).min(log_alpha(2.0) * log2_p / 2.0) - r_p_f;
+
+    let r_f_max = [r_f_1, r_f_2, r_f_3, r_f_4]
+        .iter()
+        .map(|r| r.ceil())
+        .fold(f64::MIN, f64::max);
+    r_f as f64 >= r_f_max
+}
+
+// the bounds that the Poseidon2 reference script adds to `is_secure`: a
+// tighter first Groebner basis bound, a third one, and the attack of
+// https://eprint.iacr.org/2023/537.pdf, whose cost is estimated from the
+// binomial coefficient `over` choose `under`
+fn is_secure_poseidon2(
+    t: usize,
+    r_f: usize,
+    r_p: usize,
+    alpha: u64,
+    m: usize,
+    log2_p: f64,
+) -> bool {
+    let (t_f, r_p_f, m_f) = (t as f64, r_p as f64, m as f64);
+    let log_alpha_2 = 2f64.ln() / (alpha as f64).ln();
+
+    let r_f_3 = log_alpha_2 * m_f.min(log2_p) - r_p_f;
+    let r_f_5 = (t_f - 2.0 + m_f / (2.0 * (alpha as f64).log2()) - r_p_f) / (t_f - 1.0);
+    if (r_f as f64) < r_f_3.ceil().max(r_f_5.ceil()) {
+        return false;
+    }
+
+    let r = t / 3;
+    let under = r * r_f / 2 + r_p + alpha as usize;
+    let over = (r_f - 1) * t + r_p + r + under;
+    let log2_binomial: f64 = (1..=under)
+        .map(|i| ((over - under + i) as f64 / i as f64).log2())
+        .sum();
+    (2.0 * log2_binomial).ceil() >= m_f
+}
+
+// `log(p, 2)` computed like Python does for integers, from a float mantissa
+// and the bit length, so that round numbers match the reference script
+fn log2_modulus<F: PrimeField>() -> f64 {
+    let bits = linalg::to_bits(&-F::one());
+    let top = bits
+        .iter()
+        .take(64)
+        .fold(0u64, |acc, bit| (acc << 1) | *bit as u64);
+    let mut mantissa = top as f64 / 2f64.powi(bits.len().min(64) as i32);
+    let mut exponent = bits.len() as f64;
+    if mantissa >= 1.0 {
+        mantissa /= 2.0;
+        exponent += 1.0;
+    }
+    (mantissa.ln() + LN_2 * exponent) / LN_2
+}
+
+/// The Grain LFSR used as the source of randomness for the constants
+struct Grain {
+    state: VecDeque<bool>,
+}
+
+impl Grain {
+    // the 80-bit state is the field type (1 for a prime field), the S-box (0
+    // for `x^alpha`), the field size, width and round numbers, then ones
+    fn new(
+        field_size: usize,
+        width: usize,
+        num_full_rounds: usize,
+        num_partial_rounds: usize,
+    ) -> Self {
+        let mut state = VecDeque::with_capacity(80);
+        for (value, len) in [
+            (1, 2),
+            (0, 4),
+            (field_size, 12),
+            (width, 12),
+            (num_full_rounds, 10),
+            (num_partial_rounds, 10),
+        ] {
+            state.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        }
+        state.extend([true; 30]);
+
+        let mut grain = Grain { state };
+        for _ in 0..160 {
+            grain.step();
+        }
+        grain
+    }
+
+    fn step(&mut self) -> bool {
+        let s = &self.state;
+        let bit = s[62] ^ s[51] ^ s[38] ^ s[23] ^ s[13] ^ s[0];
+        self.state.pop_front();
+        self.state.push_back(bit);
+        bit
+    }
+
+    // self-shrinking: a pair of bits is output as its second bit if the
+    // first one is set, and discarded otherwise
+    fn next_bit(&mut self) -> bool {
+        loop {
+            if self.step() {
+                return self.step();
+            }
+            self.step();
+        }
+    }
+
+    // the next `n` bits, most significant first
+    fn next_bits(&mut self, n: usize) -> Vec<bool> {
+        (0..n).map(|_| self.next_bit()).collect()
+    }
+
+    // a field element from `NUM_BITS` bits, sampled again while they exceed
+    // `modulus_minus_one` if it is given, and reduced otherwise
+    fn next_element<F: PrimeField>(&mut self, modulus_minus_one: Option<&[bool]>) -> F {
+        loop {
+            let bits = self.next_bits(F::NUM_BITS as usize);
+            let in_range = match modulus_minus_one {
+                Some(max) => bits.as_slice() <= max,
+                None => true,
+            };
+            if in_range {
+                return bits.iter().fold(F::zero(), |acc, bit| {
+                    acc.double() + if *bit { F::one() } else { F::zero() }
+                });
+            }
+        }
+    }
+
+    // a Cauchy matrix `1 / (x_i + y_j)` for distinct `x_i, y_j`
+    fn next_cauchy_matrix<F: PrimeField>(&mut self, width: usize) -> Matrix<F> {
+        loop {
+            let mut elements: Vec<F> = (0..2 * width).map(|_| self.next_element(None)).collect();
+            while (1..elements.len()).any(|i| elements[..i].contains(&elements[i])) {
+                elements = (0..2 * width).map(|_| self.next_element(None)).collect();
+            }
+
+            let (xs, ys) = elements.split_at(width);
+            if xs
+                .iter()
+                .any(|x| ys.iter().any(|y| bool::from((*x + y).is_zero())))
+            {
+                continue;
+            }
+            return xs
+                .iter()
+                .map(|x| ys.iter().map(|y| (*x + y).invert().unwrap()).collect())
+                .collect();
+        }
+    }
+}
+
+/// Generates the constants of the permutation of width `width` with the
+/// S-box `x^alpha` and `security_level` bits of security
+pub fn generate<F: PrimeField>(
+    width: usize,
+    alpha: u64,
+    security_level: usize,
+) -> PoseidonConstants<F> {
+    let (num_full_rounds, num_partial_rounds) = round_numbers::<F>(width, alpha, security_level);
+    let modulus_minus_one = linalg::to_bits(&-F::one());
+
+    let mut grain = Grain::new(
+        F::NUM_BITS as usize,
+        width,
+        num_full_rounds,
+        num_partial_rounds,
+    );
+    let round_keys = (0..(num_full_rounds + num_partial_rounds) * width)
+        .map(|_| grain.next_element(Some(&modulus_minus_one)))
+        .collect();
+    let mds_matrix = loop {
+        let mds = grain.next_cauchy_matrix(width);
+        if is_secure_mds(&mds) {
+            break mds;
+        }
+    };
+
+    PoseidonConstants::new(round_keys, mds_matrix, num_full_rounds, num_partial_rounds)
+}
+
+/// Generates the constants of the Poseidon2 permutation of width `width`,
+/// which must be 2, 3 or a multiple of 4, with the S-box `x^alpha` and
+/// `security_level` bits of security.
+///
+/// The round constants are sampled from the same LFSR as for Poseidon, in
+/// round order, but a partial round only samples the one it uses, as the
+/// reference implementation of the Poseidon2 authors does. The diagonal of the
+/// internal matrix is fixed for the widths 2 and 3, and sampled next from the
+/// LFSR otherwise, until the matrix passes `is_secure_internal_matrix`.
+pub fn generate_poseidon2<F: PrimeField>(
+    width: usize,
+    alpha: u64,
+    security_level: usize,
+) -> Poseidon2Constants<F> {
+    let (num_full_rounds, num_partial_rounds) =
+        poseidon2_round_numbers::<F>(width, alpha, security_level);
+    let modulus_minus_one = linalg::to_bits(&-F::one());
+
+    let mut grain = Grain::new(
+        F::NUM_BITS as usize,
+        width,
+        num_full_rounds,
+        num_partial_rounds,
+    );
+    let full_round_keys = |grain: &mut Grain, num_rounds: usize| -> Matrix<F> {
+        (0..num_rounds)
+            .map(|_| {
+                (0..width)
+                    .map(|_| grain.next_element(Some(&modulus_minus_one)))
+                    .collect()
+            })
+            .collect()
+    };
+    let mut round_keys = full_round_keys(&mut grain, num_full_rounds / 2);
+    let internal_round_keys = (0..num_partial_rounds)
+        .map(|_| grain.next_element(Some(&modulus_minus_one)))
+        .collect();
+    round_keys.extend(full_round_keys(&mut grain, num_full_rounds / 2));
+
+    let internal_diagonal = match width {
+        2 => vec![F::one(), F::from(2)],
+        3 => vec![F::one(), F::one(), F::from(2)],
+        _ => loop {
+            let diagonal: Vec<F> = (0..width)
+                .map(|_| grain.next_element(Some(&modulus_minus_one)))
+                .collect();
+            if is_secure_internal_matrix(&diagonal) {
+                break diagonal;
+            }
+        },
+    };
+
+    Poseidon2Constants::new(round_keys, internal_round_keys, internal_diagonal)
+}
+
+/// Checks that the Poseidon2 internal matrix `1 + diag(diagonal)` and its
+/// powers up to `2t` have irreducible characteristic polynomials, so that
+/// they are invertible and leave no subspace invariant
+pub fn is_secure_internal_matrix<F: PrimeField>(diagonal: &[F]) -> bool {
+    let t = diagonal.len();
+    let matrix: Matrix<F> = diagonal
+        .iter()
+        .enumerate()
+        .map(|(i, d)| {
+            let mut row = vec![F::one(); t];
+            row[i] += d;
+            row
+        })
+        .collect();
+    let modulus_minus_one = linalg::to_bits(&-F::one());
+
+    linalg::powers(&matrix, 2 * t)[1..]
+        .iter()
+        .all(|power| linalg::is_irreducible(&linalg::charpoly(power), &modulus_minus_one))
+}
+
+/// Checks that `mds` admits no invariant subspace trails over the partial
+/// rounds, with algorithms 1 to 3 of the reference script, for one S-box per
+/// partial round
+pub fn is_secure_mds<F: PrimeField>(mds: &[Vec<F>]) -> bool {
+    let t = mds.len();
+    let powers = linalg::powers(mds, 4 * t);
+    // algorithm 3 is algorithm 2 for the powers `M^r`, `r = 2..=4t`
+    algorithm_1(&powers, t) && (1..=4 * t).all(|r| algorithm_2(&powers[r]))
+}
+
+// no power `M^i`, `i < t`, is a multiple of the identity, has an eigenvector
+// in the subspace `S_i` left invariant by `i` partial rounds, or maps `S_i`
+// onto itself
+fn algorithm_1<F: PrimeField>(powers: &[Matrix<F>], t: usize) -> bool {
+    let modulus_minus_one = linalg::to_bits(&-F::one());
+
+    for i in 1..t {
+        let m_i = &powers[i];
+        let is_scalar = m_i.iter().enumerate().all(|(r, row)| {
+            row.iter()
+                .enumerate()
+                .all(|(c, x)| *x == if r == c { m_i[0][0] } else { F::zero() })
+        });
+        if is_scalar {
+            return false;
+        }
+
+        // the vectors with a zero first element whose image under `M^k` also
+        // has one, for `k < i`
+        let constraints = (1..i).map(|k| powers[k][0][1..].to_vec()).collect();
+        let subspace: Matrix<F> = linalg::kernel(constraints, t - 1)
+            .into_iter()
+            .map(|v| [vec![F::zero()], v].concat())
+            .collect();
+        let subspace = linalg::row_reduce(subspace);
+
+        for eigenvalue in linalg::roots(&linalg::charpoly(m_i), &modulus_minus_one) {
+            let shifted = m_i
+                .iter()
+                .enumerate()
+                .map(|(row_index, row)| {
+                    let mut row = row.clone();
+                    row[row_index] -= eigenvalue;
+                    row
+                })
+                .collect();
+            let eigenspace = linalg::kernel(shifted, t);
+            let sum = linalg::rank([subspace.clone(), eigenspace.clone()].concat());
+            if subspace.len() + eigenspace.len() > sum {
+                return false;
+            }
+        }
+
+        for power in &powers[1..=i] {
+            let image = subspace.iter().map(|v| linalg::mul_vec(power, v)).collect();
+            if linalg::row_reduce(image) == subspace {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// the first unit vector is not in a proper subspace invariant under `m`, i.e.
+// its images under the powers of `m` span the whole space
+fn algorithm_2<F: PrimeField>(m: &[Vec<F>]) -> bool {
+    let t = m.len();
+    let mut v = vec![F::zero(); t];
+    v[0] = F::one();
+    let mut span = vec![v.clone()];
+
+    loop {
+        let dim = span.len();
+        v = linalg::mul_vec(m, &v);
+        span.push(v.clone());
+        span = linalg::row_reduce(span);
+        if span.len() == t {
+            return true;
+        }
+        if span.len() == dim {
+            return false;
+        }
+    }
+}
+
+// the decimal digits of the integer `x`
+fn to_decimal<F: PrimeField>(x: &F) -> String {
+    // least significant digit first
+    let mut digits = vec![0u8];
+    for bit in linalg::to_bits(x) {
+        let mut carry = bit as u8;
+        for digit in digits.iter_mut() {
+            let doubled = *digit * 2 + carry;
+            *digit = doubled % 10;
+            carry = doubled / 10;
+        }
+        if carry > 0 {
+            digits.push(carry);
+        }
+    }
+    digits.iter().rev().map(|d| char::from(b'0' + d)).collect()
+}
+
+/// Renders `constants` as a Rust module in the layout of `k256_consts.rs`,
+/// for the field type at `field_path`
+pub fn to_rust_module<F: PrimeField>(constants: &PoseidonConstants<F>, field_path: &str) -> String {
+    let field = field_path.rsplit("::").next().unwrap();
+    let width = constants.width();
+    let parse = format!(".map(|y| {}::from_str_vartime(y).unwrap())", field);
+
+    let mut out = String::new();
+    writeln!(out, "use ff::PrimeField;").unwrap();
+    writeln!(out, "use lazy_static::lazy_static;").unwrap();
+    writeln!(out, "pub use {};\n", field_path).unwrap();
+    writeln!(
+        out,
+        "pub(crate) const NUM_FULL_ROUNDS: usize = {};",
+        constants.num_full_rounds
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "pub(crate) const NUM_PARTIAL_ROUNDS: usize = {};\n",
+        constants.num_partial_rounds
+    )
+    .unwrap();
+
+    writeln!(out, "lazy_static! {{").unwrap();
+    writeln!(
+        out,
+        "    pub(crate) static ref MDS_MATRIX: [[{}; {}]; {}] = [",
+        field, width, width
+    )
+    .unwrap();
+    for row in &constants.mds_matrix {
+        writeln!(out, "        [").unwrap();
+        for x in row {
+            writeln!(out, "            \"{}\",", to_decimal(x)).unwrap();
+        }
+        writeln!(out, "        ]\n        {},", parse).unwrap();
+    }
+    writeln!(out, "    ];").unwrap();
+    writeln!(
+        out,
+        "    pub(crate) static ref ROUND_CONSTANTS: [{}; {}] = [",
+        field,
+        constants.round_keys.len()
+    )
+    .unwrap();
+    for x in &constants.round_keys {
+        writeln!(out, "        \"{}\",", to_decimal(x)).unwrap();
+    }
+    writeln!(out, "    ]\n    {};\n}}", parse).unwrap();
+    out
+}
+
+/// Renders the constants of consecutive widths as a Rust module in the
+/// layout of `k256_sponge_consts.rs`
+pub fn to_rust_table<F: PrimeField>(constants: &[PoseidonConstants<F>]) -> String {
+    let min_width = constants[0].width();
+    let max_width = min_width + constants.len() - 1;
+    let list = |values: &[usize]| {
+        values
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "//! Round constants and MDS matrices of the Poseidon permutations of width {} to {},",
+        min_width, max_width
+    )
+    .unwrap();
+    writeln!(
+        out,
+        "//! generated by `gen_poseidon_consts`. Entry `t - {}` of each table is for width `t`.\n",
+        min_width
+    )
+    .unwrap();
+    for (name, values) in [
+        (
+            "NUM_FULL_ROUNDS",
+            constants
+                .iter()
+                .map(|c| c.num_full_rounds)
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "NUM_PARTIAL_ROUNDS",
+            constants
+                .iter()
+                .map(|c| c.num_partial_rounds)
+                .collect::<Vec<_>>(),
+        ),
+    ] {
+        // laid out as rustfmt does
+        let line = format!(
+            "pub(crate) const {}: [usize; {}] = [{}];",
+            name,
+            constants.len(),
+            list(&values)
+        );
+        if line.len() <= 100 {
+            writeln!(out, "{}", line).unwrap();
+        } else {
+            writeln!(
+                out,
+                "pub(crate) const {}: [usize; {}] = [\n    {},\n];",
+                name,
+                constants.len(),
+                list(&values)
+            )
+            .unwrap();
+        }
+    }
+
+    writeln!(
+        out,
+        "\npub(crate) const MDS_MATRICES: [&[&[&str]]; {}] = [",
+        constants.len()
+    )
+    .unwrap();
+    for c in constants {
+        writeln!(out, "    // t = {}\n    &[", c.width()).unwrap();
+        for row in &c.mds_matrix {
+            writeln!(out, "        &[").unwrap();
+            for x in row {
+                writeln!(out, "            \"{}\",", to_decimal(x)).unwrap();
+            }
+            writeln!(out, "        ],").unwrap();
+        }
+        writeln!(out, "    ],").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    writeln!(
+        out,
+        "\npub(crate) const ROUND_CONSTANTS: [&[&str]; {}] = [",
+        constants.len()
+    )
+    .unwrap();
+    for c in constants {
+        writeln!(out, "    // t = {}\n    &[", c.width()).unwrap();
+        for x in &c.round_keys {
+            writeln!(out, "        \"{}\",", to_decimal(x)).unwrap();
+        }
+        writeln!(out, "    ],").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+    out
+}
+
+/// Renders the constants of Poseidon2 permutations as a Rust module in the
+/// layout of `k256_poseidon2_consts.rs`
+pub fn to_rust_poseidon2_table<F: PrimeField>(constants: &[Poseidon2Constants<F>]) -> String {
+    let widths: Vec<String> = constants.iter().map(|c| c.width().to_string()).collect();
+    let (last, first) = widths.split_last().unwrap();
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "//! Round constants and internal matrix diagonals of the Poseidon2 permutations of width\n\
+         //! {} and {}, generated by `gen_poseidon_consts`. Entry `i` of each table is for\n\
+         //! width `poseidon2_k256::WIDTHS[i]`.",
+        first.join(", "),
+        last
+    )
+    .unwrap();
+
+    writeln!(
+        out,
+        "\npub(crate) const EXTERNAL_ROUND_CONSTANTS: [&[&[&str]]; {}] = [",
+        constants.len()
+    )
+    .unwrap();
+    for c in constants {
+        writeln!(out, "    // t = {}\n    &[", c.width()).unwrap();
+        for keys in &c.external_round_keys {
+            write_str_list(&mut out, 2, keys);
+        }
+        writeln!(out, "    ],").unwrap();
+    }
+    writeln!(out, "];").unwrap();
+
+    for (name, table) in [
+        (
+            "INTERNAL_ROUND_CONSTANTS",
+            constants
+                .iter()
+                .map(|c| &c.internal_round_keys)
+                .collect::<Vec<_>>(),
+        ),
+        (
+            "INTERNAL_DIAGONALS",
+            constants
+                .iter()
+                .map(|c| &c.internal_diagonal)
+                .collect::<Vec<_>>(),
+        ),
+    ] {
+        writeln!(
+            out,
+            "\npub(crate) const {}: [&[&str]; {}] = [",
+            name,
+            constants.len()
+        )
+        .unwrap();
+        for (c, values) in constants.iter().zip(table) {
+            writeln!(out, "    // t = {}", c.width()).unwrap();
+            write_str_list(&mut out, 1, values);
+        }
+        writeln!(out, "];").unwrap();
+    }
+    out
+}
+
+// writes `&["x", ...],` indented by `depth` levels, on one line if it fits
+// like rustfmt does, and one element per line otherwise
+fn write_str_list<F: PrimeField>(out: &mut String, depth: usize, values: &[F]) {
+    let indent = "    ".repeat(depth);
+    let values: Vec<String> = values
+        .iter()
+        .map(|x| format!("\"{}\"", to_decimal(x)))
+        .collect();
+
+    let line = format!("{}&[{}],", indent, values.join(", "));
+    if line.len() <= 100 {
+        writeln!(out, "{}", line).unwrap();
+    } else {
+        writeln!(out, "{}&[", indent).unwrap();
+        for value in values {
+            writeln!(out, "{}    {},", indent, value).unwrap();
+        }
+        writeln!(out, "{}],", indent).unwrap();
+    }
+}
+
+/// Renders `constants` as the `ROUND_KEYS` and `MDS_MATRIX` functions of
+/// `poseidon_constants.circom`
+pub fn to_circom<F: PrimeField>(constants: &PoseidonConstants<F>) -> String {
+    let mut out = String::new();
+    writeln!(out, "pragma circom 2.1.2;\n").unwrap();
+
+    let round_keys: Vec<String> = constants.round_keys.iter().map(to_decimal).collect();
+    writeln!(out, "function ROUND_KEYS() {{\n    return [").unwrap();
+    writeln!(out, "    {}", round_keys.join(",\n    ")).unwrap();
+    writeln!(out, "  ];\n}}\n").unwrap();
+
+    let rows: Vec<String> = constants
+        .mds_matrix
+        .iter()
+        .map(|row| {
+            let row: Vec<String> = row.iter().map(to_decimal).collect();
+            format!(
+                "        [\n            {}\n        ]",
+                row.join(",\n            ")
+            )
+        })
+        .collect();
+    writeln!(out, "function MDS_MATRIX() {{\n    return [").unwrap();
+    writeln!(out, "{}", rows.join(",\n")).unwrap();
+    writeln!(out, "    ];\n}}").unwrap();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bn254::Fr;
+    use crate::poseidon2_k256::WIDTHS;
+    use crate::poseidon_k256::{FieldElement, MAX_WIDTH, MIN_WIDTH};
+
+    const FIELD_PATH: &str = "secq256k1::field::field_secp::FieldElement";
+
+    #[test]
+    fn check_k256_consts() {
+        assert_eq!(round_numbers::<FieldElement>(3, 5, 128), (8, 56));
+
+        let constants = generate::<FieldElement>(3, 5, 128);
+        assert_eq!(
+            to_rust_module(&constants, FIELD_PATH),
+            include_str!("k256_consts.rs")
+        );
+        assert_eq!(
+            to_circom(&constants),
+            include_str!("../../circuits/poseidon/poseidon_constants.circom")
+        );
+    }
+
+    #[test]
+    fn check_k256_sponge_consts() {
+        let constants: Vec<PoseidonConstants<FieldElement>> = (MIN_WIDTH..=MAX_WIDTH)
+            .map(|width| generate(width, 5, 128))
+            .collect();
+        assert_eq!(
+            to_rust_table(&constants),
+            include_str!("k256_sponge_consts.rs")
+        );
+    }
+
+    #[test]
+    fn check_k256_poseidon2_consts() {
+        let round_numbers: Vec<(usize, usize)> = WIDTHS
+            .iter()
+            .map(|width| poseidon2_round_numbers::<FieldElement>(*width, 5, 128))
+            .collect();
+        assert_eq!(
+            round_numbers,
+            [(8, 56), (8, 56), (8, 56), (8, 57), (8, 57), (8, 57)]
+        );
+
+        let constants: Vec<Poseidon2Constants<FieldElement>> = WIDTHS
+            .iter()
+            .map(|width| generate_poseidon2(*width, 5, 128))
+            .collect();
+        assert_eq!(
+            to_rust_poseidon2_table(&constants),
+            include_str!("k256_poseidon2_consts.rs")
+        );
+    }
+
+    #[test]
+    fn check_poseidon2_reference_vector() {
+        // the known-answer test of the BN256 instance of the reference
+        // implementation of the Poseidon2 authors,
+        // https://github.com/HorizenLabs/poseidon2: the permutation of
+        // `[0, 1, 2]` with `Rf = 8` and `Rp = 56`
+        assert_eq!(poseidon2_round_numbers::<Fr>(3, 5, 128), (8, 56));
+        let constants = generate_poseidon2::<Fr>(3, 5, 128);
+
+        let mut state = [0u64, 1, 2].map(Fr::from);
+        constants.permute(&mut state);
+        let from_hex = |x: &str| {
+            let mut repr: [u8; 32] = hex::decode(x).unwrap().try_into().unwrap();
+            repr.reverse();
+            Fr::from_repr(repr).unwrap()
+        };
+        assert_eq!(
+            state,
+            [
+                "0bb61d24daca55eebcb1929a82650f328134334da98ea4f847f760054f4a3033",
+                "303b6f7c86d043bfcbcc80214f26a30277a15d3f74ca654992defe7ff8d03570",
+                "1ed25194542b12eef8617361c3ba7c52e660b145994427cc86296242cf766ec8",
+            ]
+            .map(from_hex)
+        );
+    }
+
+    #[test]
+    fn check_insecure_mds() {
+        let matrix = |rows: &[&[u64]]| -> Matrix<FieldElement> {
+            rows.iter()
+                .map(|row| row.iter().map(|x| FieldElement::from(*x)).collect())
+                .collect()
+        };
+
+        // a multiple of the identity
+        assert!(!is_secure_mds(&matrix(&[
+            &[2, 0, 0],
+            &[0, 2, 0],
+            &[0, 0, 2]
+        ])));
+        // the first unit vector spans an invariant subspace with the second
+        assert!(!is_secure_mds(&matrix(&[
+            &[1, 2, 0],
+            &[3, 4, 0],
+            &[0, 0, 5]
+        ])));
+        // an eigenvector `(0, 1, -1)` of `M` with a zero first element
+        assert!(!is_secure_mds(&matrix(&[
+            &[1, 2, 2],
+            &[3, 4, 5],
+            &[3, 5, 4]
+        ])));
+        assert!(is_secure_mds(
+            &generate::<FieldElement>(3, 5, 128).mds_matrix
+        ));
+    }
+}
@@ -0,0 +1,199 @@
+//! The Poseidon2 permutation, https://eprint.iacr.org/2023/323.pdf
+//!
+//! Poseidon2 keeps the round structure of Poseidon with the S-box `x^5`, but
+//! replaces its MDS matrix with two cheap linear layers:
+//!
+//! * the external layer `M_E` of the full rounds, also applied once before
+//!   the first round, is `circ(2, 1)` or `circ(2, 1, 1)` for the widths 2 and
+//!   3, and the 4x4 matrix `M_4` for the width 4; for larger multiples of 4,
+//!   it applies `M_4` to each block of 4 elements, then adds to each element
+//!   the sum of the elements at its position in all the blocks;
+//! * the internal layer `M_I` of the partial rounds is `1 + diag(d)`, the
+//!   all-ones matrix plus a diagonal, so that it costs `2t` additions and `t`
+//!   multiplications.
+//!
+//! A partial round also adds a single round constant, to `state[0]`.
+use ff::PrimeField;
+
+/// Constants of a Poseidon2 permutation
+pub struct Poseidon2Constants<F: PrimeField> {
+    /// the constants added to the whole state in each full round
+    pub external_round_keys: Vec<Vec<F>>,
+    /// the constant added to `state[0]` in each partial round
+    pub internal_round_keys: Vec<F>,
+    /// `d` in `M_I = 1 + diag(d)`
+    pub internal_diagonal: Vec<F>,
+}
+
+impl<F: PrimeField> Poseidon2Constants<F> {
+    /// Panics if the width, the length of `internal_diagonal`, is not 2, 3 or
+    /// a multiple of 4, or if the number of full rounds is odd
+    pub fn new(
+        external_round_keys: Vec<Vec<F>>,
+        internal_round_keys: Vec<F>,
+        internal_diagonal: Vec<F>,
+    ) -> Self {
+        let width = internal_diagonal.len();
+        assert!(
+            matches!(width, 2 | 3) || (width >= 4 && width % 4 == 0),
+            "no Poseidon2 external matrix for width {}",
+            width
+        );
+        assert!(
+            external_round_keys.len() % 2 == 0
+                && external_round_keys.iter().all(|keys| keys.len() == width),
+            "expected an even number of full rounds of {} constants",
+            width
+        );
+
+        Self {
+            external_round_keys,
+            internal_round_keys,
+            internal_diagonal,
+        }
+    }
+
+    /// Returns the width of the state the constants are for
+    pub fn width(&self) -> usize {
+        self.internal_diagonal.len()
+    }
+
+    /// Applies the permutation to `state`, whose length must be the width
+    pub fn permute(&self, state: &mut [F]) {
+        assert_eq!(
+            state.len(),
+            self.width(),
+            "state and constants widths differ"
+        );
+
+        let half = self.external_round_keys.len() / 2;
+        let (first_rounds, last_rounds) = self.external_round_keys.split_at(half);
+
+        external_layer(state);
+        for keys in first_rounds {
+            self.external_round(state, keys);
+        }
+        for key in &self.internal_round_keys {
+            state[0] = sbox(state[0] + key);
+            self.internal_layer(state);
+        }
+        for keys in last_rounds {
+            self.external_round(state, keys);
+        }
+    }
+
+    fn external_round(&self, state: &mut [F], keys: &[F]) {
+        for (element, key) in state.iter_mut().zip(keys) {
+            *element = sbox(*element + key);
+        }
+        external_layer(state);
+    }
+
+    // `(1 + diag(d)) * state = sum(state) + d * state`
+    fn internal_layer(&self, state: &mut [F]) {
+        let sum = state.iter().fold(F::zero(), |sum, element| sum + element);
+        for (element, d) in state.iter_mut().zip(&self.internal_diagonal) {
+            *element = sum + *element * d;
+        }
+    }
+}
+
+fn sbox<F: PrimeField>(x: F) -> F {
+    x.square().square() * x
+}
+
+// `M_E * state`
+fn external_layer<F: PrimeField>(state: &mut [F]) {
+    if state.len() < 4 {
+        // `circ(2, 1, ...) * state = sum(state) + state`
+        let sum = state.iter().fold(F::zero(), |sum, element| sum + element);
+        for element in state.iter_mut() {
+            *element += sum;
+        }
+        return;
+    }
+
+    for block in state.chunks_exact_mut(4) {
+        mul_m4(block);
+    }
+    if state.len() == 4 {
+        return;
+    }
+    let sums: Vec<F> = (0..4)
+        .map(|i| {
+            state
+                .iter()
+                .skip(i)
+                .step_by(4)
+                .fold(F::zero(), |sum, element| sum + element)
+        })
+        .collect();
+    for (i, element) in state.iter_mut().enumerate() {
+        *element += sums[i % 4];
+    }
+}
+
+// `M_4 * block` with
+//
+//     M_4 = [[5, 7, 1, 3],
+//            [4, 6, 1, 1],
+//            [1, 3, 5, 7],
+//            [1, 1, 4, 6]]
+//
+// in 8 additions and 6 doublings, as in the paper
+fn mul_m4<F: PrimeField>(block: &mut [F]) {
+    let t0 = block[0] + block[1];
+    let t1 = block[2] + block[3];
+    let t2 = block[1].double() + t1;
+    let t3 = block[3].double() + t0;
+    let t4 = t1.double().double() + t3;
+    let t5 = t0.double().double() + t2;
+    let t6 = t3 + t5;
+    let t7 = t2 + t4;
+    block.copy_from_slice(&[t6, t5, t7, t4]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_k256::FieldElement;
+
+    // `M_E` as a dense matrix
+    fn external_matrix(width: usize) -> Vec<Vec<FieldElement>> {
+        let m4 = [[5, 7, 1, 3], [4, 6, 1, 1], [1, 3, 5, 7], [1, 1, 4, 6]];
+        (0..width)
+            .map(|i| {
+                (0..width)
+                    .map(|j| {
+                        let entry = match width {
+                            2 | 3 => 1 + (i == j) as u64,
+                            4 => m4[i][j],
+                            _ => m4[i % 4][j % 4] * (1 + (i / 4 == j / 4) as u64),
+                        };
+                        FieldElement::from(entry)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn check_external_layer() {
+        for width in [2, 3, 4, 8, 12, 16] {
+            let mut state: Vec<FieldElement> = (0..width as u64)
+                .map(|i| FieldElement::from(i * i + 3))
+                .collect();
+            let expected: Vec<FieldElement> = external_matrix(width)
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .zip(&state)
+                        .fold(FieldElement::zero(), |sum, (m, s)| sum + *m * s)
+                })
+                .collect();
+
+            external_layer(&mut state);
+            assert_eq!(state, expected);
+        }
+    }
+}
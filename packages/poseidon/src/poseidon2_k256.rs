@@ -0,0 +1,126 @@
+//! Poseidon2 over the secp256k1 base field, the field of `poseidon_k256`.
+//!
+//! `hash` takes the place of `poseidon_k256::hash` for circuits built on the
+//! width-3 Poseidon2 permutation: it permutes `[3, a, b]` and outputs
+//! `state[1]`, as the Merkle tree domain of the sponge does.
+use crate::k256_poseidon2_consts;
+use crate::poseidon2::Poseidon2Constants;
+use crate::poseidon_k256::FieldElement;
+use crate::sponge::{Domain, Sponge, SpongeError};
+use ff::PrimeField;
+use lazy_static::lazy_static;
+
+/// Widths with generated constants
+pub const WIDTHS: [usize; 6] = [2, 3, 4, 8, 12, 16];
+
+lazy_static! {
+    static ref CONSTANTS: Vec<Poseidon2Constants<FieldElement>> = (0..WIDTHS.len())
+        .map(|i| {
+            let parse = |x: &&str| FieldElement::from_str_vartime(x).unwrap();
+            Poseidon2Constants::new(
+                k256_poseidon2_consts::EXTERNAL_ROUND_CONSTANTS[i]
+                    .iter()
+                    .map(|keys| keys.iter().map(parse).collect())
+                    .collect(),
+                k256_poseidon2_consts::INTERNAL_ROUND_CONSTANTS[i]
+                    .iter()
+                    .map(parse)
+                    .collect(),
+                k256_poseidon2_consts::INTERNAL_DIAGONALS[i]
+                    .iter()
+                    .map(parse)
+                    .collect(),
+            )
+        })
+        .collect();
+}
+
+/// Returns the constants of the permutation of width `width`
+pub fn constants(width: usize) -> Result<&'static Poseidon2Constants<FieldElement>, SpongeError> {
+    WIDTHS
+        .iter()
+        .position(|w| *w == width)
+        .map(|i| &CONSTANTS[i])
+        .ok_or(SpongeError::InvalidWidth(width))
+}
+
+/// Creates a sponge over the permutation of width `width`
+pub fn sponge(
+    width: usize,
+    domain: Domain,
+) -> Result<Sponge<'static, FieldElement, Poseidon2Constants<FieldElement>>, SpongeError> {
+    Sponge::new(constants(width)?, domain)
+}
+
+/// Hashes two inputs with the width-3 permutation
+pub fn hash(input: &[FieldElement; 2]) -> FieldElement {
+    // add the domain tag, 2^arity - 1
+    let mut state = [FieldElement::from(3), input[0], input[1]];
+    CONSTANTS[1].permute(&mut state);
+    state[1]
+}
+
+/// Hashes each pair of `inputs`, as `hash` does
+pub fn hash_pairs(inputs: &[[FieldElement; 2]]) -> Vec<FieldElement> {
+    inputs.iter().map(hash).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the permutation of `[0, 1, ..., t - 1]`
+    fn permute_range(width: usize) -> Vec<FieldElement> {
+        let mut state: Vec<FieldElement> = (0..width as u64).map(FieldElement::from).collect();
+        constants(width).unwrap().permute(&mut state);
+        state
+    }
+
+    fn parse(x: &str) -> FieldElement {
+        FieldElement::from_str_vartime(x).unwrap()
+    }
+
+    // computed with these constants; the generator and the permutation are
+    // checked against the reference implementation in `params`
+    #[test]
+    fn check_test_vectors() {
+        assert_eq!(
+            permute_range(3),
+            [
+                "51682984177619898557665181435368014018678553241918531446217248422913705195956",
+                "14968038307345328072734245424096249827577142812722161760026332172578534888897",
+                "22591336048360040809864772835552798825068371258589340593960972252936287611824",
+            ]
+            .map(parse)
+        );
+
+        let first_elements = [
+            "25719160181350019191964706423655770150120477446108152275071168457832510334803",
+            "51682984177619898557665181435368014018678553241918531446217248422913705195956",
+            "41882161662173668519551296926656683260099473272333306522269885017802224333",
+            "43794307155168533703351830329950219965275972398382243490702138315136639441707",
+            "54642539373408548632864016568061587911139112451765588420127966554915295937878",
+            "6308488898776068153444636302079597562077425929746516325393438675692884519309",
+        ];
+        for (width, expected) in WIDTHS.iter().zip(first_elements) {
+            assert_eq!(permute_range(*width)[0], parse(expected));
+        }
+    }
+
+    #[test]
+    fn check_hash() {
+        let input = [1234567u64, 109987].map(FieldElement::from);
+        assert_eq!(
+            hash(&input),
+            parse("81136958139628215414893586563076355974958430478398067439545581682592351483336")
+        );
+
+        let mut sponge = sponge(3, Domain::MerkleTree).unwrap();
+        sponge.absorb(&input).unwrap();
+        assert_eq!(sponge.squeeze(1).unwrap(), vec![hash(&input)]);
+
+        assert_ne!(hash(&input), crate::poseidon_k256::hash(&input));
+        assert_eq!(hash_pairs(&[input, input]), vec![hash(&input); 2]);
+        assert_eq!(constants(5).err(), Some(SpongeError::InvalidWidth(5)));
+    }
+}
@@ -1,22 +1,125 @@
 use crate::k256_consts::*;
-use crate::{Poseidon, PoseidonConstants};
+use crate::k256_sponge_consts;
+use crate::optimized::OptimizedConstants;
+use crate::sponge::{Domain, Sponge, SpongeError};
+use crate::PoseidonConstants;
+use ff::PrimeField;
+use lazy_static::lazy_static;
 pub use secq256k1::field::field_secp::FieldElement;
 
-#[allow(dead_code)]
+/// Smallest width with generated constants
+pub const MIN_WIDTH: usize = 2;
+/// Largest width with generated constants, i.e. up to 16 inputs per permutation
+pub const MAX_WIDTH: usize = 17;
+
+lazy_static! {
+    /// the constants of `hash`
+    static ref CONSTANTS: OptimizedConstants<FieldElement> =
+        OptimizedConstants::new(PoseidonConstants::new(
+            ROUND_CONSTANTS.to_vec(),
+            MDS_MATRIX.iter().map(|row| row.to_vec()).collect(),
+            NUM_FULL_ROUNDS,
+            NUM_PARTIAL_ROUNDS,
+        ));
+    static ref SPONGE_CONSTANTS: Vec<OptimizedConstants<FieldElement>> = (MIN_WIDTH..=MAX_WIDTH)
+        .map(|width| {
+            let parse = |x: &&str| FieldElement::from_str_vartime(x).unwrap();
+            let i = width - MIN_WIDTH;
+            OptimizedConstants::new(PoseidonConstants::new(
+                k256_sponge_consts::ROUND_CONSTANTS[i]
+                    .iter()
+                    .map(parse)
+                    .collect(),
+                k256_sponge_consts::MDS_MATRICES[i]
+                    .iter()
+                    .map(|row| row.iter().map(parse).collect())
+                    .collect(),
+                k256_sponge_consts::NUM_FULL_ROUNDS[i],
+                k256_sponge_consts::NUM_PARTIAL_ROUNDS[i],
+            ))
+        })
+        .collect();
+}
+
+/// Returns the constants of the permutation of width `width`
+pub fn constants(width: usize) -> Result<&'static PoseidonConstants<FieldElement>, SpongeError> {
+    Ok(optimized_constants(width)?.constants())
+}
+
+/// Returns the constants of the permutation of width `width`, prepared for
+/// cheap partial rounds
+pub fn optimized_constants(
+    width: usize,
+) -> Result<&'static OptimizedConstants<FieldElement>, SpongeError> {
+    match width {
+        MIN_WIDTH..=MAX_WIDTH => Ok(&SPONGE_CONSTANTS[width - MIN_WIDTH]),
+        _ => Err(SpongeError::InvalidWidth(width)),
+    }
+}
+
+/// Creates a sponge over the permutation of width `width`
+pub fn sponge(width: usize, domain: Domain) -> Result<Sponge<'static, FieldElement>, SpongeError> {
+    Sponge::new(optimized_constants(width)?, domain)
+}
+
+/// Hashes a fixed number of inputs. Up to 16 inputs are hashed with a single
+/// permutation of width `inputs.len() + 1`; more are absorbed 16 at a time.
+pub fn hash_fixed_length(inputs: &[FieldElement]) -> FieldElement {
+    let width = (inputs.len() + 1).clamp(MIN_WIDTH, MAX_WIDTH);
+    let domain = Domain::ConstantLength {
+        input_len: inputs.len(),
+        output_len: 1,
+    };
+    let mut sponge = sponge(width, domain).unwrap();
+    sponge.absorb(inputs).unwrap();
+    sponge.squeeze(1).unwrap()[0]
+}
+
+/// Hashes a list of inputs whose length is not fixed, absorbing `width - 1`
+/// inputs per permutation
+pub fn hash_variable_length(
+    width: usize,
+    inputs: &[FieldElement],
+) -> Result<FieldElement, SpongeError> {
+    let mut sponge = sponge(width, Domain::VariableLength { output_len: 1 })?;
+    sponge.absorb(inputs)?;
+    Ok(sponge.squeeze(1)?[0])
+}
+
+/// Hashes two inputs as the `Poseidon` circuit does
 pub fn hash(input: &[FieldElement; 2]) -> FieldElement {
-    let constants = PoseidonConstants::<FieldElement>::new(
-        ROUND_CONSTANTS.to_vec(),
-        vec![
-            MDS_MATRIX[0].to_vec(),
-            MDS_MATRIX[1].to_vec(),
-            MDS_MATRIX[2].to_vec(),
-        ],
-        NUM_FULL_ROUNDS,
-        NUM_PARTIAL_ROUNDS,
-    );
-    let mut poseidon = Poseidon::new(constants);
-
-    let result = poseidon.hash(input);
-
-    result
+    // add the domain tag, 2^arity - 1
+    let mut state = [FieldElement::from(3), input[0], input[1]];
+    CONSTANTS.permute(&mut state);
+    state[1]
+}
+
+/// Hashes each pair of `inputs`, as `hash` does
+pub fn hash_pairs(inputs: &[[FieldElement; 2]]) -> Vec<FieldElement> {
+    inputs.iter().map(hash).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Poseidon;
+
+    #[test]
+    fn check_hash() {
+        let mut poseidon = Poseidon::new(PoseidonConstants::new(
+            ROUND_CONSTANTS.to_vec(),
+            MDS_MATRIX.iter().map(|row| row.to_vec()).collect(),
+            NUM_FULL_ROUNDS,
+            NUM_PARTIAL_ROUNDS,
+        ));
+        let pairs: Vec<[FieldElement; 2]> = (0..4u64)
+            .map(|i| [FieldElement::from(i), -FieldElement::from(i * 7 + 1)])
+            .collect();
+
+        // the textbook permutation gives the same hashes, also when reused
+        let expected: Vec<FieldElement> = pairs.iter().map(|pair| poseidon.hash(pair)).collect();
+        assert_eq!(hash_pairs(&pairs), expected);
+        assert_eq!(hash(&pairs[3]), expected[3]);
+        assert!(hash_pairs(&[]).is_empty());
+    }
 }
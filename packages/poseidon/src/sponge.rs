@@ -0,0 +1,406 @@
+//! Poseidon permutation of any width and a sponge over it.
+//!
+//! The state is `[capacity, rate...]`, as in the `Poseidon` circuit: the
+//! capacity element starts as a domain tag, inputs are added to the rate
+//! elements and outputs are read from them, starting with `state[1]`. Hashing
+//! `n` inputs with the constant-length domain and a width of `n + 1` is thus a
+//! single permutation of `[tag, inputs...]`, the shape of a circuit of that
+//! width. The domain tags and padding are the ones of section 4.2 of the
+//! Poseidon paper, https://eprint.iacr.org/2019/458.pdf
+use crate::optimized::OptimizedConstants;
+use crate::poseidon2::Poseidon2Constants;
+use crate::PoseidonConstants;
+use ff::PrimeField;
+use thiserror::Error;
+
+/// Errors returned by the sponge
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum SpongeError {
+    /// there are no constants for the width, or it leaves no rate element
+    #[error("invalid Poseidon width: {0}")]
+    InvalidWidth(usize),
+    /// more or fewer inputs than the domain fixes were absorbed
+    #[error("expected {expected} inputs, got {actual}")]
+    InputLength { expected: usize, actual: usize },
+    /// `absorb` was called after `squeeze`
+    #[error("cannot absorb after squeezing")]
+    AbsorbAfterSqueeze,
+    /// more outputs than the domain fixes were squeezed
+    #[error("cannot squeeze more than {0} outputs")]
+    OutputLength(usize),
+}
+
+/// What the sponge hashes, which determines the capacity element it starts
+/// with and how the last block is padded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Domain {
+    /// a node of a Merkle tree whose arity is the rate: exactly `width - 1`
+    /// inputs and one output, with the tag `2^arity - 1`
+    MerkleTree,
+    /// exactly `input_len` inputs and up to `output_len` outputs, with the tag
+    /// `input_len * 2^64 + output_len - 1` and zero padding
+    ConstantLength { input_len: usize, output_len: usize },
+    /// any number of inputs and up to `output_len` outputs, with the tag
+    /// `2^64 + output_len - 1`; the inputs are padded with a one and zeros
+    VariableLength { output_len: usize },
+}
+
+impl Domain {
+    /// Returns the capacity element for a sponge of rate `rate`
+    pub fn tag<F: PrimeField>(&self, rate: usize) -> F {
+        let two_pow_64 = F::from(u64::MAX) + F::one();
+        match *self {
+            Domain::MerkleTree => F::from(2).pow_vartime([rate as u64]) - F::one(),
+            Domain::ConstantLength {
+                input_len,
+                output_len,
+            } => F::from(input_len as u64) * two_pow_64 + F::from(output_len as u64) - F::one(),
+            Domain::VariableLength { output_len } => {
+                two_pow_64 + F::from(output_len as u64) - F::one()
+            }
+        }
+    }
+
+    fn input_len(&self, rate: usize) -> Option<usize> {
+        match *self {
+            Domain::MerkleTree => Some(rate),
+            Domain::ConstantLength { input_len, .. } => Some(input_len),
+            Domain::VariableLength { .. } => None,
+        }
+    }
+
+    fn output_len(&self) -> usize {
+        match *self {
+            Domain::MerkleTree => 1,
+            Domain::ConstantLength { output_len, .. } | Domain::VariableLength { output_len } => {
+                output_len
+            }
+        }
+    }
+}
+
+fn sbox<F: PrimeField>(x: F) -> F {
+    x.square().square() * x
+}
+
+/// Applies the Poseidon permutation defined by `constants` to `state`, whose
+/// length must be the width of the constants
+pub fn permute<F: PrimeField>(constants: &PoseidonConstants<F>, state: &mut [F]) {
+    let width = state.len();
+    assert_eq!(
+        width,
+        constants.width(),
+        "state and constants widths differ"
+    );
+
+    let full_rounds_half = constants.num_full_rounds / 2;
+    let partial_rounds = full_rounds_half..full_rounds_half + constants.num_partial_rounds;
+    let mut mixed = vec![F::zero(); width];
+
+    for (round, keys) in constants.round_keys.chunks_exact(width).enumerate() {
+        for (element, key) in state.iter_mut().zip(keys) {
+            *element += key;
+        }
+
+        if partial_rounds.contains(&round) {
+            state[0] = sbox(state[0]);
+        } else {
+            for element in state.iter_mut() {
+                *element = sbox(*element);
+            }
+        }
+
+        for (out, row) in mixed.iter_mut().zip(&constants.mds_matrix) {
+            *out = row
+                .iter()
+                .zip(state.iter())
+                .fold(F::zero(), |sum, (m, s)| sum + *m * s);
+        }
+        state.copy_from_slice(&mixed);
+    }
+}
+
+/// A permutation a sponge can be built over
+pub trait Permutation<F: PrimeField> {
+    /// Returns the number of elements permuted
+    fn width(&self) -> usize;
+    /// Applies the permutation to `state`, whose length must be the width
+    fn permute(&self, state: &mut [F]);
+}
+
+impl<F: PrimeField> Permutation<F> for OptimizedConstants<F> {
+    fn width(&self) -> usize {
+        OptimizedConstants::width(self)
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        OptimizedConstants::permute(self, state)
+    }
+}
+
+impl<F: PrimeField> Permutation<F> for Poseidon2Constants<F> {
+    fn width(&self) -> usize {
+        Poseidon2Constants::width(self)
+    }
+
+    fn permute(&self, state: &mut [F]) {
+        Poseidon2Constants::permute(self, state)
+    }
+}
+
+/// A sponge over a Poseidon permutation, or any other `Permutation`: inputs
+/// are absorbed into the rate elements, which are permuted whenever they are
+/// all used, and the outputs are squeezed from them
+#[derive(Clone)]
+pub struct Sponge<'a, F: PrimeField, P: Permutation<F> = OptimizedConstants<F>> {
+    constants: &'a P,
+    domain: Domain,
+    state: Vec<F>,
+    /// the next rate element to absorb into or squeeze from
+    pos: usize,
+    absorbed: usize,
+    /// the number of outputs squeezed so far, `None` while absorbing
+    squeezed: Option<usize>,
+}
+
+impl<'a, F: PrimeField, P: Permutation<F>> Sponge<'a, F, P> {
+    /// Creates a sponge over the permutation defined by `constants`
+    pub fn new(constants: &'a P, domain: Domain) -> Result<Self, SpongeError> {
+        let width = constants.width();
+        if width < 2 {
+            return Err(SpongeError::InvalidWidth(width));
+        }
+
+        let mut state = vec![F::zero(); width];
+        state[0] = domain.tag(width - 1);
+        Ok(Sponge {
+            constants,
+            domain,
+            state,
+            pos: 0,
+            absorbed: 0,
+            squeezed: None,
+        })
+    }
+
+    /// Returns the number of elements absorbed or squeezed per permutation
+    pub fn rate(&self) -> usize {
+        self.state.len() - 1
+    }
+
+    /// Absorbs `inputs` after the ones absorbed before
+    pub fn absorb(&mut self, inputs: &[F]) -> Result<(), SpongeError> {
+        if self.squeezed.is_some() {
+            return Err(SpongeError::AbsorbAfterSqueeze);
+        }
+        if let Some(expected) = self.domain.input_len(self.rate()) {
+            let actual = self.absorbed + inputs.len();
+            if actual > expected {
+                return Err(SpongeError::InputLength { expected, actual });
+            }
+        }
+
+        for input in inputs {
+            self.absorb_element(*input);
+        }
+        self.absorbed += inputs.len();
+        Ok(())
+    }
+
+    /// Squeezes the next `n` outputs; the first call pads and closes the input
+    pub fn squeeze(&mut self, n: usize) -> Result<Vec<F>, SpongeError> {
+        let squeezed = match self.squeezed {
+            Some(squeezed) => squeezed,
+            None => {
+                self.finish_absorbing()?;
+                0
+            }
+        };
+        let output_len = self.domain.output_len();
+        if squeezed + n > output_len {
+            return Err(SpongeError::OutputLength(output_len));
+        }
+
+        let mut outputs = Vec::with_capacity(n);
+        for _ in 0..n {
+            if self.pos == self.rate() {
+                self.constants.permute(&mut self.state);
+                self.pos = 0;
+            }
+            outputs.push(self.state[1 + self.pos]);
+            self.pos += 1;
+        }
+        self.squeezed = Some(squeezed + n);
+        Ok(outputs)
+    }
+
+    fn absorb_element(&mut self, input: F) {
+        if self.pos == self.rate() {
+            self.constants.permute(&mut self.state);
+            self.pos = 0;
+        }
+        self.state[1 + self.pos] += input;
+        self.pos += 1;
+    }
+
+    fn finish_absorbing(&mut self) -> Result<(), SpongeError> {
+        match self.domain.input_len(self.rate()) {
+            Some(expected) if expected != self.absorbed => {
+                return Err(SpongeError::InputLength {
+                    expected,
+                    actual: self.absorbed,
+                });
+            }
+            Some(_) => {}
+            None => self.absorb_element(F::one()),
+        }
+
+        self.constants.permute(&mut self.state);
+        self.pos = 0;
+        self.squeezed = Some(0);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::poseidon_k256::{self, constants, FieldElement};
+
+    fn inputs(n: u64) -> Vec<FieldElement> {
+        (1..=n).map(FieldElement::from).collect()
+    }
+
+    #[test]
+    fn check_merkle_tree_domain() {
+        // the width-3 sponge in the Merkle tree domain is the `Poseidon` circuit
+        let input = [1234567u64, 109987].map(FieldElement::from);
+        let mut sponge = poseidon_k256::sponge(3, Domain::MerkleTree).unwrap();
+        sponge.absorb(&input).unwrap();
+        assert_eq!(
+            sponge.squeeze(1).unwrap(),
+            vec![poseidon_k256::hash(&input)]
+        );
+
+        let mut sponge = poseidon_k256::sponge(3, Domain::MerkleTree).unwrap();
+        sponge.absorb(&input[..1]).unwrap();
+        assert_eq!(
+            sponge.squeeze(1),
+            Err(SpongeError::InputLength {
+                expected: 2,
+                actual: 1
+            })
+        );
+    }
+
+    #[test]
+    fn check_constant_length() {
+        // a single permutation of [4 * 2^64, 1, 2, 3, 4]
+        let expected = FieldElement::from_str_vartime(
+            "70321840442881682915055317897802304038924307728451187667011333094738231229560",
+        )
+        .unwrap();
+        assert_eq!(poseidon_k256::hash_fixed_length(&inputs(4)), expected);
+
+        let mut state = vec![Domain::ConstantLength {
+            input_len: 4,
+            output_len: 1,
+        }
+        .tag(4)];
+        state.extend(inputs(4));
+        permute(constants(5).unwrap(), &mut state);
+        assert_eq!(state[1], expected);
+
+        // the length is part of the domain
+        assert_ne!(
+            poseidon_k256::hash_fixed_length(&inputs(3)),
+            poseidon_k256::hash_fixed_length(&[inputs(3), vec![FieldElement::zero()]].concat())
+        );
+    }
+
+    #[test]
+    fn check_variable_length() {
+        let expected = FieldElement::from_str_vartime(
+            "26589822151077060703724625025891852343535736321967731082926859943718307253212",
+        )
+        .unwrap();
+        assert_eq!(
+            poseidon_k256::hash_variable_length(3, &inputs(5)),
+            Ok(expected)
+        );
+
+        // the padding separates inputs that end with zeros
+        for n in 0..6 {
+            let padded = [inputs(n), vec![FieldElement::zero()]].concat();
+            assert_ne!(
+                poseidon_k256::hash_variable_length(3, &inputs(n)),
+                poseidon_k256::hash_variable_length(3, &padded)
+            );
+        }
+    }
+
+    #[test]
+    fn check_absorb_and_squeeze() {
+        let domain = Domain::VariableLength { output_len: 7 };
+        let mut sponge = poseidon_k256::sponge(4, domain).unwrap();
+        sponge.absorb(&inputs(10)).unwrap();
+        let outputs = sponge.squeeze(7).unwrap();
+        assert_eq!(sponge.squeeze(1), Err(SpongeError::OutputLength(7)));
+        assert_eq!(
+            sponge.absorb(&inputs(1)),
+            Err(SpongeError::AbsorbAfterSqueeze)
+        );
+
+        // absorbing and squeezing in pieces gives the same outputs
+        let mut sponge = poseidon_k256::sponge(4, domain).unwrap();
+        for chunk in inputs(10).chunks(4) {
+            sponge.absorb(chunk).unwrap();
+        }
+        let mut pieces = sponge.squeeze(2).unwrap();
+        pieces.extend(sponge.squeeze(0).unwrap());
+        pieces.extend(sponge.squeeze(5).unwrap());
+        assert_eq!(pieces, outputs);
+
+        let domain = Domain::ConstantLength {
+            input_len: 3,
+            output_len: 1,
+        };
+        let mut sponge = poseidon_k256::sponge(4, domain).unwrap();
+        assert_eq!(
+            sponge.absorb(&inputs(4)),
+            Err(SpongeError::InputLength {
+                expected: 3,
+                actual: 4
+            })
+        );
+    }
+
+    #[test]
+    fn check_widths() {
+        for width in poseidon_k256::MIN_WIDTH..=poseidon_k256::MAX_WIDTH {
+            let constants = constants(width).unwrap();
+            assert_eq!(constants.width(), width);
+            assert_eq!(
+                constants.round_keys.len(),
+                (constants.num_full_rounds + constants.num_partial_rounds) * width
+            );
+
+            let n = width as u64 - 1;
+            let mut sponge = poseidon_k256::sponge(
+                width,
+                Domain::ConstantLength {
+                    input_len: n as usize,
+                    output_len: 1,
+                },
+            )
+            .unwrap();
+            sponge.absorb(&inputs(n)).unwrap();
+            assert_eq!(
+                sponge.squeeze(1).unwrap()[0],
+                poseidon_k256::hash_fixed_length(&inputs(n))
+            );
+        }
+        assert!(constants(1).is_err());
+        assert_eq!(constants(18).err(), Some(SpongeError::InvalidWidth(18)));
+    }
+}
@@ -51,7 +51,8 @@ impl Field for Scalar {
 
     #[must_use]
     fn square(&self) -> Self {
-        unimplemented!();
+        let self_as_f = FieldElement::from_bytes(&self.to_bytes()).unwrap();
+        Scalar::from_repr(*FieldBytes::from_slice(&self_as_f.square().to_be_bytes())).unwrap()
     }
 
     #[must_use]
@@ -60,7 +61,10 @@ impl Field for Scalar {
     }
 
     fn invert(&self) -> CtOption<Self> {
-        unimplemented!();
+        let self_as_f = FieldElement::from_bytes(&self.to_bytes()).unwrap();
+        self_as_f.invert().map(|inverse| {
+            Scalar::from_repr(*FieldBytes::from_slice(&inverse.to_be_bytes())).unwrap()
+        })
     }
 
     fn sqrt(&self) -> CtOption<Self> {
@@ -340,4 +344,12 @@ mod tests {
         println!("mul {:?}", mul.0.to_string());
         println!("neg {:?}", neg.0.to_string());
     }
+
+    #[test]
+    fn invert() {
+        let a = Scalar::from(2u64.pow(63) - 2);
+        assert_eq!(a * a.invert().unwrap(), Scalar::one());
+        assert_eq!(a.square(), a * a);
+        assert!(bool::from(Scalar::zero().invert().is_none()));
+    }
 }
@@ -0,0 +1,241 @@
+//! Public inputs of the efficient ECDSA membership circuit.
+//!
+//! Instead of the signature's `r` and the message hash, the circuit takes the
+//! points `T = r^-1 * R` and `U = -(r^-1 * m * G)`, so that it only has to check
+//! `s * T + U = Q` for the signer's public key `Q`. The prover computes them
+//! from the signature, and the verifier recomputes them from the revealed `r`,
+//! `v` and message hash to make sure they are not made up.
+//! See https://personaelabs.org/posts/efficient-ecdsa-1/
+use k256::elliptic_curve::bigint::{ArrayEncoding, CheckedAdd, U256};
+use k256::elliptic_curve::ops::Reduce;
+use k256::elliptic_curve::sec1::{FromEncodedPoint, ToEncodedPoint};
+use k256::elliptic_curve::subtle::Choice;
+use k256::elliptic_curve::{Curve, DecompressPoint, PrimeField};
+use k256::{AffinePoint, EncodedPoint, FieldBytes, ProjectivePoint, Scalar, Secp256k1};
+use thiserror::Error;
+
+/// Errors returned when the public input cannot be computed or decoded
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum EffEcdsaError {
+    /// `r` is zero or not smaller than the order of secp256k1
+    #[error("r is not a valid signature component")]
+    InvalidR,
+    /// `v` is neither a recovery id in `0..=3` nor one in `27..=30`
+    #[error("invalid recovery id: {0}")]
+    InvalidRecoveryId(u8),
+    /// no curve point has the x-coordinate given by `r` and `v`
+    #[error("r is not the x-coordinate of a curve point")]
+    NoSuchPoint,
+    /// an encoded coordinate pair is not a curve point
+    #[error("invalid encoding of T or U")]
+    InvalidEncoding,
+    /// the supplied `T` and `U` are not the ones of the signature
+    #[error("T and U do not match the signature")]
+    Mismatch,
+}
+
+/// The points `T` and `U` the membership circuit takes as public input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EffEcdsaPubInput {
+    /// `T = r^-1 * R`
+    pub t: AffinePoint,
+    /// `U = -(r^-1 * m * G)`
+    pub u: AffinePoint,
+}
+
+impl EffEcdsaPubInput {
+    /// Computes `T` and `U` from the `r` and `v` of a signature over
+    /// `msg_hash`. `r` and `msg_hash` are big-endian; `v` is either the raw
+    /// recovery id or, as in Ethereum signatures, the recovery id plus 27.
+    pub fn compute(r: &[u8; 32], v: u8, msg_hash: &[u8; 32]) -> Result<Self, EffEcdsaError> {
+        let recovery_id = match v {
+            0..=3 => v,
+            27..=30 => v - 27,
+            _ => return Err(EffEcdsaError::InvalidRecoveryId(v)),
+        };
+
+        let r_scalar = Option::<Scalar>::from(Scalar::from_repr(FieldBytes::from(*r)))
+            .filter(|r| !bool::from(r.is_zero()))
+            .ok_or(EffEcdsaError::InvalidR)?;
+        let r_point = recover_r(r, recovery_id)?;
+
+        let r_inv = r_scalar.invert().unwrap();
+        let m = <Scalar as Reduce<U256>>::from_be_bytes_reduced(FieldBytes::from(*msg_hash));
+
+        Ok(EffEcdsaPubInput {
+            t: (ProjectivePoint::from(r_point) * r_inv).to_affine(),
+            u: (ProjectivePoint::GENERATOR * -(r_inv * m)).to_affine(),
+        })
+    }
+
+    /// Checks that `T` and `U` are the ones of the signature with the given
+    /// `r` and `v` over `msg_hash`
+    pub fn verify(&self, r: &[u8; 32], v: u8, msg_hash: &[u8; 32]) -> Result<(), EffEcdsaError> {
+        if EffEcdsaPubInput::compute(r, v, msg_hash)? == *self {
+            Ok(())
+        } else {
+            Err(EffEcdsaError::Mismatch)
+        }
+    }
+
+    /// Encodes the points as `Tx || Ty || Ux || Uy`, each coordinate as 32
+    /// big-endian bytes, in the layout of `CircuitPubInput` in the JS library.
+    /// The identity is encoded as zero coordinates.
+    pub fn to_bytes(&self) -> [u8; 128] {
+        let mut bytes = [0u8; 128];
+        for (point, chunk) in [self.t, self.u].iter().zip(bytes.chunks_mut(64)) {
+            let encoded = point.to_encoded_point(false);
+            if let (Some(x), Some(y)) = (encoded.x(), encoded.y()) {
+                chunk[..32].copy_from_slice(x);
+                chunk[32..].copy_from_slice(y);
+            }
+        }
+        bytes
+    }
+
+    /// Decodes points encoded by `to_bytes`, checking that they are on the curve
+    pub fn from_bytes(bytes: &[u8; 128]) -> Result<Self, EffEcdsaError> {
+        let decode = |chunk: &[u8]| -> Result<AffinePoint, EffEcdsaError> {
+            if chunk.iter().all(|b| *b == 0) {
+                return Ok(AffinePoint::IDENTITY);
+            }
+            let encoded = EncodedPoint::from_affine_coordinates(
+                FieldBytes::from_slice(&chunk[..32]),
+                FieldBytes::from_slice(&chunk[32..]),
+                false,
+            );
+            Option::from(AffinePoint::from_encoded_point(&encoded))
+                .ok_or(EffEcdsaError::InvalidEncoding)
+        };
+
+        Ok(EffEcdsaPubInput {
+            t: decode(&bytes[..64])?,
+            u: decode(&bytes[64..])?,
+        })
+    }
+}
+
+/// Recovers the point `R` of a signature from `r` and the recovery id: bit 0
+/// is the parity of `R.y`, and bit 1 is set when `R.x` is `r + n`, which
+/// happens for the few points whose x-coordinate is at least the group order.
+fn recover_r(r: &[u8; 32], recovery_id: u8) -> Result<AffinePoint, EffEcdsaError> {
+    let mut x = U256::from_be_byte_array(FieldBytes::from(*r));
+    if recovery_id & 2 != 0 {
+        x = Option::from(x.checked_add(&Secp256k1::ORDER)).ok_or(EffEcdsaError::NoSuchPoint)?;
+    }
+
+    // fails if `x` is not a field element or not the x-coordinate of a point
+    Option::from(AffinePoint::decompress(
+        &x.to_be_byte_array(),
+        Choice::from(recovery_id & 1),
+    ))
+    .ok_or(EffEcdsaError::NoSuchPoint)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::elliptic_curve::Field;
+    use rand_core::OsRng;
+
+    // signs `m` with nonce `k` and returns `(r, v, s)`
+    fn sign(d: &Scalar, k: &Scalar, m: &Scalar) -> ([u8; 32], u8, Scalar) {
+        let encoded = (ProjectivePoint::GENERATOR * k)
+            .to_affine()
+            .to_encoded_point(false);
+        let x = U256::from_be_byte_array(*encoded.x().unwrap());
+        let r = <Scalar as Reduce<U256>>::from_uint_reduced(x);
+        let s = k.invert().unwrap() * (m + r * d);
+
+        let y_is_odd = encoded.y().unwrap()[31] & 1;
+        let x_is_reduced = (x >= Secp256k1::ORDER) as u8;
+        (r.to_bytes().into(), 27 + y_is_odd + 2 * x_is_reduced, s)
+    }
+
+    #[test]
+    fn check_eff_ecdsa_pub_input() {
+        for _ in 0..8 {
+            let d = Scalar::random(&mut OsRng);
+            let m = Scalar::random(&mut OsRng);
+            let (r, v, s) = sign(&d, &Scalar::random(&mut OsRng), &m);
+            let msg_hash: [u8; 32] = m.to_bytes().into();
+
+            // s * T + U = Q
+            let pub_input = EffEcdsaPubInput::compute(&r, v, &msg_hash).unwrap();
+            let q = ProjectivePoint::from(pub_input.t) * s + ProjectivePoint::from(pub_input.u);
+            assert_eq!(q, ProjectivePoint::GENERATOR * d);
+
+            assert_eq!(pub_input.verify(&r, v, &msg_hash), Ok(()));
+            assert_eq!(pub_input.verify(&r, v - 27, &msg_hash), Ok(()));
+            // the other point with the same x-coordinate
+            assert_eq!(
+                pub_input.verify(&r, 27 + ((v - 27) ^ 1), &msg_hash),
+                Err(EffEcdsaError::Mismatch)
+            );
+            let other_hash: [u8; 32] = (m + Scalar::ONE).to_bytes().into();
+            assert_eq!(
+                pub_input.verify(&r, v, &other_hash),
+                Err(EffEcdsaError::Mismatch)
+            );
+
+            let bytes = pub_input.to_bytes();
+            assert_eq!(EffEcdsaPubInput::from_bytes(&bytes), Ok(pub_input));
+            let mut tampered = bytes;
+            tampered[127] ^= 1;
+            assert_eq!(
+                EffEcdsaPubInput::from_bytes(&tampered),
+                Err(EffEcdsaError::InvalidEncoding)
+            );
+        }
+    }
+
+    #[test]
+    fn check_high_r() {
+        // find a point whose x-coordinate is at least the group order
+        let (x, r_point) = (1u64..)
+            .map(|i| Secp256k1::ORDER.wrapping_add(&U256::from_u64(i)))
+            .find_map(|x| {
+                Option::<AffinePoint>::from(AffinePoint::decompress(
+                    &x.to_be_byte_array(),
+                    Choice::from(0),
+                ))
+                .map(|p| (x, p))
+            })
+            .unwrap();
+        let r: [u8; 32] = x.wrapping_sub(&Secp256k1::ORDER).to_be_byte_array().into();
+        let r_scalar = Scalar::from_repr(FieldBytes::from(r)).unwrap();
+
+        let pub_input = EffEcdsaPubInput::compute(&r, 29, &[1u8; 32]).unwrap();
+        assert_eq!(
+            ProjectivePoint::from(pub_input.t) * r_scalar,
+            ProjectivePoint::from(r_point)
+        );
+        // the low-r reading of the same `r` is a different point, if any
+        assert_ne!(
+            EffEcdsaPubInput::compute(&r, 27, &[1u8; 32]).ok(),
+            Some(pub_input)
+        );
+    }
+
+    #[test]
+    fn check_invalid_signatures() {
+        let msg_hash = [1u8; 32];
+        assert_eq!(
+            EffEcdsaPubInput::compute(&[0u8; 32], 27, &msg_hash),
+            Err(EffEcdsaError::InvalidR)
+        );
+        assert_eq!(
+            EffEcdsaPubInput::compute(&Secp256k1::ORDER.to_be_byte_array().into(), 27, &msg_hash),
+            Err(EffEcdsaError::InvalidR)
+        );
+        assert_eq!(
+            EffEcdsaPubInput::compute(&[1u8; 32], 31, &msg_hash),
+            Err(EffEcdsaError::InvalidRecoveryId(31))
+        );
+        // r + n is not a field element for a large r
+        assert_eq!(
+            EffEcdsaPubInput::compute(&[0x7f; 32], 29, &msg_hash),
+            Err(EffEcdsaError::NoSuchPoint)
+        );
+    }
+}
@@ -1 +1,3 @@
+pub mod eff_ecdsa;
+pub mod membership;
 pub mod wasm;
@@ -0,0 +1,374 @@
+//! Verification of membership proofs outside of JS.
+//!
+//! A membership proof only means something together with its public values:
+//! besides the Spartan proof, `MembershipVerifier` checks that `T` and `U` are
+//! the ones of the revealed signature and that the Merkle root is one of the
+//! groups the verifier accepts.
+use crate::eff_ecdsa::{EffEcdsaError, EffEcdsaPubInput};
+use crate::wasm::{to_little_endian, DEFAULT_LABEL};
+use libspartan::{Assignment, Instance, NIZKGens, ProofContext, ProofVerifyError, NIZK};
+use merlin::Transcript;
+use std::collections::HashSet;
+use thiserror::Error;
+
+/// Errors returned when a verifier cannot be set up
+#[derive(Error, Debug)]
+pub enum MembershipError {
+    /// the circuit could not be decoded
+    #[error("invalid circuit: {0}")]
+    InvalidCircuit(#[from] bincode::Error),
+    /// the public parameters do not belong to the circuit
+    #[error("invalid public parameters: {0}")]
+    InvalidGens(#[from] libspartan::GensError),
+}
+
+/// The public inputs of the membership circuit: the Merkle root followed by
+/// `Tx, Ty, Ux, Uy`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitPubInput {
+    /// big-endian root of the group's Merkle tree
+    pub merkle_root: [u8; 32],
+    /// the points `T` and `U` of the efficient ECDSA signature
+    pub eff_ecdsa: EffEcdsaPubInput,
+}
+
+impl CircuitPubInput {
+    /// Encoded length, 32 bytes per public input
+    pub const LEN: usize = 32 * 5;
+
+    /// Encodes the inputs as in `CircuitPubInput.serialize` of the JS library
+    pub fn to_bytes(&self) -> [u8; CircuitPubInput::LEN] {
+        let mut bytes = [0u8; CircuitPubInput::LEN];
+        bytes[..32].copy_from_slice(&self.merkle_root);
+        bytes[32..].copy_from_slice(&self.eff_ecdsa.to_bytes());
+        bytes
+    }
+
+    /// Decodes inputs encoded by `to_bytes`
+    pub fn from_bytes(bytes: &[u8; CircuitPubInput::LEN]) -> Result<Self, EffEcdsaError> {
+        Ok(CircuitPubInput {
+            merkle_root: bytes[..32].try_into().unwrap(),
+            eff_ecdsa: EffEcdsaPubInput::from_bytes(bytes[32..].try_into().unwrap())?,
+        })
+    }
+}
+
+/// All public values of a membership proof, in the layout of `PublicInput` in
+/// the JS library: `r || v || circuit public inputs || message hash`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PublicInput {
+    /// big-endian `r` of the signature
+    pub r: [u8; 32],
+    /// `v` of the signature
+    pub v: u8,
+    /// the inputs the proof is made for
+    pub circuit_pub_input: CircuitPubInput,
+    /// the signed message hash
+    pub msg_hash: [u8; 32],
+}
+
+impl PublicInput {
+    /// Encoded length of a public input with a 32-byte message hash
+    pub const LEN: usize = 32 + 1 + CircuitPubInput::LEN + 32;
+
+    /// Encodes the values as in `PublicInput.serialize` of the JS library
+    pub fn to_bytes(&self) -> [u8; PublicInput::LEN] {
+        let mut bytes = [0u8; PublicInput::LEN];
+        bytes[..32].copy_from_slice(&self.r);
+        bytes[32] = self.v;
+        bytes[33..33 + CircuitPubInput::LEN].copy_from_slice(&self.circuit_pub_input.to_bytes());
+        bytes[33 + CircuitPubInput::LEN..].copy_from_slice(&self.msg_hash);
+        bytes
+    }
+
+    /// Decodes values encoded by `to_bytes`. Only 32-byte message hashes are
+    /// supported, as the efficient ECDSA inputs are computed from one
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EffEcdsaError> {
+        if bytes.len() != PublicInput::LEN {
+            return Err(EffEcdsaError::InvalidEncoding);
+        }
+
+        Ok(PublicInput {
+            r: bytes[..32].try_into().unwrap(),
+            v: bytes[32],
+            circuit_pub_input: CircuitPubInput::from_bytes(
+                bytes[33..33 + CircuitPubInput::LEN].try_into().unwrap(),
+            )?,
+            msg_hash: bytes[33 + CircuitPubInput::LEN..].try_into().unwrap(),
+        })
+    }
+}
+
+/// The outcome of verifying a membership proof
+#[derive(Debug)]
+pub enum Verdict {
+    /// the proof is valid for its public input
+    Valid,
+    /// the public input could not be decoded
+    MalformedPublicInput(EffEcdsaError),
+    /// `T` and `U` do not match the revealed signature and message hash
+    InvalidEffEcdsaInput(EffEcdsaError),
+    /// the Merkle root is not one of the allowed roots
+    UnknownRoot([u8; 32]),
+    /// the Spartan proof does not verify
+    InvalidProof(ProofVerifyError),
+}
+
+impl Verdict {
+    /// Returns true if the proof was accepted
+    pub fn is_valid(&self) -> bool {
+        matches!(self, Verdict::Valid)
+    }
+}
+
+/// Verifies membership proofs made for a circuit, against a set of allowed
+/// Merkle roots
+pub struct MembershipVerifier {
+    circuit: Instance,
+    gens: NIZKGens,
+    roots: HashSet<[u8; 32]>,
+}
+
+impl MembershipVerifier {
+    /// Creates a verifier for the encoded `circuit`, deriving its public
+    /// parameters. No root is allowed until one is added with `allow_root`
+    pub fn new(circuit: &[u8]) -> Result<Self, MembershipError> {
+        let circuit: Instance = bincode::deserialize(circuit)?;
+        let gens = NIZKGens::new(
+            circuit.inst.get_num_cons(),
+            circuit.inst.get_num_vars(),
+            circuit.inst.get_num_inputs(),
+        );
+
+        Ok(MembershipVerifier {
+            circuit,
+            gens,
+            roots: HashSet::new(),
+        })
+    }
+
+    /// Same as `new`, but uses public parameters produced by `nizk_gens`
+    pub fn with_gens(circuit: &[u8], gens: &[u8]) -> Result<Self, MembershipError> {
+        let circuit: Instance = bincode::deserialize(circuit)?;
+        let gens = NIZKGens::from_bytes(
+            gens,
+            circuit.inst.get_num_cons(),
+            circuit.inst.get_num_vars(),
+            circuit.inst.get_num_inputs(),
+        )?;
+
+        Ok(MembershipVerifier {
+            circuit,
+            gens,
+            roots: HashSet::new(),
+        })
+    }
+
+    /// Accepts proofs of membership in the group with the big-endian Merkle `root`
+    pub fn allow_root(&mut self, root: [u8; 32]) {
+        self.roots.insert(root);
+    }
+
+    /// Stops accepting proofs for `root`
+    pub fn revoke_root(&mut self, root: &[u8; 32]) {
+        self.roots.remove(root);
+    }
+
+    /// Verifies a proof made by `prove` against its encoded `PublicInput`
+    pub fn verify(&self, proof: &[u8], public_input: &[u8]) -> Verdict {
+        self.verify_with_transcript(proof, public_input, Transcript::new(DEFAULT_LABEL))
+    }
+
+    /// Verifies a proof made by `prove_in_context` for `context`
+    pub fn verify_in_context(
+        &self,
+        proof: &[u8],
+        public_input: &[u8],
+        context: &ProofContext,
+    ) -> Verdict {
+        self.verify_with_transcript(proof, public_input, context.transcript())
+    }
+
+    fn verify_with_transcript(
+        &self,
+        proof: &[u8],
+        public_input: &[u8],
+        mut transcript: Transcript,
+    ) -> Verdict {
+        let public_input = match PublicInput::from_bytes(public_input) {
+            Ok(public_input) => public_input,
+            Err(e) => return Verdict::MalformedPublicInput(e),
+        };
+
+        // the cheap checks go first
+        let circuit_pub_input = &public_input.circuit_pub_input;
+        if let Err(e) = circuit_pub_input.eff_ecdsa.verify(
+            &public_input.r,
+            public_input.v,
+            &public_input.msg_hash,
+        ) {
+            return Verdict::InvalidEffEcdsaInput(e);
+        }
+        if !self.roots.contains(&circuit_pub_input.merkle_root) {
+            return Verdict::UnknownRoot(circuit_pub_input.merkle_root);
+        }
+
+        // the inputs are handed to Spartan exactly as the JS prover does
+        let inputs = circuit_pub_input
+            .to_bytes()
+            .chunks(32)
+            .map(to_little_endian)
+            .collect::<Vec<[u8; 32]>>();
+        let inputs = match Assignment::new(&inputs) {
+            Ok(inputs) => inputs,
+            Err(_) => return Verdict::MalformedPublicInput(EffEcdsaError::InvalidEncoding),
+        };
+
+        let result = NIZK::from_bytes(proof, &self.circuit)
+            .and_then(|proof| proof.verify(&self.circuit, &inputs, &mut transcript, &self.gens));
+        match result {
+            Ok(()) => Verdict::Valid,
+            Err(e) => Verdict::InvalidProof(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use k256::elliptic_curve::sec1::ToEncodedPoint;
+    use k256::elliptic_curve::Field;
+    use k256::{ProjectivePoint, Scalar};
+    use rand_core::OsRng;
+
+    // a circuit with five public inputs that any input satisfies
+    fn circuit() -> Instance {
+        let (num_cons, num_vars, num_inputs) = (5, 8, 5);
+        let mut one = [0u8; 32];
+        one[0] = 1;
+
+        let input = |i: usize| (i, num_vars + 1 + i, one);
+        let a = (0..num_inputs).map(input).collect::<Vec<_>>();
+        let b = (0..num_inputs)
+            .map(|i| (i, num_vars, one))
+            .collect::<Vec<_>>();
+        Instance::new(num_cons, num_vars, num_inputs, &a, &b, &a).unwrap()
+    }
+
+    fn random_public_input(merkle_root: [u8; 32]) -> PublicInput {
+        let k = Scalar::random(&mut OsRng);
+        let r_point = (ProjectivePoint::GENERATOR * k)
+            .to_affine()
+            .to_encoded_point(false);
+        let r: [u8; 32] = (*r_point.x().unwrap()).into();
+        let v = 27 + (r_point.y().unwrap()[31] & 1);
+        let msg_hash: [u8; 32] = Scalar::random(&mut OsRng).to_bytes().into();
+
+        PublicInput {
+            r,
+            v,
+            circuit_pub_input: CircuitPubInput {
+                merkle_root,
+                eff_ecdsa: EffEcdsaPubInput::compute(&r, v, &msg_hash).unwrap(),
+            },
+            msg_hash,
+        }
+    }
+
+    fn prove(
+        circuit: &Instance,
+        public_input: &PublicInput,
+        transcript: &mut Transcript,
+    ) -> Vec<u8> {
+        let inputs = public_input
+            .circuit_pub_input
+            .to_bytes()
+            .chunks(32)
+            .map(to_little_endian)
+            .collect::<Vec<[u8; 32]>>();
+        let vars = Assignment::new(&[[0u8; 32]; 8]).unwrap();
+        let gens = NIZKGens::new(5, 8, 5);
+
+        let proof = NIZK::prove(
+            circuit,
+            vars,
+            &Assignment::new(&inputs).unwrap(),
+            &gens,
+            transcript,
+        );
+        proof.to_bytes(circuit)
+    }
+
+    #[test]
+    fn check_membership_verifier() {
+        let circuit = circuit();
+        let mut verifier = MembershipVerifier::new(&bincode::serialize(&circuit).unwrap()).unwrap();
+
+        let root = [7u8; 32];
+        let public_input = random_public_input(root);
+        let public_input_bytes = public_input.to_bytes();
+        assert_eq!(
+            PublicInput::from_bytes(&public_input_bytes).unwrap(),
+            public_input
+        );
+
+        let proof = prove(&circuit, &public_input, &mut Transcript::new(DEFAULT_LABEL));
+        assert!(matches!(
+            verifier.verify(&proof, &public_input_bytes),
+            Verdict::UnknownRoot(r) if r == root
+        ));
+
+        verifier.allow_root(root);
+        assert!(verifier.verify(&proof, &public_input_bytes).is_valid());
+
+        // a signature other than the one T and U were computed from
+        let mut tampered = public_input;
+        tampered.msg_hash[0] ^= 1;
+        assert!(matches!(
+            verifier.verify(&proof, &tampered.to_bytes()),
+            Verdict::InvalidEffEcdsaInput(EffEcdsaError::Mismatch)
+        ));
+
+        // consistent public values that the proof was not made for
+        let other = random_public_input(root);
+        assert!(matches!(
+            verifier.verify(&proof, &other.to_bytes()),
+            Verdict::InvalidProof(_)
+        ));
+
+        assert!(matches!(
+            verifier.verify(&proof, &public_input_bytes[1..]),
+            Verdict::MalformedPublicInput(_)
+        ));
+        assert!(matches!(
+            verifier.verify(&proof[1..], &public_input_bytes),
+            Verdict::InvalidProof(_)
+        ));
+
+        verifier.revoke_root(&root);
+        assert!(!verifier.verify(&proof, &public_input_bytes).is_valid());
+    }
+
+    #[test]
+    fn check_membership_verifier_in_context() {
+        let circuit = circuit();
+        let mut verifier = MembershipVerifier::new(&bincode::serialize(&circuit).unwrap()).unwrap();
+
+        let root = [7u8; 32];
+        verifier.allow_root(root);
+        let public_input = random_public_input(root);
+
+        let context = ProofContext::new(b"app").with_chain_id(1);
+        let proof = prove(&circuit, &public_input, &mut context.transcript());
+
+        let public_input = public_input.to_bytes();
+        assert!(verifier
+            .verify_in_context(&proof, &public_input, &context)
+            .is_valid());
+        assert!(!verifier.verify(&proof, &public_input).is_valid());
+        let other = ProofContext::new(b"app").with_chain_id(2);
+        assert!(!verifier
+            .verify_in_context(&proof, &public_input, &other)
+            .is_valid());
+    }
+}
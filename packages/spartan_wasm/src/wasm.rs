@@ -1,12 +1,15 @@
-use byteorder::{LittleEndian, ReadBytesExt};
+use crate::eff_ecdsa::EffEcdsaPubInput;
+use circuit_reader::{load_witness_from_bin, read_circuit, to_spartan_assignments, WireLayout};
 use console_error_panic_hook;
 use ff::PrimeField;
-use libspartan::{Assignment, Instance, NIZKGens, NIZK};
+use libspartan::{Assignment, Instance, NIZKGens, ProofContext, NIZK};
 use merlin::Transcript;
-use poseidon::poseidon_k256::{hash, FieldElement};
+use poseidon::merkle::{IncrementalMerkleTree, MerkleProof};
+use poseidon::poseidon2_k256;
+use poseidon::poseidon_k256::{hash, hash_pairs, FieldElement};
 use secq256k1::{affine::Group, field::BaseField};
-use std::io::{Error, Read};
 use wasm_bindgen::prelude::*;
+use zeroize::Zeroizing;
 
 pub type G1 = secq256k1::AffinePoint;
 pub type F1 = <G1 as Group>::Scalar;
@@ -16,72 +19,319 @@ pub fn init_panic_hook() {
     console_error_panic_hook::set_once();
 }
 
+/// Label of the transcript used by the functions that take no application
+/// context. Their proofs are valid in every application using the circuit.
+pub(crate) const DEFAULT_LABEL: &[u8] = b"nizk_example";
+
 #[wasm_bindgen]
 pub fn prove(circuit: &[u8], vars: &[u8], public_inputs: &[u8]) -> Result<Vec<u8>, JsValue> {
-    let witness = load_witness_from_bin_reader::<F1, _>(vars).unwrap();
-    let witness_bytes = witness
-        .iter()
-        .map(|w| w.to_repr().into())
-        .collect::<Vec<[u8; 32]>>();
+    let (circuit, layout) = read_circuit(circuit).map_err(to_js_error)?;
+    let gens = gens_for(&circuit);
+    prove_inner(
+        &circuit,
+        vars,
+        public_inputs,
+        &gens,
+        layout,
+        Transcript::new(DEFAULT_LABEL),
+        None,
+    )
+}
 
-    let assignment = Assignment::new(&witness_bytes).unwrap();
-    let circuit: Instance = bincode::deserialize(&circuit).unwrap();
+/// Same as `prove`, but binds the proof to an application: `domain` names the
+/// application and `context` holds any further bytes the proof is bound to,
+/// such as a chain id or an external nullifier. The proof only verifies with
+/// `verify_in_context` called with the same `domain` and `context`.
+#[wasm_bindgen]
+pub fn prove_in_context(
+    circuit: &[u8],
+    vars: &[u8],
+    public_inputs: &[u8],
+    domain: &[u8],
+    context: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let (circuit, layout) = read_circuit(circuit).map_err(to_js_error)?;
+    let gens = gens_for(&circuit);
+    let context = ProofContext::new(domain).with_message(context);
+    prove_inner(
+        &circuit,
+        vars,
+        public_inputs,
+        &gens,
+        layout,
+        context.transcript(),
+        None,
+    )
+}
 
-    let num_cons = circuit.inst.get_num_cons();
-    let num_vars = circuit.inst.get_num_vars();
-    let num_inputs = circuit.inst.get_num_inputs();
+/// Same as `prove`, but deterministic: the prover's randomness is derived from
+/// the 32-byte `seed` and the witness instead of being drawn from the
+/// browser's entropy source, so the same arguments always give the same proof.
+#[wasm_bindgen]
+pub fn prove_deterministic(
+    circuit: &[u8],
+    vars: &[u8],
+    public_inputs: &[u8],
+    seed: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let seed: &[u8; 32] = seed
+        .try_into()
+        .map_err(|_| JsValue::from_str("seed must be 32 bytes"))?;
+    let (circuit, layout) = read_circuit(circuit).map_err(to_js_error)?;
+    let gens = gens_for(&circuit);
+    prove_inner(
+        &circuit,
+        vars,
+        public_inputs,
+        &gens,
+        layout,
+        Transcript::new(DEFAULT_LABEL),
+        Some(seed),
+    )
+}
 
-    // produce public parameters
-    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+/// Same as `prove`, but uses public parameters produced by `nizk_gens`
+/// instead of deriving them on every call.
+#[wasm_bindgen]
+pub fn prove_with_gens(
+    circuit: &[u8],
+    vars: &[u8],
+    public_inputs: &[u8],
+    gens: &[u8],
+) -> Result<Vec<u8>, JsValue> {
+    let (circuit, layout) = read_circuit(circuit).map_err(to_js_error)?;
+    let gens = load_gens(&circuit, gens)?;
+    prove_inner(
+        &circuit,
+        vars,
+        public_inputs,
+        &gens,
+        layout,
+        Transcript::new(DEFAULT_LABEL),
+        None,
+    )
+}
 
-    let mut input = Vec::new();
-    for i in 0..num_inputs {
-        input.push(public_inputs[(i * 32)..((i + 1) * 32)].try_into().unwrap());
-    }
-    let input = Assignment::new(&input).unwrap();
+#[wasm_bindgen]
+pub fn verify(circuit: &[u8], proof: &[u8], public_input: &[u8]) -> Result<bool, JsValue> {
+    let circuit: Instance = bincode::deserialize(circuit).map_err(to_js_error)?;
+    let gens = gens_for(&circuit);
+    verify_inner(
+        &circuit,
+        proof,
+        public_input,
+        &gens,
+        Transcript::new(DEFAULT_LABEL),
+    )
+}
 
-    let mut prover_transcript = Transcript::new(b"nizk_example");
+/// Verifies a proof made by `prove_in_context` with the same `domain` and
+/// `context`.
+#[wasm_bindgen]
+pub fn verify_in_context(
+    circuit: &[u8],
+    proof: &[u8],
+    public_input: &[u8],
+    domain: &[u8],
+    context: &[u8],
+) -> Result<bool, JsValue> {
+    let circuit: Instance = bincode::deserialize(circuit).map_err(to_js_error)?;
+    let gens = gens_for(&circuit);
+    let context = ProofContext::new(domain).with_message(context);
+    verify_inner(&circuit, proof, public_input, &gens, context.transcript())
+}
 
-    // produce a proof of satisfiability
-    let proof = NIZK::prove(
+/// Same as `verify`, but uses public parameters produced by `nizk_gens`
+/// instead of deriving them on every call.
+#[wasm_bindgen]
+pub fn verify_with_gens(
+    circuit: &[u8],
+    proof: &[u8],
+    public_input: &[u8],
+    gens: &[u8],
+) -> Result<bool, JsValue> {
+    let circuit: Instance = bincode::deserialize(circuit).map_err(to_js_error)?;
+    let gens = load_gens(&circuit, gens)?;
+    verify_inner(
         &circuit,
-        assignment.clone(),
-        &input,
+        proof,
+        public_input,
         &gens,
-        &mut prover_transcript,
-    );
-
-    Ok(bincode::serialize(&proof).unwrap())
+        Transcript::new(DEFAULT_LABEL),
+    )
 }
 
+/// Derives the public parameters for `circuit` and encodes them so they can
+/// be stored and passed to `prove_with_gens`/`verify_with_gens`.
 #[wasm_bindgen]
-pub fn verify(circuit: &[u8], proof: &[u8], public_input: &[u8]) -> Result<bool, JsValue> {
-    let circuit: Instance = bincode::deserialize(&circuit).unwrap();
-    let proof: NIZK = bincode::deserialize(&proof).unwrap();
+pub fn nizk_gens(circuit: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let circuit: Instance = bincode::deserialize(circuit).map_err(to_js_error)?;
+    Ok(gens_for(&circuit).to_bytes())
+}
 
+fn gens_for(circuit: &Instance) -> NIZKGens {
     let num_cons = circuit.inst.get_num_cons();
     let num_vars = circuit.inst.get_num_vars();
     let num_inputs = circuit.inst.get_num_inputs();
 
-    // produce public parameters
-    let gens = NIZKGens::new(num_cons, num_vars, num_inputs);
+    NIZKGens::new(num_cons, num_vars, num_inputs)
+}
 
-    let mut inputs = Vec::new();
-    for i in 0..num_inputs {
-        inputs.push(public_input[(i * 32)..((i + 1) * 32)].try_into().unwrap());
+fn load_gens(circuit: &Instance, gens: &[u8]) -> Result<NIZKGens, JsValue> {
+    let num_cons = circuit.inst.get_num_cons();
+    let num_vars = circuit.inst.get_num_vars();
+    let num_inputs = circuit.inst.get_num_inputs();
+
+    NIZKGens::from_bytes(gens, num_cons, num_vars, num_inputs)
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+fn prove_inner(
+    circuit: &Instance,
+    vars: &[u8],
+    public_inputs: &[u8],
+    gens: &NIZKGens,
+    layout: Option<WireLayout>,
+    mut prover_transcript: Transcript,
+    seed: Option<&[u8; 32]>,
+) -> Result<Vec<u8>, JsValue> {
+    let num_inputs = circuit.inst.get_num_inputs();
+    let public_inputs = spartan_inputs(public_inputs, num_inputs)?;
+
+    // every copy of the witness is wiped once the proof is made
+    let witness = Zeroizing::new(load_witness_from_bin::<F1, _>(vars).map_err(to_js_error)?);
+    // files written before the layouts number their variables as the circom
+    // wires, which a witness cannot be split into without the layout
+    let layout = layout.ok_or_else(|| {
+        JsValue::from_str(
+            "the circuit file has no wire layout, regenerate it with gen_spartan_inst",
+        )
+    })?;
+    let (assignment, input) = to_spartan_assignments(&layout, &witness).map_err(to_js_error)?;
+
+    // the proof is for the public signals of the witness, which would not
+    // verify with other public inputs
+    let witness_inputs = witness[1..=num_inputs]
+        .iter()
+        .map(|w| to_little_endian(&w.to_repr()));
+    if !witness_inputs.eq(public_inputs) {
+        return Err(JsValue::from_str(
+            "the public inputs do not match the witness",
+        ));
     }
+    // a proof for an unsatisfying witness would not verify either
+    let unsatisfied = circuit
+        .check_sat_detailed(&assignment, &input)
+        .map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
+    if let Some(constraint) = unsatisfied.first() {
+        return Err(JsValue::from_str(&format!(
+            "the witness does not satisfy constraint {}",
+            constraint.index
+        )));
+    }
+
+    // produce a proof of satisfiability
+    let proof = match seed {
+        Some(seed) => NIZK::prove_deterministic(
+            circuit,
+            assignment,
+            &input,
+            gens,
+            &mut prover_transcript,
+            seed,
+            None,
+        ),
+        None => NIZK::prove(circuit, assignment, &input, gens, &mut prover_transcript),
+    };
+
+    Ok(proof.to_bytes(circuit))
+}
 
-    let inputs = Assignment::new(&inputs).unwrap();
+fn verify_inner(
+    circuit: &Instance,
+    proof: &[u8],
+    public_input: &[u8],
+    gens: &NIZKGens,
+    mut verifier_transcript: Transcript,
+) -> Result<bool, JsValue> {
+    let proof = NIZK::from_bytes(proof, circuit).map_err(|e| JsValue::from_str(&e.to_string()))?;
 
-    let mut verifier_transcript = Transcript::new(b"nizk_example");
+    let num_inputs = circuit.inst.get_num_inputs();
+    let inputs = spartan_inputs(public_input, num_inputs)?;
+    let inputs = Assignment::new(&inputs).map_err(|e| JsValue::from_str(&format!("{:?}", e)))?;
 
     let verified = proof
-        .verify(&circuit, &inputs, &mut verifier_transcript, &gens)
+        .verify(circuit, &inputs, &mut verifier_transcript, gens)
         .is_ok();
 
     Ok(verified)
 }
 
+// Spartan reads little-endian scalars, while the public inputs are given as
+// in the JS library, 32 big-endian bytes each
+fn spartan_inputs(public_inputs: &[u8], num_inputs: usize) -> Result<Vec<[u8; 32]>, JsValue> {
+    if public_inputs.len() != num_inputs * 32 {
+        return Err(JsValue::from_str(&format!(
+            "expected {} public inputs",
+            num_inputs
+        )));
+    }
+    Ok(public_inputs
+        .chunks_exact(32)
+        .map(to_little_endian)
+        .collect())
+}
+
+pub(crate) fn to_little_endian(bytes: &[u8]) -> [u8; 32] {
+    let mut le_bytes: [u8; 32] = bytes.try_into().unwrap();
+    le_bytes.reverse();
+    le_bytes
+}
+
+/// Computes the points `T` and `U` of the efficient ECDSA circuit from the
+/// big-endian `r` and `msg_hash` of a signature and its `v`, encoded as
+/// `Tx || Ty || Ux || Uy` with 32 big-endian bytes per coordinate.
+#[wasm_bindgen]
+pub fn eff_ecdsa_pub_input(r: &[u8], v: u8, msg_hash: &[u8]) -> Result<Vec<u8>, JsValue> {
+    let (r, msg_hash) = signature_bytes(r, msg_hash)?;
+    let pub_input =
+        EffEcdsaPubInput::compute(r, v, msg_hash).map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    Ok(pub_input.to_bytes().to_vec())
+}
+
+/// Checks that `pub_input`, encoded as by `eff_ecdsa_pub_input`, holds the
+/// `T` and `U` of the signature with the given `r` and `v` over `msg_hash`.
+#[wasm_bindgen]
+pub fn verify_eff_ecdsa_pub_input(
+    r: &[u8],
+    v: u8,
+    msg_hash: &[u8],
+    pub_input: &[u8],
+) -> Result<bool, JsValue> {
+    let (r, msg_hash) = signature_bytes(r, msg_hash)?;
+    let pub_input: &[u8; 128] = pub_input
+        .try_into()
+        .map_err(|_| JsValue::from_str("public input must be 128 bytes"))?;
+
+    Ok(EffEcdsaPubInput::from_bytes(pub_input)
+        .and_then(|pub_input| pub_input.verify(r, v, msg_hash))
+        .is_ok())
+}
+
+fn signature_bytes<'a>(
+    r: &'a [u8],
+    msg_hash: &'a [u8],
+) -> Result<(&'a [u8; 32], &'a [u8; 32]), JsValue> {
+    let r = r
+        .try_into()
+        .map_err(|_| JsValue::from_str("r must be 32 bytes"))?;
+    let msg_hash = msg_hash
+        .try_into()
+        .map_err(|_| JsValue::from_str("message hash must be 32 bytes"))?;
+    Ok((r, msg_hash))
+}
+
 #[wasm_bindgen]
 pub fn poseidon(input_bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
     assert_eq!(input_bytes.len(), 64);
@@ -96,82 +346,195 @@ pub fn poseidon(input_bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
     Ok(result.to_bytes().to_vec())
 }
 
-// Copied from Nova Scotia
-pub fn read_field<R: Read, Fr: PrimeField>(mut reader: R) -> Result<Fr, Error> {
-    let mut repr = Fr::zero().to_repr();
-    for digit in repr.as_mut().iter_mut() {
-        // TODO: may need to reverse order?
-        *digit = reader.read_u8()?;
+/// Hashes many pairs in one call: `input_bytes` holds 64-byte pairs encoded
+/// as for `poseidon`, and the 32-byte hashes are returned in the same order
+#[wasm_bindgen]
+pub fn poseidon_batch(input_bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
+    hash_pair_bytes(input_bytes, hash_pairs)
+}
+
+/// Same as `poseidon`, with the width-3 Poseidon2 permutation instead
+#[wasm_bindgen]
+pub fn poseidon2(input_bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
+    if input_bytes.len() != 64 {
+        return Err(JsValue::from_str("input must be 64 bytes"));
+    }
+    let input = [
+        field_element(&input_bytes[..32])?,
+        field_element(&input_bytes[32..])?,
+    ];
+
+    Ok(poseidon2_k256::hash(&input).to_bytes().to_vec())
+}
+
+/// Same as `poseidon_batch`, with the width-3 Poseidon2 permutation instead
+#[wasm_bindgen]
+pub fn poseidon2_batch(input_bytes: &[u8]) -> Result<Vec<u8>, JsValue> {
+    hash_pair_bytes(input_bytes, poseidon2_k256::hash_pairs)
+}
+
+fn hash_pair_bytes(
+    input_bytes: &[u8],
+    hash_pairs: fn(&[[FieldElement; 2]]) -> Vec<FieldElement>,
+) -> Result<Vec<u8>, JsValue> {
+    if input_bytes.len() % 64 != 0 {
+        return Err(JsValue::from_str("inputs must be 64-byte pairs"));
     }
-    let fr = Fr::from_repr(repr).unwrap();
-    Ok(fr)
+    let pairs = input_bytes
+        .chunks_exact(64)
+        .map(|pair| Ok([field_element(&pair[..32])?, field_element(&pair[32..])?]))
+        .collect::<Result<Vec<_>, JsValue>>()?;
+
+    Ok(hash_pairs(&pairs)
+        .iter()
+        .flat_map(|hash| hash.to_bytes())
+        .collect())
+}
+
+/// Poseidon Merkle tree of the membership circuits, in place of zk-kit's
+/// `IncrementalMerkleTree`. Leaves and roots are 32-byte field elements,
+/// encoded as for `poseidon`.
+#[wasm_bindgen]
+pub struct PoseidonTree {
+    inner: IncrementalMerkleTree,
 }
 
-pub fn load_witness_from_bin_reader<Fr: PrimeField, R: Read>(
-    mut reader: R,
-) -> Result<Vec<Fr>, Error> {
-    let mut wtns_header = [0u8; 4];
-    reader.read_exact(&mut wtns_header)?;
-    if wtns_header != [119, 116, 110, 115] {
-        // ruby -e 'p "wtns".bytes' => [119, 116, 110, 115]
-        panic!("invalid file header");
+#[wasm_bindgen]
+impl PoseidonTree {
+    #[wasm_bindgen(constructor)]
+    pub fn new(depth: usize) -> Result<PoseidonTree, JsValue> {
+        let inner = IncrementalMerkleTree::new(depth).map_err(to_js_error)?;
+        Ok(PoseidonTree { inner })
     }
-    let version = reader.read_u32::<LittleEndian>()?;
-    // println!("wtns version {}", version);
-    if version > 2 {
-        panic!("unsupported file version");
+
+    /// Builds a tree from the concatenation of its leaves
+    pub fn from_leaves(depth: usize, leaves: &[u8]) -> Result<PoseidonTree, JsValue> {
+        let chunks = leaves.chunks_exact(32);
+        if !chunks.remainder().is_empty() {
+            return Err(JsValue::from_str("leaves must be 32 bytes each"));
+        }
+        let leaves = chunks.map(field_element).collect::<Result<Vec<_>, _>>()?;
+
+        let inner = IncrementalMerkleTree::from_leaves(depth, &leaves).map_err(to_js_error)?;
+        Ok(PoseidonTree { inner })
     }
-    let num_sections = reader.read_u32::<LittleEndian>()?;
-    if num_sections != 2 {
-        panic!("invalid num sections");
+
+    pub fn insert(&mut self, leaf: &[u8]) -> Result<usize, JsValue> {
+        self.inner.insert(field_element(leaf)?).map_err(to_js_error)
     }
-    // read the first section
-    let sec_type = reader.read_u32::<LittleEndian>()?;
-    if sec_type != 1 {
-        panic!("invalid section type");
+
+    pub fn update(&mut self, index: usize, leaf: &[u8]) -> Result<(), JsValue> {
+        self.inner
+            .update(index, field_element(leaf)?)
+            .map_err(to_js_error)
     }
-    let sec_size = reader.read_u64::<LittleEndian>()?;
-    if sec_size != 4 + 32 + 4 {
-        panic!("invalid section len")
+
+    pub fn delete(&mut self, index: usize) -> Result<(), JsValue> {
+        self.inner.delete(index).map_err(to_js_error)
     }
-    let field_size = reader.read_u32::<LittleEndian>()?;
-    if field_size != 32 {
-        panic!("invalid field byte size");
+
+    pub fn root(&self) -> Vec<u8> {
+        self.inner.root().to_bytes().to_vec()
     }
-    let mut prime = vec![0u8; field_size as usize];
-    reader.read_exact(&mut prime)?;
-    // if prime != hex!("010000f093f5e1439170b97948e833285d588181b64550b829a031e1724e6430") {
-    //     bail!("invalid curve prime {:?}", prime);
-    // }
-    let witness_len = reader.read_u32::<LittleEndian>()?;
-    // println!("witness len {}", witness_len);
-    let sec_type = reader.read_u32::<LittleEndian>()?;
-    if sec_type != 2 {
-        panic!("invalid section type");
+
+    /// Returns the concatenation of the leaves, deleted ones being zero
+    pub fn leaves(&self) -> Vec<u8> {
+        self.inner
+            .leaves()
+            .iter()
+            .flat_map(|leaf| leaf.to_bytes())
+            .collect()
     }
-    let sec_size = reader.read_u64::<LittleEndian>()?;
-    if sec_size != (witness_len * field_size) as u64 {
-        panic!("invalid witness section size {}", sec_size);
+
+    pub fn index_of(&self, leaf: &[u8]) -> Result<Option<usize>, JsValue> {
+        Ok(self.inner.index_of(&field_element(leaf)?))
     }
-    let mut result = Vec::with_capacity(witness_len as usize);
-    for _ in 0..witness_len {
-        result.push(read_field::<&mut R, Fr>(&mut reader)?);
+
+    /// Returns `root || leaf || siblings || path indices`, with one byte per
+    /// path index
+    pub fn create_proof(&self, index: usize) -> Result<Vec<u8>, JsValue> {
+        let proof = self.inner.create_proof(index).map_err(to_js_error)?;
+
+        let mut bytes = Vec::with_capacity(64 + 33 * proof.siblings.len());
+        bytes.extend_from_slice(&proof.root.to_bytes());
+        bytes.extend_from_slice(&proof.leaf.to_bytes());
+        for sibling in &proof.siblings {
+            bytes.extend_from_slice(&sibling.to_bytes());
+        }
+        bytes.extend_from_slice(&proof.path_indices);
+        Ok(bytes)
     }
-    Ok(result)
+
+    /// Checks a proof encoded by `create_proof` against the current root
+    pub fn verify_proof(&self, proof: &[u8]) -> Result<bool, JsValue> {
+        let depth = self.inner.depth();
+        if proof.len() != 64 + 33 * depth {
+            return Ok(false);
+        }
+
+        let siblings_end = 64 + 32 * depth;
+        let proof = MerkleProof {
+            root: field_element(&proof[..32])?,
+            leaf: field_element(&proof[32..64])?,
+            siblings: proof[64..siblings_end]
+                .chunks(32)
+                .map(field_element)
+                .collect::<Result<Vec<_>, _>>()?,
+            path_indices: proof[siblings_end..].to_vec(),
+        };
+        Ok(self.inner.verify_proof(&proof))
+    }
+}
+
+fn field_element(bytes: &[u8]) -> Result<FieldElement, JsValue> {
+    let bytes: &[u8; 32] = bytes
+        .try_into()
+        .map_err(|_| JsValue::from_str("field elements must be 32 bytes"))?;
+    Option::from(FieldElement::from_bytes(bytes))
+        .ok_or_else(|| JsValue::from_str("invalid field element"))
+}
+
+fn to_js_error(e: impl std::fmt::Display) -> JsValue {
+    JsValue::from_str(&e.to_string())
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use hex_literal::hex;
     use std::{env::current_dir, fs};
 
+    #[test]
+    fn check_eff_ecdsa_pub_input() {
+        // the values of efficientEcdsa.test.ts in the JS library
+        let r = hex!("5d5d43bec648296f5ef4b72c269bfde291fc0ed13bfc7e59c56b6c74aa9c932e");
+        let msg_hash = hex!("8e05c70f46dbc3dda34547fc23ac835d728001bac55db9bd122d77d10d294431");
+        let expected = hex!(
+            "2af2c62145d39e7dd285b55d5c51963baa31b58e0c1b8b7e1de9351840917581"
+            "a662125801a14f2301cfb92965d5ba7a63765e6477a14ecd8e2d4f0b1353b83b"
+            "7641bcce6a558dfa5018fe45da507ff49cc09aca5c02cceddfd845edebea6682"
+            "eaeeff65d77a9334606577c4696178497a94e775573553267eb856bee4c54a6f"
+        );
+
+        let pub_input = eff_ecdsa_pub_input(&r, 28, &msg_hash).unwrap();
+        assert_eq!(pub_input, expected);
+        assert!(verify_eff_ecdsa_pub_input(&r, 28, &msg_hash, &pub_input).unwrap());
+        assert!(!verify_eff_ecdsa_pub_input(&r, 27, &msg_hash, &pub_input).unwrap());
+
+        let mut tampered = pub_input;
+        tampered[0] ^= 1;
+        assert!(!verify_eff_ecdsa_pub_input(&r, 28, &msg_hash, &tampered).unwrap());
+    }
+
     #[test]
     fn check_nizk() {
         let root = current_dir().unwrap();
         let circuit = fs::read(root.join("test_circuit/test_circuit.circuit")).unwrap();
         let vars = fs::read(root.join("test_circuit/witness.wtns")).unwrap();
 
-        let public_inputs = [F1::from(1u64), F1::from(1u64), F1::from(1u64)]
+        // `c`, `a` and `b` of `c <== a * b[0] * b[1]`
+        let public_inputs = [2u64, 1, 1, 2]
+            .map(F1::from)
             .iter()
             .map(|w| w.to_repr())
             .flatten()
@@ -191,6 +554,110 @@ mod test {
         );
 
         assert!(result.unwrap());
+
+        // the public inputs are bound to the circuit
+        let mut other_inputs = public_inputs.clone();
+        other_inputs[31] = 3;
+        assert!(!verify(&circuit, &proof, &other_inputs).unwrap());
+    }
+
+    #[test]
+    fn check_nizk_with_gens() {
+        let root = current_dir().unwrap();
+        let circuit = fs::read(root.join("test_circuit/test_circuit.circuit")).unwrap();
+        let vars = fs::read(root.join("test_circuit/witness.wtns")).unwrap();
+
+        let public_inputs = [2u64, 1, 1, 2]
+            .map(F1::from)
+            .iter()
+            .flat_map(|w| w.to_repr())
+            .collect::<Vec<u8>>();
+
+        let gens = nizk_gens(circuit.as_slice()).unwrap();
+
+        let proof = prove_with_gens(
+            circuit.as_slice(),
+            vars.as_slice(),
+            public_inputs.as_slice(),
+            gens.as_slice(),
+        )
+        .unwrap();
+
+        // proofs made with stored gens verify with freshly derived ones
+        let result = verify(
+            circuit.as_slice(),
+            proof.as_slice(),
+            public_inputs.as_slice(),
+        );
+
+        assert!(result.unwrap());
+    }
+
+    #[test]
+    fn check_nizk_deterministic() {
+        let root = current_dir().unwrap();
+        let circuit = fs::read(root.join("test_circuit/test_circuit.circuit")).unwrap();
+        let vars = fs::read(root.join("test_circuit/witness.wtns")).unwrap();
+
+        let public_inputs = [2u64, 1, 1, 2]
+            .map(F1::from)
+            .iter()
+            .flat_map(|w| w.to_repr())
+            .collect::<Vec<u8>>();
+
+        let prove = |seed: &[u8]| prove_deterministic(&circuit, &vars, &public_inputs, seed);
+        let proof = prove(&[7u8; 32]).unwrap();
+        assert_eq!(proof, prove(&[7u8; 32]).unwrap());
+
+        assert!(verify(&circuit, &proof, &public_inputs).unwrap());
+    }
+
+    #[test]
+    fn check_nizk_in_context() {
+        let root = current_dir().unwrap();
+        let circuit = fs::read(root.join("test_circuit/test_circuit.circuit")).unwrap();
+        let vars = fs::read(root.join("test_circuit/witness.wtns")).unwrap();
+
+        let public_inputs = [2u64, 1, 1, 2]
+            .map(F1::from)
+            .iter()
+            .flat_map(|w| w.to_repr())
+            .collect::<Vec<u8>>();
+
+        let proof = prove_in_context(&circuit, &vars, &public_inputs, b"app", b"poll-1").unwrap();
+        let verify_ctx = |domain: &[u8], context: &[u8]| {
+            verify_in_context(&circuit, &proof, &public_inputs, domain, context).unwrap()
+        };
+
+        assert!(verify_ctx(b"app", b"poll-1"));
+        assert!(!verify_ctx(b"app", b"poll-2"));
+        assert!(!verify_ctx(b"other app", b"poll-1"));
+        assert!(!verify(&circuit, &proof, &public_inputs).unwrap());
+    }
+
+    #[test]
+    fn check_nizk_optimized() {
+        let root = current_dir().unwrap();
+        let r1cs = root.join("test_circuit/linear_circuit.r1cs");
+        let (optimized, stats) = circuit_reader::load_optimized_circuit(&r1cs).unwrap();
+        assert!(stats.after.num_cons < stats.before.num_cons);
+        // the witness computed for the original circuit
+        let vars = fs::read(root.join("test_circuit/linear_witness.wtns")).unwrap();
+
+        // `c` and `a` of `c <== (2 * (a + 1)^2 + a + 1) * b`
+        let public_inputs = [108u64, 3]
+            .map(F1::from)
+            .iter()
+            .flat_map(|w| w.to_repr())
+            .collect::<Vec<u8>>();
+
+        for circuit in [
+            optimized.to_bytes(),
+            circuit_reader::load_circuit(&r1cs).unwrap().to_bytes(),
+        ] {
+            let proof = prove(&circuit, &vars, &public_inputs).unwrap();
+            assert!(verify(&circuit, &proof, &public_inputs).unwrap());
+        }
     }
 
     #[test]
@@ -220,4 +687,75 @@ mod test {
             ]
         )
     }
+
+    #[test]
+    fn check_poseidon_batch() {
+        let inputs = (0..6u64)
+            .flat_map(|i| FieldElement::from(i * 1000 + 1).to_bytes())
+            .collect::<Vec<u8>>();
+        let hashes = poseidon_batch(&inputs).unwrap();
+
+        assert_eq!(hashes.len(), 3 * 32);
+        for (pair, hash) in inputs.chunks(64).zip(hashes.chunks(32)) {
+            assert_eq!(poseidon(pair).unwrap(), hash);
+        }
+        assert!(poseidon_batch(&[]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn check_poseidon2() {
+        let input = [1234567u64, 109987].map(FieldElement::from);
+        let input_bytes = [input[0].to_bytes(), input[1].to_bytes()].concat();
+
+        assert_eq!(
+            poseidon2(&input_bytes).unwrap(),
+            poseidon2_k256::hash(&input).to_bytes()
+        );
+        assert_ne!(
+            poseidon2(&input_bytes).unwrap(),
+            poseidon(&input_bytes).unwrap()
+        );
+        assert_eq!(
+            poseidon2_batch(&[input_bytes.clone(), input_bytes.clone()].concat()).unwrap(),
+            [
+                poseidon2(&input_bytes).unwrap(),
+                poseidon2(&input_bytes).unwrap()
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn check_poseidon_tree() {
+        let leaves = (1..=5u64).map(FieldElement::from).collect::<Vec<_>>();
+        let leaves_bytes = leaves
+            .iter()
+            .flat_map(|leaf| leaf.to_bytes())
+            .collect::<Vec<u8>>();
+
+        let mut tree = PoseidonTree::new(20).unwrap();
+        for leaf in leaves_bytes.chunks(32) {
+            tree.insert(leaf).unwrap();
+        }
+        let bulk = PoseidonTree::from_leaves(20, &leaves_bytes).unwrap();
+        assert_eq!(tree.root(), bulk.root());
+        assert_eq!(tree.leaves(), leaves_bytes);
+        assert_eq!(
+            tree.root(),
+            IncrementalMerkleTree::from_leaves(20, &leaves)
+                .unwrap()
+                .root()
+                .to_bytes()
+        );
+        assert_eq!(tree.index_of(&leaves_bytes[64..96]).unwrap(), Some(2));
+
+        let proof = tree.create_proof(2).unwrap();
+        assert!(tree.verify_proof(&proof).unwrap());
+        let mut tampered = proof.clone();
+        tampered[40] ^= 1;
+        assert!(!tree.verify_proof(&tampered).unwrap());
+
+        tree.delete(2).unwrap();
+        assert!(!tree.verify_proof(&proof).unwrap());
+    }
 }
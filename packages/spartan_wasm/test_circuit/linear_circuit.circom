@@ -0,0 +1,20 @@
+pragma circom 2.1.2;
+
+// The constraints of `linear_circuit.r1cs`, where `x` and `z` are linear
+// combinations that the optimizer of circuit_reader removes
+template LinearCircuit() {
+    signal input a;
+    signal input b;
+    signal output c;
+
+    signal x;
+    signal y;
+    signal z;
+    x <== a + 1;
+    y <== x * x;
+    z <== 2 * y + x;
+
+    c <== z * b;
+}
+
+component main { public [ a ] } = LinearCircuit();
@@ -1,2 +1,2 @@
 #!/bin/bash 
-sh ./scripts/compile_circuit.sh addr_membership 5
\ No newline at end of file
+sh ./scripts/compile_circuit.sh addr_membership
\ No newline at end of file